@@ -0,0 +1,602 @@
+// src-tauri/src/a2s.rs
+//
+// Steam A2S query client (A2S_INFO, A2S_PLAYER, A2S_RULES over UDP). Player counts
+// parsed from `ShooterGame.log` drift after a missed line or a log rotation; the query
+// port tells us what the server is actually advertising. Each running profile gets a
+// poller that emits `server-query` with the latest status.
+//
+// Protocol reference: https://developer.valvesoftware.com/wiki/Server_queries
+
+use chrono::Local;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tauri::{Emitter, Window};
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// Upper bound on split responses; real servers send a handful at most.
+const MAX_SPLIT_PACKETS: u8 = 32;
+
+const SIMPLE_HEADER: i32 = -1;
+const SPLIT_HEADER: i32 = -2;
+
+const A2S_INFO: u8 = 0x54;
+const A2S_PLAYER: u8 = 0x55;
+const A2S_RULES: u8 = 0x56;
+const S2C_CHALLENGE: u8 = 0x41;
+const S2A_INFO: u8 = 0x49;
+const S2A_PLAYER: u8 = 0x44;
+const S2A_RULES: u8 = 0x45;
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct A2sInfo {
+    pub protocol: u8,
+    pub name: String,
+    pub map: String,
+    pub folder: String,
+    pub game: String,
+    pub app_id: u16,
+    pub players: u8,
+    pub max_players: u8,
+    pub bots: u8,
+    pub server_type: char,
+    pub environment: char,
+    pub password_protected: bool,
+    pub vac: bool,
+    pub version: String,
+    pub port: Option<u16>,
+    pub steam_id: Option<u64>,
+    pub keywords: Option<String>,
+    pub game_id: Option<u64>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct A2sPlayer {
+    pub name: String,
+    pub score: i32,
+    pub duration_seconds: f32,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct A2sRule {
+    pub name: String,
+    pub value: String,
+}
+
+/// What the `server-query` event carries for one poll.
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerQueryStatus {
+    pub address: String,
+    /// True when the server answered A2S_INFO, i.e. it is listed and joinable.
+    pub advertising: bool,
+    pub info: Option<A2sInfo>,
+    pub players: Option<Vec<A2sPlayer>>,
+    pub rules: Option<Vec<A2sRule>>,
+    pub error: Option<String>,
+    pub queried_at: String,
+}
+
+/// Little-endian reader over a response payload.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or("Truncated A2S response")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.pos)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// Null-terminated string; servers are not strict about UTF-8.
+    fn string(&mut self) -> Result<String, String> {
+        let rest = &self.data[self.pos.min(self.data.len())..];
+        let end = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("Unterminated string in A2S response")?;
+        self.pos += end + 1;
+        Ok(String::from_utf8_lossy(&rest[..end]).to_string())
+    }
+}
+
+fn request(kind: u8, challenge: Option<[u8; 4]>) -> Vec<u8> {
+    let mut packet = SIMPLE_HEADER.to_le_bytes().to_vec();
+    packet.push(kind);
+    if kind == A2S_INFO {
+        packet.extend_from_slice(b"Source Engine Query\0");
+        if let Some(challenge) = challenge {
+            packet.extend_from_slice(&challenge);
+        }
+    } else {
+        // Player and rule requests must carry a challenge; -1 asks for one
+        packet.extend_from_slice(&challenge.unwrap_or([0xFF; 4]));
+    }
+    packet
+}
+
+async fn recv(socket: &UdpSocket) -> Result<Vec<u8>, String> {
+    let mut buf = vec![0u8; 65535];
+    let len = tokio::time::timeout(QUERY_TIMEOUT, socket.recv(&mut buf))
+        .await
+        .map_err(|_| "No response from the query port".to_string())?
+        .map_err(|e| format!("Query failed: {}", e))?;
+    buf.truncate(len);
+    Ok(buf)
+}
+
+/// Receives one response, reassembling split (multi-packet) responses. Returns the
+/// payload after the -1 header.
+async fn recv_response(socket: &UdpSocket) -> Result<Vec<u8>, String> {
+    let first = recv(socket).await?;
+    let mut reader = Reader::new(&first);
+    match reader.i32()? {
+        SIMPLE_HEADER => return Ok(first[4..].to_vec()),
+        SPLIT_HEADER => {}
+        other => return Err(format!("Unexpected A2S packet header {:#x}", other)),
+    }
+
+    let mut packet = first;
+    let mut parts: Vec<Option<Vec<u8>>> = vec![];
+    let mut response_id = None;
+    loop {
+        let mut reader = Reader::new(&packet);
+        if reader.i32()? != SPLIT_HEADER {
+            return Err("Mixed split and single A2S packets".to_string());
+        }
+        let id = reader.i32()?;
+        if id as u32 & 0x8000_0000 != 0 {
+            return Err("Compressed A2S responses are not supported".to_string());
+        }
+        let total = reader.u8()?;
+        let number = reader.u8()?;
+        let _size = reader.u16()?;
+        if total == 0 || total > MAX_SPLIT_PACKETS || number >= total {
+            return Err("Malformed split A2S packet".to_string());
+        }
+        match response_id {
+            None => {
+                response_id = Some(id);
+                parts = vec![None; total as usize];
+            }
+            Some(expected) if expected != id || parts.len() != total as usize => {
+                return Err("Split A2S packets from different responses".to_string());
+            }
+            Some(_) => {}
+        }
+        parts[number as usize] = Some(packet[reader.pos..].to_vec());
+
+        if parts.iter().all(Option::is_some) {
+            break;
+        }
+        packet = recv(socket).await?;
+    }
+
+    let payload: Vec<u8> = parts.into_iter().flatten().flatten().collect();
+    let mut reader = Reader::new(&payload);
+    if reader.i32()? != SIMPLE_HEADER {
+        return Err("Malformed reassembled A2S response".to_string());
+    }
+    Ok(payload[4..].to_vec())
+}
+
+/// Sends a request, answering a challenge if the server issues one, and returns the
+/// response body after the type byte `expected`.
+async fn exchange(addr: SocketAddr, kind: u8, expected: u8) -> Result<Vec<u8>, String> {
+    let local: SocketAddr = match addr {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (std::net::Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(local)
+        .await
+        .map_err(|e| format!("Failed to open query socket: {}", e))?;
+    socket
+        .connect(addr)
+        .await
+        .map_err(|e| format!("Failed to reach {}: {}", addr, e))?;
+
+    let mut challenge = None;
+    // One round for the challenge, one for the answer; some servers re-challenge once
+    for _ in 0..3 {
+        socket
+            .send(&request(kind, challenge))
+            .await
+            .map_err(|e| format!("Query failed: {}", e))?;
+        let response = recv_response(&socket).await?;
+        match response.first() {
+            Some(&S2C_CHALLENGE) => {
+                let bytes = response.get(1..5).ok_or("Truncated A2S challenge")?;
+                challenge = Some(bytes.try_into().unwrap());
+            }
+            Some(&t) if t == expected => return Ok(response[1..].to_vec()),
+            Some(&t) => return Err(format!("Unexpected A2S response type {:#x}", t)),
+            None => return Err("Empty A2S response".to_string()),
+        }
+    }
+    Err("Server kept answering with a new challenge".to_string())
+}
+
+fn parse_info(body: &[u8]) -> Result<A2sInfo, String> {
+    let mut r = Reader::new(body);
+    let protocol = r.u8()?;
+    let name = r.string()?;
+    let map = r.string()?;
+    let folder = r.string()?;
+    let game = r.string()?;
+    let app_id = r.u16()?;
+    let players = r.u8()?;
+    let max_players = r.u8()?;
+    let bots = r.u8()?;
+    let server_type = r.u8()? as char;
+    let environment = r.u8()? as char;
+    let password_protected = r.u8()? != 0;
+    let vac = r.u8()? != 0;
+    let version = r.string()?;
+
+    let mut info = A2sInfo {
+        protocol,
+        name,
+        map,
+        folder,
+        game,
+        app_id,
+        players,
+        max_players,
+        bots,
+        server_type,
+        environment,
+        password_protected,
+        vac,
+        version,
+        port: None,
+        steam_id: None,
+        keywords: None,
+        game_id: None,
+    };
+
+    // Extra data flag; optional fields follow in this order
+    if r.remaining() > 0 {
+        let edf = r.u8()?;
+        if edf & 0x80 != 0 {
+            info.port = Some(r.u16()?);
+        }
+        if edf & 0x10 != 0 {
+            info.steam_id = Some(r.u64()?);
+        }
+        if edf & 0x40 != 0 {
+            let _spectator_port = r.u16()?;
+            let _spectator_name = r.string()?;
+        }
+        if edf & 0x20 != 0 {
+            info.keywords = Some(r.string()?);
+        }
+        if edf & 0x01 != 0 {
+            info.game_id = Some(r.u64()?);
+        }
+    }
+    Ok(info)
+}
+
+fn parse_players(body: &[u8]) -> Result<Vec<A2sPlayer>, String> {
+    let mut r = Reader::new(body);
+    let count = r.u8()?;
+    let mut players = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let _index = r.u8()?;
+        players.push(A2sPlayer {
+            name: r.string()?,
+            score: r.i32()?,
+            duration_seconds: r.f32()?,
+        });
+    }
+    Ok(players)
+}
+
+fn parse_rules(body: &[u8]) -> Result<Vec<A2sRule>, String> {
+    let mut r = Reader::new(body);
+    let count = r.u16()?;
+    let mut rules = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        // Some servers cut the list short when it does not fit; keep what arrived
+        if r.remaining() == 0 {
+            break;
+        }
+        rules.push(A2sRule {
+            name: r.string()?,
+            value: r.string()?,
+        });
+    }
+    Ok(rules)
+}
+
+pub async fn query_info(addr: SocketAddr) -> Result<A2sInfo, String> {
+    parse_info(&exchange(addr, A2S_INFO, S2A_INFO).await?)
+}
+
+pub async fn query_players(addr: SocketAddr) -> Result<Vec<A2sPlayer>, String> {
+    parse_players(&exchange(addr, A2S_PLAYER, S2A_PLAYER).await?)
+}
+
+pub async fn query_rules(addr: SocketAddr) -> Result<Vec<A2sRule>, String> {
+    parse_rules(&exchange(addr, A2S_RULES, S2A_RULES).await?)
+}
+
+/// Runs all three queries. A server that does not answer A2S_INFO is reported as not
+/// advertising; player and rule failures only leave those fields empty.
+pub async fn query_status(addr: SocketAddr) -> ServerQueryStatus {
+    let queried_at = Local::now().to_rfc3339();
+    match query_info(addr).await {
+        Ok(info) => ServerQueryStatus {
+            address: addr.to_string(),
+            advertising: true,
+            info: Some(info),
+            players: query_players(addr).await.ok(),
+            rules: query_rules(addr).await.ok(),
+            error: None,
+            queried_at,
+        },
+        Err(e) => ServerQueryStatus {
+            address: addr.to_string(),
+            advertising: false,
+            info: None,
+            players: None,
+            rules: None,
+            error: Some(e),
+            queried_at,
+        },
+    }
+}
+
+/// Where to query a server bound to `bind_ip` (all interfaces when `None`).
+pub fn query_address(bind_ip: Option<&str>, query_port: u16) -> SocketAddr {
+    let ip = bind_ip
+        .and_then(|ip| ip.parse().ok())
+        .filter(|ip: &IpAddr| !ip.is_unspecified())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    SocketAddr::new(ip, query_port)
+}
+
+/// Polls `addr` until `cancellation_token` fires, emitting `server-query` each round.
+pub fn spawn_poller(
+    window: Window,
+    profile_id: String,
+    addr: SocketAddr,
+    cancellation_token: CancellationToken,
+) {
+    tokio::spawn(async move {
+        loop {
+            let status = tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                status = query_status(addr) => status,
+            };
+            let _ = window.emit(
+                "server-query",
+                serde_json::json!({ "profile_id": profile_id, "status": status }),
+            );
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHALLENGE: [u8; 4] = [0x11, 0x22, 0x33, 0x44];
+
+    fn cstr(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(s.as_bytes());
+        out.push(0);
+    }
+
+    fn info_body() -> Vec<u8> {
+        let mut body = SIMPLE_HEADER.to_le_bytes().to_vec();
+        body.push(S2A_INFO);
+        body.push(17);
+        cstr(&mut body, "My ASA Server");
+        cstr(&mut body, "TheIsland_WP");
+        cstr(&mut body, "ark_survival_ascended");
+        cstr(&mut body, "ARK: Survival Ascended");
+        body.extend_from_slice(&0u16.to_le_bytes());
+        body.extend_from_slice(&[3, 70, 0, b'd', b'w', 0, 1]);
+        cstr(&mut body, "v52.1");
+        body.push(0x80 | 0x20);
+        body.extend_from_slice(&7777u16.to_le_bytes());
+        cstr(&mut body, "custom,pve");
+        body
+    }
+
+    fn players_body() -> Vec<u8> {
+        let mut body = SIMPLE_HEADER.to_le_bytes().to_vec();
+        body.push(S2A_PLAYER);
+        body.push(2);
+        for (i, name) in ["Alice", "Bob"].iter().enumerate() {
+            body.push(i as u8);
+            cstr(&mut body, name);
+            body.extend_from_slice(&(i as i32 * 10).to_le_bytes());
+            body.extend_from_slice(&120.5f32.to_le_bytes());
+        }
+        body
+    }
+
+    fn rules_body() -> Vec<u8> {
+        let mut body = SIMPLE_HEADER.to_le_bytes().to_vec();
+        body.push(S2A_RULES);
+        body.extend_from_slice(&200u16.to_le_bytes());
+        for i in 0..200 {
+            cstr(&mut body, &format!("RULE_{}", i));
+            cstr(&mut body, &format!("value {}", i));
+        }
+        body
+    }
+
+    /// Splits a response into Source-style split packets.
+    fn split(body: &[u8], chunk: usize) -> Vec<Vec<u8>> {
+        let chunks: Vec<&[u8]> = body.chunks(chunk).collect();
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                let mut packet = SPLIT_HEADER.to_le_bytes().to_vec();
+                packet.extend_from_slice(&1234i32.to_le_bytes());
+                packet.push(chunks.len() as u8);
+                packet.push(i as u8);
+                packet.extend_from_slice(&1248u16.to_le_bytes());
+                packet.extend_from_slice(part);
+                packet
+            })
+            .collect()
+    }
+
+    /// A fake A2S server: every request without the right challenge gets one; rules
+    /// are answered with split packets sent out of order.
+    async fn fake_responder() -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 2048];
+            loop {
+                let (len, peer) = socket.recv_from(&mut buf).await.unwrap();
+                let request = &buf[..len];
+                let kind = request[4];
+                let challenged = request.ends_with(&CHALLENGE);
+                let responses = if !challenged {
+                    let mut packet = SIMPLE_HEADER.to_le_bytes().to_vec();
+                    packet.push(S2C_CHALLENGE);
+                    packet.extend_from_slice(&CHALLENGE);
+                    vec![packet]
+                } else {
+                    match kind {
+                        A2S_INFO => vec![info_body()],
+                        A2S_PLAYER => vec![players_body()],
+                        A2S_RULES => {
+                            let mut packets = split(&rules_body(), 1000);
+                            packets.reverse();
+                            packets
+                        }
+                        _ => vec![],
+                    }
+                };
+                for packet in responses {
+                    socket.send_to(&packet, peer).await.unwrap();
+                }
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn info_answers_challenge_and_reads_extra_data() {
+        let addr = fake_responder().await;
+        let info = query_info(addr).await.unwrap();
+        assert_eq!(info.name, "My ASA Server");
+        assert_eq!(info.map, "TheIsland_WP");
+        assert_eq!(info.players, 3);
+        assert_eq!(info.max_players, 70);
+        assert_eq!(info.version, "v52.1");
+        assert_eq!(info.server_type, 'd');
+        assert!(info.vac);
+        assert_eq!(info.port, Some(7777));
+        assert_eq!(info.keywords.as_deref(), Some("custom,pve"));
+        assert_eq!(info.steam_id, None);
+    }
+
+    #[tokio::test]
+    async fn players_are_parsed() {
+        let addr = fake_responder().await;
+        let players = query_players(addr).await.unwrap();
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[1].name, "Bob");
+        assert_eq!(players[1].score, 10);
+        assert_eq!(players[0].duration_seconds, 120.5);
+    }
+
+    #[tokio::test]
+    async fn split_rules_are_reassembled_in_order() {
+        let addr = fake_responder().await;
+        let rules = query_rules(addr).await.unwrap();
+        assert_eq!(rules.len(), 200);
+        assert_eq!(rules[0].name, "RULE_0");
+        assert_eq!(rules[199].value, "value 199");
+    }
+
+    #[tokio::test]
+    async fn status_reports_advertising() {
+        let addr = fake_responder().await;
+        let status = query_status(addr).await;
+        assert!(status.advertising);
+        assert_eq!(status.players.map(|p| p.len()), Some(2));
+        assert_eq!(status.rules.map(|r| r.len()), Some(200));
+    }
+
+    #[tokio::test]
+    async fn silent_port_is_not_advertising() {
+        // Bound but never answers
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let status = query_status(socket.local_addr().unwrap()).await;
+        assert!(!status.advertising);
+        assert!(status.error.is_some());
+    }
+
+    #[test]
+    fn truncated_info_is_an_error() {
+        assert!(parse_info(&info_body()[5..20]).is_err());
+    }
+
+    #[test]
+    fn query_address_defaults_to_localhost() {
+        assert_eq!(
+            query_address(None, 27015),
+            "127.0.0.1:27015".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            query_address(Some("0.0.0.0"), 27015),
+            "127.0.0.1:27015".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            query_address(Some("10.0.0.5"), 27016),
+            "10.0.0.5:27016".parse::<SocketAddr>().unwrap()
+        );
+    }
+}
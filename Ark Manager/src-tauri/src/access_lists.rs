@@ -0,0 +1,317 @@
+// src-tauri/src/access_lists.rs
+//
+// The player lists ASA reads from `ShooterGame/Saved`: the no-check whitelist, the
+// exclusive join list, the admin list (`AllowedCheaterAccountIDs.txt`) and the ban list.
+// Each file holds one player id per line. Edits de-duplicate ids (case-insensitively),
+// keep the file's line endings and replace the file atomically. The ban and whitelist
+// also have RCON commands, so a running server picks up a change without a restart;
+// the other two are only read at startup.
+//
+// Every change, file and RCON, is appended to `access_audit.jsonl` in the app data dir.
+
+use chrono::Local;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const UTF8_BOM: &str = "\u{FEFF}";
+const MAX_ID_LEN: usize = 64;
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+const MAX_AUDIT_LIMIT: usize = 5000;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessList {
+    /// `PlayersJoinNoCheckList.txt`: may join even when the server is full or locked.
+    JoinNoCheck,
+    /// `PlayersExclusiveJoinList.txt`: only these players may join when exclusive join is on.
+    ExclusiveJoin,
+    /// `AllowedCheaterAccountIDs.txt`: admins without the admin password.
+    Admins,
+    /// `BanList.txt`.
+    Bans,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AccessAction {
+    Add,
+    Remove,
+}
+
+impl AccessList {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            AccessList::JoinNoCheck => "PlayersJoinNoCheckList.txt",
+            AccessList::ExclusiveJoin => "PlayersExclusiveJoinList.txt",
+            AccessList::Admins => "AllowedCheaterAccountIDs.txt",
+            AccessList::Bans => "BanList.txt",
+        }
+    }
+
+    pub fn path(self, saved_dir: &Path) -> PathBuf {
+        saved_dir.join(self.file_name())
+    }
+
+    /// The RCON command that applies `action` to a running server, if there is one.
+    pub fn rcon_command(self, action: AccessAction, player_id: &str) -> Option<String> {
+        let command = match (self, action) {
+            (AccessList::JoinNoCheck, AccessAction::Add) => "AllowPlayerToJoinNoCheck",
+            (AccessList::JoinNoCheck, AccessAction::Remove) => "DisallowPlayerToJoinNoCheck",
+            (AccessList::Bans, AccessAction::Add) => "BanPlayer",
+            (AccessList::Bans, AccessAction::Remove) => "UnbanPlayer",
+            (AccessList::ExclusiveJoin | AccessList::Admins, _) => return None,
+        };
+        Some(format!("{} {}", command, player_id))
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListContents {
+    pub list: AccessList,
+    pub path: String,
+    pub exists: bool,
+    pub entries: Vec<String>,
+}
+
+/// A change requested by the UI; `profile_id` picks the server to apply it to live.
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessListChange {
+    pub install_path: String,
+    pub list: AccessList,
+    pub player_ids: Vec<String>,
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    /// Free text kept in the audit trail, e.g. the reason for a ban.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Outcome of applying one change to the running server over RCON.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveApplication {
+    pub command: String,
+    pub applied: bool,
+    #[serde(default)]
+    pub response: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessChange {
+    pub contents: AccessListContents,
+    /// Ids whose line was added or removed; ids already in the requested state are left out.
+    pub changed: Vec<String>,
+    pub live: Vec<LiveApplication>,
+    /// Set when the server is running and the change only applies after a restart.
+    pub restart_required: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessAuditEntry {
+    pub at: String,
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    pub install_path: String,
+    pub list: AccessList,
+    pub action: AccessAction,
+    pub player_id: String,
+    /// False when the id was already in the requested state.
+    pub file_changed: bool,
+    #[serde(default)]
+    pub live: Option<LiveApplication>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessAuditQuery {
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default)]
+    pub install_path: Option<String>,
+    #[serde(default)]
+    pub player_id: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Checks and trims player ids. ASA uses 32-character EOS ids, but older lists can hold
+/// Steam ids, so any short id without whitespace is accepted.
+pub fn normalize_ids(player_ids: &[String]) -> Result<Vec<String>, String> {
+    let mut ids: Vec<String> = vec![];
+    for id in player_ids {
+        let id = id.trim();
+        if id.is_empty()
+            || id.len() > MAX_ID_LEN
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("'{}' is not a valid player id", id));
+        }
+        if !ids.iter().any(|known| known.eq_ignore_ascii_case(id)) {
+            ids.push(id.to_string());
+        }
+    }
+    if ids.is_empty() {
+        return Err("No player ids given".to_string());
+    }
+    Ok(ids)
+}
+
+struct ListFile {
+    entries: Vec<String>,
+    line_ending: &'static str,
+}
+
+fn read_list(path: &Path) -> Result<ListFile, String> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(format!("Failed to read {:?}: {}", path, e)),
+    };
+    let text = String::from_utf8_lossy(&bytes);
+    let text = text.strip_prefix(UTF8_BOM).unwrap_or(&text);
+    let mut entries: Vec<String> = vec![];
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if !entries.iter().any(|e| e.eq_ignore_ascii_case(line)) {
+            entries.push(line.to_string());
+        }
+    }
+    Ok(ListFile {
+        entries,
+        line_ending: if text.contains("\r\n") { "\r\n" } else { "\n" },
+    })
+}
+
+fn write_list(path: &Path, list: &ListFile) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut content = list.entries.join(list.line_ending);
+    if !content.is_empty() {
+        content.push_str(list.line_ending);
+    }
+    let tmp_path = path.with_extension("txt.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to replace {:?}: {}", path, e)
+    })
+}
+
+pub fn contents(saved_dir: &Path, list: AccessList) -> Result<AccessListContents, String> {
+    let path = list.path(saved_dir);
+    Ok(AccessListContents {
+        list,
+        path: path.to_string_lossy().to_string(),
+        exists: path.exists(),
+        entries: read_list(&path)?.entries,
+    })
+}
+
+/// Adds or removes `player_ids` (already normalized) and returns the ids that changed.
+pub fn apply(
+    saved_dir: &Path,
+    list: AccessList,
+    action: AccessAction,
+    player_ids: &[String],
+) -> Result<Vec<String>, String> {
+    let path = list.path(saved_dir);
+    let mut file = read_list(&path)?;
+    let mut changed = vec![];
+    for id in player_ids {
+        let position = file.entries.iter().position(|e| e.eq_ignore_ascii_case(id));
+        match (action, position) {
+            (AccessAction::Add, None) => file.entries.push(id.clone()),
+            (AccessAction::Remove, Some(index)) => {
+                file.entries.remove(index);
+            }
+            _ => continue,
+        }
+        changed.push(id.clone());
+    }
+    if !changed.is_empty() {
+        write_list(&path, &file)?;
+    }
+    Ok(changed)
+}
+
+pub struct AccessAudit {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl AccessAudit {
+    pub fn new(path: PathBuf) -> Self {
+        AccessAudit {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn record(&self, mut entry: AccessAuditEntry) {
+        entry.at = Local::now().to_rfc3339();
+        let _guard = self.lock.lock().unwrap();
+        let result = serde_json::to_string(&entry)
+            .map_err(|e| e.to_string())
+            .and_then(|mut line| {
+                line.push('\n');
+                if let Some(parent) = self.path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)
+                    .and_then(|mut file| file.write_all(line.as_bytes()))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            println!("Failed to write access audit entry: {}", e);
+        }
+    }
+
+    /// Matching entries, newest first.
+    pub fn entries(&self, query: &AccessAuditQuery) -> Vec<AccessAuditEntry> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_AUDIT_LIMIT)
+            .clamp(1, MAX_AUDIT_LIMIT);
+        let _guard = self.lock.lock().unwrap();
+        let Ok(file) = File::open(&self.path) else {
+            return vec![];
+        };
+        let mut entries: Vec<AccessAuditEntry> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str::<AccessAuditEntry>(&line).ok())
+            .filter(|e| {
+                query
+                    .profile_id
+                    .as_ref()
+                    .is_none_or(|p| e.profile_id.as_ref() == Some(p))
+                    && query
+                        .install_path
+                        .as_ref()
+                        .is_none_or(|p| e.install_path == *p)
+                    && query
+                        .player_id
+                        .as_ref()
+                        .is_none_or(|p| e.player_id.eq_ignore_ascii_case(p))
+            })
+            .collect();
+        entries.reverse();
+        entries.truncate(limit);
+        entries
+    }
+}
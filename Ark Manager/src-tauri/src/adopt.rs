@@ -0,0 +1,218 @@
+// src-tauri/src/adopt.rs
+//
+// Keeps a small on-disk registry of the servers this manager launched, so that after
+// the manager is closed or crashes it can find those servers again and take them back
+// under management. Servers missing from the registry can still be matched against the
+// saved profiles the UI passes in.
+//
+// Under Wine or Proton the server's `/proc/<pid>/exe` is the Wine loader, so for those
+// processes the executable is read from the command line instead, where Wine shows the
+// Windows path (`Z:\srv\ark\...\ArkAscendedServer.exe`).
+
+use crate::launch::LaunchSpec;
+use crate::platform::ServerRuntime;
+use crate::{LaunchParams, ServerProcessInfo};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use sysinfo::{ProcessRefreshKind, System, UpdateKind};
+
+const REGISTRY_FILE: &str = "running_servers.json";
+
+// Registry snapshots are numbered as they are taken, so a write that loses a race with
+// a later one never replaces the newer contents.
+static SNAPSHOT_SEQ: AtomicU64 = AtomicU64::new(0);
+static LAST_SAVED_SEQ: Mutex<u64> = Mutex::new(0);
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryEntry {
+    pub pid: u32,
+    pub launch: LaunchParams,
+}
+
+/// A saved profile to look for among the running servers, described like a
+/// `start_ark_server` call.
+#[derive(serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileCandidate {
+    pub profile_id: String,
+    pub install_path: String,
+    #[serde(default)]
+    pub server_path: Option<String>,
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    #[serde(default)]
+    pub launch_spec: Option<LaunchSpec>,
+    pub rcon_ip: String,
+    pub rcon_port: u16,
+    #[serde(default)]
+    pub rcon_password: Option<String>,
+    #[serde(default)]
+    pub rcon_enabled: bool,
+    #[serde(default)]
+    pub runtime: Option<ServerRuntime>,
+}
+
+pub struct RunningArkProcess {
+    pub pid: u32,
+    pub exe: Option<PathBuf>,
+    /// The full command line, program first.
+    pub cmd: Vec<String>,
+}
+
+pub fn registry_path(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(REGISTRY_FILE)
+}
+
+pub fn load_registry(path: &Path) -> Vec<RegistryEntry> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The registry contents at one moment of the process table. Taken while the table is
+/// locked; `save_registry` can then write it after the lock is released.
+pub struct RegistrySnapshot {
+    seq: u64,
+    entries: Vec<RegistryEntry>,
+}
+
+pub fn snapshot_registry(procs: &HashMap<String, ServerProcessInfo>) -> RegistrySnapshot {
+    RegistrySnapshot {
+        seq: SNAPSHOT_SEQ.fetch_add(1, Ordering::SeqCst) + 1,
+        entries: procs
+            .values()
+            .map(|info| RegistryEntry {
+                pid: info.pid,
+                launch: info.launch.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Writes a snapshot, unless one taken after it has already been written.
+pub fn save_registry(path: &Path, snapshot: RegistrySnapshot) -> Result<(), String> {
+    let mut last_saved = LAST_SAVED_SEQ.lock().unwrap_or_else(|e| e.into_inner());
+    if snapshot.seq < *last_saved {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(&snapshot.entries).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+    fs::rename(&tmp_path, path).map_err(|e| e.to_string())?;
+    *last_saved = snapshot.seq;
+    Ok(())
+}
+
+pub fn scan_ark_processes() -> Vec<RunningArkProcess> {
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessRefreshKind::new()
+            .with_exe(UpdateKind::Always)
+            .with_cmd(UpdateKind::Always),
+    );
+
+    sys.processes()
+        .iter()
+        // Linux truncates process names to 15 characters ("ArkAscendedServ")
+        .filter(|(_, p)| p.name().to_lowercase().starts_with("arkascendedserv"))
+        .map(|(pid, p)| RunningArkProcess {
+            pid: pid.as_u32(),
+            exe: p.exe().map(|e| e.to_path_buf()),
+            cmd: p.cmd().to_vec(),
+        })
+        .collect()
+}
+
+fn normalize_path(path: &str) -> String {
+    let normalized = path.trim().trim_matches('"').replace('\\', "/");
+    if cfg!(target_os = "windows") {
+        return normalized.to_lowercase();
+    }
+    // Wine maps drive Z: to the Unix root
+    match normalized.get(..2) {
+        Some(drive) if drive.eq_ignore_ascii_case("z:") => normalized[2..].to_string(),
+        _ => normalized,
+    }
+}
+
+/// The Wine or Proton loader that runs a Windows executable, judged by its file name
+/// (`wine`, `wine64`, `wine64-preloader`, ...).
+fn is_wine_loader(exe: &Path) -> bool {
+    exe.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .is_some_and(|name| name.starts_with("wine") || name.contains("preloader"))
+}
+
+impl RunningArkProcess {
+    /// The program on the command line. Wine can report the whole command line as one
+    /// string, so it is cut after `.exe`.
+    fn program(&self) -> Option<&str> {
+        let first = self.cmd.first()?;
+        let end = first.to_lowercase().find(".exe").map(|i| i + 4);
+        Some(end.map_or(first.as_str(), |end| &first[..end]))
+    }
+
+    /// Whether this process runs `server_path`. The executable is compared unless it is a
+    /// Wine loader or unreadable, in which case the command line's program is.
+    pub fn runs(&self, server_path: &str) -> bool {
+        let expected = normalize_path(server_path);
+        match &self.exe {
+            Some(exe) if !is_wine_loader(exe) => normalize_path(&exe.to_string_lossy()) == expected,
+            _ => self
+                .program()
+                .is_some_and(|program| normalize_path(program).eq_ignore_ascii_case(&expected)),
+        }
+    }
+
+    /// Whether `arg` appears as a whole token on the command line. Quotes are ignored,
+    /// since the OS or Wine may have re-split UE-style quoting (`-Key="a b"`).
+    pub fn has_arg(&self, arg: &str) -> bool {
+        let arg = arg.replace('"', "");
+        let line = self.cmd.join(" ").replace('"', "");
+        !arg.is_empty()
+            && line.match_indices(&arg).any(|(i, _)| {
+                line[..i].ends_with(char::is_whitespace)
+                    && line[i + arg.len()..]
+                        .chars()
+                        .next()
+                        .is_none_or(char::is_whitespace)
+            })
+    }
+}
+
+/// Finds the running process that belongs to a registry entry or profile. The executable
+/// must match, and either the PID is unchanged or every launch argument appears on the
+/// process command line. Without arguments to compare, the executable only decides
+/// when exactly one running server uses it.
+pub fn find_process<'a>(
+    entry: &RegistryEntry,
+    running: &'a [RunningArkProcess],
+) -> Option<&'a RunningArkProcess> {
+    let server_path = &entry.launch.server_path;
+    if let Some(process) = running
+        .iter()
+        .find(|p| p.pid == entry.pid && p.runs(server_path))
+    {
+        return Some(process);
+    }
+
+    let mut candidates = running.iter().filter(|p| p.runs(server_path));
+    if entry.launch.args.is_empty() {
+        let first = candidates.next();
+        return if candidates.next().is_none() {
+            first
+        } else {
+            None
+        };
+    }
+    candidates.find(|p| entry.launch.args.iter().all(|arg| p.has_arg(arg)))
+}
@@ -0,0 +1,668 @@
+// src-tauri/src/backup_scheduler.rs
+//
+// Per-profile automatic backups and retention. Policies are persisted to the app data
+// dir and re-armed at startup; interval backups only run while the profile's server is
+// in `ServerProcesses`. After every automatic backup the retention rules are applied
+// and anything pruned is reported through `backups-pruned`.
+
+use crate::backup_store::{BackupStore, SnapshotManifest};
+use crate::layout::InstallLayout;
+use crate::{get_backup_dir, ServerProcessInfo};
+use chrono::{Datelike, NaiveDateTime};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use walkdir::WalkDir;
+
+const POLICIES_FILE: &str = "backup_policies.json";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub keep_last: Option<u32>,
+    #[serde(default)]
+    pub keep_hourly: Option<u32>,
+    #[serde(default)]
+    pub keep_daily: Option<u32>,
+    #[serde(default)]
+    pub keep_weekly: Option<u32>,
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
+}
+
+impl RetentionPolicy {
+    fn has_count_rules(&self) -> bool {
+        self.keep_last.is_some()
+            || self.keep_hourly.is_some()
+            || self.keep_daily.is_some()
+            || self.keep_weekly.is_some()
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupPolicy {
+    /// Take a backup every N minutes while the server is running.
+    #[serde(default)]
+    pub interval_minutes: Option<u64>,
+    #[serde(default)]
+    pub before_update: bool,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackupTrigger {
+    Interval,
+    BeforeUpdate,
+    BeforeRestore,
+    /// A backup task from the task scheduler.
+    ScheduledTask,
+    BeforeWipe,
+}
+
+impl BackupTrigger {
+    /// Triggers whose snapshots rotate under the retention rules. The safety snapshots
+    /// taken before an update, restore or wipe are only ever deleted by hand.
+    const ROTATING: [BackupTrigger; 2] = [BackupTrigger::Interval, BackupTrigger::ScheduledTask];
+
+    fn label(&self) -> &'static str {
+        match self {
+            BackupTrigger::Interval => "auto: scheduled",
+            BackupTrigger::BeforeUpdate => "auto: before update",
+            BackupTrigger::BeforeRestore => "auto: before restore",
+            BackupTrigger::ScheduledTask => "auto: scheduled task",
+            BackupTrigger::BeforeWipe => "auto: before wipe",
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PersistedPolicy {
+    profile_id: String,
+    install_path: String,
+    policy: BackupPolicy,
+}
+
+struct ProfileEntry {
+    install_path: String,
+    policy: BackupPolicy,
+    interval_token: Option<CancellationToken>,
+}
+
+#[derive(serde::Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneReport {
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+#[derive(Default)]
+pub struct BackupScheduler {
+    profiles: Mutex<HashMap<String, ProfileEntry>>,
+}
+
+fn policies_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(POLICIES_FILE))
+}
+
+impl BackupScheduler {
+    /// Loads persisted policies and starts their interval loops.
+    pub async fn load(
+        self: &Arc<Self>,
+        app: AppHandle,
+        processes: Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    ) {
+        let persisted: Vec<PersistedPolicy> = policies_path(&app)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        for entry in persisted {
+            self.apply_policy(
+                app.clone(),
+                processes.clone(),
+                entry.profile_id,
+                entry.install_path,
+                entry.policy,
+            )
+            .await;
+        }
+    }
+
+    pub async fn set_policy(
+        self: &Arc<Self>,
+        app: AppHandle,
+        processes: Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+        profile_id: String,
+        install_path: String,
+        policy: BackupPolicy,
+    ) -> Result<(), String> {
+        self.apply_policy(app.clone(), processes, profile_id, install_path, policy)
+            .await;
+        self.save(&app).await
+    }
+
+    pub async fn remove_policy(&self, app: &AppHandle, profile_id: &str) -> Result<(), String> {
+        {
+            let mut profiles = self.profiles.lock().await;
+            if let Some(entry) = profiles.remove(profile_id) {
+                if let Some(token) = entry.interval_token {
+                    token.cancel();
+                }
+            }
+        }
+        self.save(app).await
+    }
+
+    pub async fn policy(&self, profile_id: &str) -> Option<BackupPolicy> {
+        let profiles = self.profiles.lock().await;
+        profiles.get(profile_id).map(|entry| entry.policy.clone())
+    }
+
+    async fn apply_policy(
+        self: &Arc<Self>,
+        app: AppHandle,
+        processes: Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+        profile_id: String,
+        install_path: String,
+        policy: BackupPolicy,
+    ) {
+        let mut profiles = self.profiles.lock().await;
+        if let Some(old) = profiles.remove(&profile_id) {
+            if let Some(token) = old.interval_token {
+                token.cancel();
+            }
+        }
+
+        let interval_token = policy
+            .interval_minutes
+            .filter(|minutes| *minutes > 0)
+            .map(|minutes| {
+                let token = CancellationToken::new();
+                spawn_interval_loop(
+                    app,
+                    processes,
+                    profile_id.clone(),
+                    install_path.clone(),
+                    Duration::from_secs(minutes * 60),
+                    policy.retention.clone(),
+                    token.clone(),
+                );
+                token
+            });
+
+        profiles.insert(
+            profile_id,
+            ProfileEntry {
+                install_path,
+                policy,
+                interval_token,
+            },
+        );
+    }
+
+    async fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = policies_path(app).ok_or("Could not resolve app data dir")?;
+        let persisted: Vec<PersistedPolicy> = {
+            let profiles = self.profiles.lock().await;
+            profiles
+                .iter()
+                .map(|(profile_id, entry)| PersistedPolicy {
+                    profile_id: profile_id.clone(),
+                    install_path: entry.install_path.clone(),
+                    policy: entry.policy.clone(),
+                })
+                .collect()
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(&persisted).map_err(|e| e.to_string())?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+
+    /// Takes an automatic backup of `install_path` if any profile on it asks for this
+    /// trigger. Restores are always preceded by a backup, policy or not. Returns the ID
+    /// of the snapshot taken, if any.
+    pub async fn backup_for_trigger(
+        &self,
+        app: &AppHandle,
+        install_path: &str,
+        trigger: BackupTrigger,
+    ) -> Result<Option<String>, String> {
+        let targets: Vec<(String, RetentionPolicy)> = {
+            let profiles = self.profiles.lock().await;
+            profiles
+                .iter()
+                .filter(|(_, entry)| entry.install_path == install_path)
+                .filter(|(_, entry)| match trigger {
+                    BackupTrigger::BeforeUpdate => entry.policy.before_update,
+                    BackupTrigger::BeforeRestore => true,
+                    BackupTrigger::Interval
+                    | BackupTrigger::ScheduledTask
+                    | BackupTrigger::BeforeWipe => false,
+                })
+                .map(|(profile_id, entry)| (profile_id.clone(), entry.policy.retention.clone()))
+                .collect()
+        };
+
+        // Profiles sharing an install share a Saved folder, so one snapshot covers them all
+        let (profile_id, retention) = match (targets.into_iter().next(), trigger) {
+            (Some((profile_id, retention)), _) => (Some(profile_id), retention),
+            (None, BackupTrigger::BeforeRestore) => (None, RetentionPolicy::default()),
+            (None, _) => return Ok(None),
+        };
+        // Nothing to protect yet, e.g. the first install of a new server
+        if !InstallLayout::detect(install_path).saved_dir.exists() {
+            return Ok(None);
+        }
+        run_backup(app, profile_id.as_deref(), install_path, trigger, retention)
+            .await
+            .map(Some)
+    }
+}
+
+fn spawn_interval_loop(
+    app: AppHandle,
+    processes: Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    profile_id: String,
+    install_path: String,
+    interval: Duration,
+    retention: RetentionPolicy,
+    token: CancellationToken,
+) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+
+            let running = processes.lock().await.contains_key(&profile_id);
+            if !running {
+                continue;
+            }
+            if let Err(e) = run_backup(
+                &app,
+                Some(&profile_id),
+                &install_path,
+                BackupTrigger::Interval,
+                retention.clone(),
+            )
+            .await
+            {
+                println!("Scheduled backup for profile {} failed: {}", profile_id, e);
+                let _ = app.emit(
+                    "backup-failed",
+                    serde_json::json!({ "profile_id": profile_id, "error": e }),
+                );
+            }
+        }
+    });
+}
+
+/// Takes a snapshot of the profile's Saved folder and then applies its retention rules.
+pub async fn run_backup(
+    app: &AppHandle,
+    profile_id: Option<&str>,
+    install_path: &str,
+    trigger: BackupTrigger,
+    retention: RetentionPolicy,
+) -> Result<String, String> {
+    let backup_dir = get_backup_dir(install_path);
+    let saved_dir = InstallLayout::detect(install_path).saved_dir;
+    if !saved_dir.exists() {
+        return Err("Saved directory not found. Cannot create backup.".to_string());
+    }
+    fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
+
+    let store = BackupStore::new(backup_dir);
+    let label = Some(trigger.label().to_string());
+    let (manifest, report) = tauri::async_runtime::spawn_blocking(move || {
+        let manifest = store.create_snapshot(&saved_dir, label)?;
+        let report = apply_retention(&store, &retention)?;
+        Ok::<_, String>((manifest, report))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let _ = app.emit(
+        "backup-created",
+        serde_json::json!({
+            "profile_id": profile_id,
+            "install_path": install_path,
+            "backup_id": manifest.id,
+            "trigger": trigger.label(),
+        }),
+    );
+    if !report.removed.is_empty() {
+        let _ = app.emit(
+            "backups-pruned",
+            serde_json::json!({
+                "profile_id": profile_id,
+                "install_path": install_path,
+                "removed": report.removed,
+                "bytes_freed": report.bytes_freed,
+            }),
+        );
+    }
+    Ok(manifest.id)
+}
+
+fn parse_created_at(snapshot: &SnapshotManifest) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(&snapshot.created_at, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// Picks the snapshots to keep under the count rules (keep-last plus
+/// grandfather-father-son buckets). `snapshots` must be sorted newest first.
+fn select_kept(snapshots: &[SnapshotManifest], retention: &RetentionPolicy) -> HashSet<String> {
+    if !retention.has_count_rules() {
+        return snapshots.iter().map(|s| s.id.clone()).collect();
+    }
+
+    let mut keep: HashSet<String> = snapshots
+        .iter()
+        .take(retention.keep_last.unwrap_or(0) as usize)
+        .map(|s| s.id.clone())
+        .collect();
+
+    let mut keep_buckets = |limit: Option<u32>, bucket: &dyn Fn(&NaiveDateTime) -> String| {
+        let limit = limit.unwrap_or(0) as usize;
+        let mut seen = HashSet::new();
+        for snapshot in snapshots {
+            if seen.len() >= limit {
+                break;
+            }
+            if let Some(created) = parse_created_at(snapshot) {
+                // Newest first, so the first snapshot seen in a bucket is the one kept
+                if seen.insert(bucket(&created)) {
+                    keep.insert(snapshot.id.clone());
+                }
+            }
+        }
+    };
+    keep_buckets(retention.keep_hourly, &|t| t.format("%Y-%m-%d %H").to_string());
+    keep_buckets(retention.keep_daily, &|t| t.format("%Y-%m-%d").to_string());
+    keep_buckets(retention.keep_weekly, &|t| {
+        let week = t.iso_week();
+        format!("{}-W{}", week.year(), week.week())
+    });
+    keep
+}
+
+fn store_size(root: &Path) -> u64 {
+    WalkDir::new(root.join("chunks"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Deletes automatic snapshots not covered by the retention rules, then drops the
+/// oldest remaining ones until the store fits `max_total_bytes` (the newest is always
+/// kept). Only interval and scheduled-task snapshots are pruned; manual backups and
+/// the safety snapshots of the other triggers never are.
+pub fn apply_retention(
+    store: &BackupStore,
+    retention: &RetentionPolicy,
+) -> Result<PruneReport, String> {
+    let mut report = PruneReport::default();
+    let snapshots: Vec<SnapshotManifest> = store
+        .list_snapshots()?
+        .into_iter()
+        .filter(|s| {
+            BackupTrigger::ROTATING
+                .iter()
+                .any(|trigger| s.label.as_deref() == Some(trigger.label()))
+        })
+        .collect();
+    let kept = select_kept(&snapshots, retention);
+
+    for snapshot in snapshots.iter().filter(|s| !kept.contains(&s.id)) {
+        let stats = store.delete_snapshot(&snapshot.id)?;
+        report.bytes_freed += stats.bytes_freed;
+        report.removed.push(snapshot.id.clone());
+    }
+
+    if let Some(max_total_bytes) = retention.max_total_bytes {
+        let mut remaining: Vec<&SnapshotManifest> =
+            snapshots.iter().filter(|s| kept.contains(&s.id)).collect();
+        while remaining.len() > 1 && store_size(store.root()) > max_total_bytes {
+            let oldest = remaining.pop().expect("more than one snapshot left");
+            let stats = store.delete_snapshot(&oldest.id)?;
+            report.bytes_freed += stats.bytes_freed;
+            report.removed.push(oldest.id.clone());
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(id: &str, created_at: &str) -> SnapshotManifest {
+        SnapshotManifest {
+            version: 1,
+            id: id.to_string(),
+            created_at: created_at.to_string(),
+            label: Some(BackupTrigger::Interval.label().to_string()),
+            total_size: 0,
+            new_data_size: 0,
+            directories: vec![],
+            files: vec![],
+        }
+    }
+
+    /// Snapshots named after their timestamps, sorted newest first like `list_snapshots`.
+    fn snapshots(times: &[&str]) -> Vec<SnapshotManifest> {
+        let mut snapshots: Vec<_> = times.iter().map(|t| snapshot(t, t)).collect();
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        snapshots
+    }
+
+    fn kept(snapshots: &[SnapshotManifest], retention: RetentionPolicy) -> Vec<String> {
+        let mut kept: Vec<String> = select_kept(snapshots, &retention).into_iter().collect();
+        kept.sort();
+        kept
+    }
+
+    #[test]
+    fn without_count_rules_everything_is_kept() {
+        let all = snapshots(&["2024-05-01 10:00:00", "2024-05-02 10:00:00"]);
+        let retention = RetentionPolicy {
+            max_total_bytes: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(kept(&all, retention).len(), 2);
+    }
+
+    #[test]
+    fn keep_last_keeps_the_newest() {
+        let all = snapshots(&[
+            "2024-05-01 10:00:00",
+            "2024-05-01 10:10:00",
+            "2024-05-01 10:20:00",
+        ]);
+        let retention = RetentionPolicy {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            kept(&all, retention),
+            ["2024-05-01 10:10:00", "2024-05-01 10:20:00"]
+        );
+    }
+
+    #[test]
+    fn hourly_buckets_keep_the_newest_of_each_hour() {
+        let all = snapshots(&[
+            "2024-05-01 08:40:00",
+            "2024-05-01 09:00:00",
+            "2024-05-01 09:40:00",
+            "2024-05-01 10:00:00",
+            "2024-05-01 10:20:00",
+        ]);
+        let retention = RetentionPolicy {
+            keep_hourly: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            kept(&all, retention),
+            ["2024-05-01 09:40:00", "2024-05-01 10:20:00"]
+        );
+    }
+
+    #[test]
+    fn daily_buckets_keep_the_newest_of_each_day() {
+        let all = snapshots(&[
+            "2024-05-01 23:59:59",
+            "2024-05-02 00:00:00",
+            "2024-05-02 12:00:00",
+            "2024-05-04 06:00:00",
+            "2024-05-05 01:00:00",
+            "2024-05-05 02:00:00",
+        ]);
+        let retention = RetentionPolicy {
+            keep_daily: Some(3),
+            ..Default::default()
+        };
+        // A day without snapshots doesn't use up a slot
+        assert_eq!(
+            kept(&all, retention),
+            [
+                "2024-05-02 12:00:00",
+                "2024-05-04 06:00:00",
+                "2024-05-05 02:00:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn weekly_buckets_follow_iso_weeks() {
+        // 2024-01-07 is a Sunday (week 1), 2024-01-08 the Monday after (week 2)
+        let all = snapshots(&[
+            "2023-12-31 12:00:00",
+            "2024-01-01 12:00:00",
+            "2024-01-07 12:00:00",
+            "2024-01-08 12:00:00",
+            "2024-01-09 12:00:00",
+        ]);
+        let retention = RetentionPolicy {
+            keep_weekly: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            kept(&all, retention),
+            ["2024-01-07 12:00:00", "2024-01-09 12:00:00"]
+        );
+    }
+
+    #[test]
+    fn rules_are_combined() {
+        let all = snapshots(&[
+            "2024-05-01 10:00:00",
+            "2024-05-02 10:00:00",
+            "2024-05-03 09:00:00",
+            "2024-05-03 10:00:00",
+        ]);
+        let retention = RetentionPolicy {
+            keep_last: Some(1),
+            keep_daily: Some(2),
+            keep_weekly: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            kept(&all, retention),
+            ["2024-05-02 10:00:00", "2024-05-03 10:00:00"]
+        );
+    }
+
+    struct TempDir(PathBuf);
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Incompressible bytes, so every version of the save costs about `len` in the store.
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn byte_cap_drops_the_oldest_automatic_snapshots() {
+        let temp = TempDir(
+            std::env::temp_dir().join(format!("ark-manager-retention-{}", std::process::id())),
+        );
+        let _ = fs::remove_dir_all(&temp.0);
+        let source = temp.0.join("source");
+        fs::create_dir_all(&source).unwrap();
+        let store = BackupStore::new(temp.0.join("ManagerBackups"));
+        let snapshot = |seed, label: Option<&str>| {
+            fs::write(source.join("save.ark"), noise(10_000, seed)).unwrap();
+            store
+                .create_snapshot(&source, label.map(str::to_string))
+                .unwrap()
+                .id
+        };
+        let interval = Some(BackupTrigger::Interval.label());
+        let oldest = snapshot(1, interval);
+        let manual = snapshot(2, None);
+        let older = snapshot(3, Some(BackupTrigger::ScheduledTask.label()));
+        let newest = snapshot(4, interval);
+
+        // Four ~10 KB chunks; dropping the two oldest automatic snapshots fits 25 KB
+        let retention = RetentionPolicy {
+            max_total_bytes: Some(25_000),
+            ..Default::default()
+        };
+        let report = apply_retention(&store, &retention).unwrap();
+        assert_eq!(report.removed, [oldest, older]);
+        assert!(report.bytes_freed >= 20_000);
+        let left: Vec<String> = store
+            .list_snapshots()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(left.len(), 2);
+        assert!(left.contains(&manual) && left.contains(&newest));
+
+        // The newest automatic snapshot survives even a cap it can't meet
+        let retention = RetentionPolicy {
+            max_total_bytes: Some(1),
+            ..Default::default()
+        };
+        assert!(apply_retention(&store, &retention)
+            .unwrap()
+            .removed
+            .is_empty());
+    }
+}
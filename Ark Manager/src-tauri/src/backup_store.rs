@@ -0,0 +1,638 @@
+// src-tauri/src/backup_store.rs
+//
+// Content-addressed backup repository under `ManagerBackups`. Files are split into
+// fixed-size chunks, each stored once (deflated) under `chunks/` by its SHA-256, and
+// every snapshot is a JSON manifest under `snapshots/` listing the chunks of each file.
+// Files are streamed chunk by chunk, so large saves never sit in memory in full.
+//
+//   ManagerBackups/
+//     chunks/ab/ab12...ef      deflated chunk data
+//     snapshots/<id>.json      snapshot manifest
+
+use chrono::Local;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use once_cell::sync::Lazy;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+const MANIFEST_VERSION: u32 = 1;
+
+// Serializes writers and garbage collection so GC never removes chunks of a snapshot
+// whose manifest hasn't been written yet.
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    /// Path relative to the snapshot root, always with forward slashes.
+    pub path: String,
+    pub size: u64,
+    pub chunks: Vec<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub id: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub total_size: u64,
+    /// Bytes of chunk data this snapshot added to the store when it was created.
+    pub new_data_size: u64,
+    pub directories: Vec<String>,
+    pub files: Vec<FileEntry>,
+}
+
+#[derive(serde::Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GcStats {
+    pub chunks_removed: u64,
+    pub bytes_freed: u64,
+}
+
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl BackupStore {
+    pub fn new(root: PathBuf) -> Self {
+        BackupStore { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn chunks_dir(&self) -> PathBuf {
+        self.root.join("chunks")
+    }
+
+    fn snapshots_dir(&self) -> PathBuf {
+        self.root.join("snapshots")
+    }
+
+    fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.chunks_dir().join(&hash[..2]).join(hash)
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.snapshots_dir().join(format!("{}.json", id))
+    }
+
+    pub fn has_snapshot(&self, id: &str) -> bool {
+        is_valid_id(id) && self.manifest_path(id).exists()
+    }
+
+    /// Stores a chunk if it isn't already present. Returns its hash and the number of
+    /// bytes newly written to disk (0 when deduplicated).
+    fn store_chunk(&self, data: &[u8]) -> Result<(String, u64), String> {
+        let hash = format!("{:x}", Sha256::digest(data));
+        let path = self.chunk_path(&hash);
+        if path.exists() {
+            return Ok((hash, 0));
+        }
+
+        let dir = path.parent().expect("chunk path has a parent");
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        let tmp_path = dir.join(format!("{}.tmp", hash));
+        {
+            let file = fs::File::create(&tmp_path).map_err(|e| e.to_string())?;
+            let mut encoder = DeflateEncoder::new(file, Compression::fast());
+            encoder.write_all(data).map_err(|e| e.to_string())?;
+            encoder
+                .finish()
+                .and_then(|f| f.sync_all())
+                .map_err(|e| e.to_string())?;
+        }
+        fs::rename(&tmp_path, &path).map_err(|e| e.to_string())?;
+        let written = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Ok((hash, written))
+    }
+
+    /// Reads a chunk back and checks it against its hash.
+    fn read_chunk(&self, hash: &str) -> Result<Vec<u8>, String> {
+        if !is_valid_hash(hash) {
+            return Err(format!("Invalid chunk reference '{}'", hash));
+        }
+        let file = fs::File::open(self.chunk_path(hash))
+            .map_err(|e| format!("Missing chunk {}: {}", hash, e))?;
+        let mut data = Vec::new();
+        DeflateDecoder::new(file)
+            .read_to_end(&mut data)
+            .map_err(|e| format!("Corrupt chunk {}: {}", hash, e))?;
+        if format!("{:x}", Sha256::digest(&data)) != hash {
+            return Err(format!("Chunk {} failed its integrity check", hash));
+        }
+        Ok(data)
+    }
+
+    pub fn create_snapshot(
+        &self,
+        source: &Path,
+        label: Option<String>,
+    ) -> Result<SnapshotManifest, String> {
+        let _lock = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        fs::create_dir_all(self.snapshots_dir()).map_err(|e| e.to_string())?;
+        let timestamp = Local::now();
+        let base_id = format!("snapshot-{}", timestamp.format("%Y%m%d-%H%M%S"));
+        let mut id = base_id.clone();
+        let mut suffix = 1;
+        while self.manifest_path(&id).exists() {
+            suffix += 1;
+            id = format!("{}-{}", base_id, suffix);
+        }
+
+        let mut manifest = SnapshotManifest {
+            version: MANIFEST_VERSION,
+            id,
+            created_at: timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            label,
+            total_size: 0,
+            new_data_size: 0,
+            directories: Vec::new(),
+            files: Vec::new(),
+        };
+
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        for entry in WalkDir::new(source) {
+            // A file we can't read would silently be missing from the snapshot
+            let entry = entry.map_err(|e| format!("Failed to read backup source: {}", e))?;
+            let path = entry.path();
+            let relative = match path.strip_prefix(source) {
+                Ok(rel) if !rel.as_os_str().is_empty() => {
+                    rel.to_string_lossy().replace('\\', "/")
+                }
+                _ => continue,
+            };
+
+            if entry.file_type().is_dir() {
+                manifest.directories.push(relative);
+                continue;
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let mut file = fs::File::open(path).map_err(|e| format!("{}: {}", relative, e))?;
+            let mut file_entry = FileEntry {
+                path: relative,
+                size: 0,
+                chunks: Vec::new(),
+            };
+            loop {
+                let filled = read_full(&mut file, &mut buffer)
+                    .map_err(|e| format!("{}: {}", file_entry.path, e))?;
+                if filled == 0 {
+                    break;
+                }
+                let (hash, written) = self.store_chunk(&buffer[..filled])?;
+                file_entry.chunks.push(hash);
+                file_entry.size += filled as u64;
+                manifest.new_data_size += written;
+                if filled < CHUNK_SIZE {
+                    break;
+                }
+            }
+            manifest.total_size += file_entry.size;
+            manifest.files.push(file_entry);
+        }
+
+        self.write_manifest(&manifest)?;
+        Ok(manifest)
+    }
+
+    fn write_manifest(&self, manifest: &SnapshotManifest) -> Result<(), String> {
+        let path = self.manifest_path(&manifest.id);
+        let tmp_path = path.with_extension("json.tmp");
+        let content = serde_json::to_vec_pretty(manifest).map_err(|e| e.to_string())?;
+        fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+
+    pub fn load_manifest(&self, id: &str) -> Result<SnapshotManifest, String> {
+        if !is_valid_id(id) {
+            return Err(format!("Invalid snapshot id '{}'", id));
+        }
+        let content = fs::read(self.manifest_path(id))
+            .map_err(|e| format!("Snapshot '{}' not found: {}", id, e))?;
+        serde_json::from_slice(&content).map_err(|e| format!("Corrupt snapshot manifest: {}", e))
+    }
+
+    /// Ids of every manifest file in the store, readable or not.
+    fn manifest_ids(&self) -> Result<Vec<String>, String> {
+        let dir = self.snapshots_dir();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(id) = path.file_stem().and_then(|s| s.to_str()) {
+                ids.push(id.to_string());
+            }
+        }
+        Ok(ids)
+    }
+
+    pub fn list_snapshots(&self) -> Result<Vec<SnapshotManifest>, String> {
+        let mut snapshots = Vec::new();
+        for id in self.manifest_ids()? {
+            match self.load_manifest(&id) {
+                Ok(manifest) => snapshots.push(manifest),
+                Err(e) => println!("Skipping unreadable snapshot {}: {}", id, e),
+            }
+        }
+        snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        Ok(snapshots)
+    }
+
+    /// Writes every file of a snapshot into `dest`, which is created if needed.
+    pub fn restore_snapshot(&self, id: &str, dest: &Path) -> Result<(), String> {
+        let manifest = self.load_manifest(id)?;
+        fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+
+        for dir in &manifest.directories {
+            let path = safe_join(dest, dir)?;
+            fs::create_dir_all(path).map_err(|e| e.to_string())?;
+        }
+
+        for file_entry in &manifest.files {
+            let path = safe_join(dest, &file_entry.path)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut out = fs::File::create(&path).map_err(|e| e.to_string())?;
+            let mut written = 0u64;
+            for hash in &file_entry.chunks {
+                let data = self.read_chunk(hash)?;
+                out.write_all(&data).map_err(|e| e.to_string())?;
+                written += data.len() as u64;
+            }
+            if written != file_entry.size {
+                return Err(format!(
+                    "{}: restored {} bytes, expected {}",
+                    file_entry.path, written, file_entry.size
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every file of a snapshot is present in `dir` with the recorded size.
+    pub fn verify_restore(&self, id: &str, dir: &Path) -> Result<(), String> {
+        let manifest = self.load_manifest(id)?;
+        for file_entry in &manifest.files {
+            let path = safe_join(dir, &file_entry.path)?;
+            let size = fs::metadata(&path)
+                .map_err(|e| format!("{}: {}", file_entry.path, e))?
+                .len();
+            if size != file_entry.size {
+                return Err(format!(
+                    "{}: found {} bytes, expected {}",
+                    file_entry.path, size, file_entry.size
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn delete_snapshot(&self, id: &str) -> Result<GcStats, String> {
+        {
+            let _lock = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            if !self.has_snapshot(id) {
+                return Err("Backup not found.".to_string());
+            }
+            fs::remove_file(self.manifest_path(id)).map_err(|e| e.to_string())?;
+        }
+        self.collect_garbage()
+    }
+
+    /// Removes chunks that no snapshot manifest references any more. Aborts if any
+    /// manifest can't be read, since its chunks would otherwise look unreferenced.
+    pub fn collect_garbage(&self) -> Result<GcStats, String> {
+        let _lock = STORE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stats = GcStats::default();
+        let chunks_dir = self.chunks_dir();
+        if !chunks_dir.exists() {
+            return Ok(stats);
+        }
+
+        let mut referenced = HashSet::new();
+        for id in self.manifest_ids()? {
+            let manifest = self.load_manifest(&id).map_err(|e| {
+                format!("Garbage collection aborted, snapshot {} is unreadable: {}", id, e)
+            })?;
+            for file_entry in manifest.files {
+                referenced.extend(file_entry.chunks);
+            }
+        }
+
+        for entry in WalkDir::new(&chunks_dir) {
+            let entry = entry.map_err(|e| e.to_string())?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Leftover temp files from an interrupted write are garbage too
+            if referenced.contains(&name) {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if fs::remove_file(entry.path()).is_ok() {
+                stats.chunks_removed += 1;
+                stats.bytes_freed += size;
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Replaces `target` with a directory produced by `fill`. `fill` writes into a staging
+/// directory next to `target`; only once it succeeds is the old directory moved aside
+/// and the staging one renamed into place. Any failure leaves `target` as it was.
+pub fn replace_dir_atomically<F>(target: &Path, fill: F) -> Result<(), String>
+where
+    F: FnOnce(&Path) -> Result<(), String>,
+{
+    let name = target
+        .file_name()
+        .ok_or_else(|| format!("Invalid directory {:?}", target))?
+        .to_string_lossy()
+        .to_string();
+    let staging = target.with_file_name(format!("{}.restore-staging", name));
+    let previous = target.with_file_name(format!("{}.restore-previous", name));
+
+    // Leftovers from an interrupted restore
+    for dir in [&staging, &previous] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)
+                .map_err(|e| format!("Failed to clear {:?}: {}", dir, e))?;
+        }
+    }
+
+    fs::create_dir_all(&staging).map_err(|e| e.to_string())?;
+    if let Err(e) = fill(&staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    let had_previous = target.exists();
+    if had_previous {
+        if let Err(e) = fs::rename(target, &previous) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(format!("Failed to move current directory aside: {}", e));
+        }
+    }
+    if let Err(e) = fs::rename(&staging, target) {
+        if had_previous {
+            if let Err(rollback) = fs::rename(&previous, target) {
+                return Err(format!(
+                    "Failed to swap in restored directory ({}) and to roll back ({}); previous data is in {:?}",
+                    e, rollback, previous
+                ));
+            }
+        }
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!("Failed to swap in restored directory: {}", e));
+    }
+
+    if had_previous {
+        if let Err(e) = fs::remove_dir_all(&previous) {
+            println!("Restore succeeded but {:?} could not be removed: {}", previous, e);
+        }
+    }
+    Ok(())
+}
+
+/// Fills `buf` as far as the reader allows, returning the number of bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Joins a manifest path onto `root`, rejecting anything that would escape it.
+fn safe_join(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative_path = Path::new(relative);
+    let escapes = relative_path.components().any(|c| {
+        !matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir)
+    });
+    if escapes {
+        return Err(format!("Refusing to restore unsafe path '{}'", relative));
+    }
+    Ok(root.join(relative_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "ark-manager-backup-store-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn write(&self, relative: &str, data: &[u8]) {
+            let path = self.0.join("source").join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+
+        fn source(&self) -> PathBuf {
+            self.0.join("source")
+        }
+
+        fn store(&self) -> BackupStore {
+            BackupStore::new(self.0.join("ManagerBackups"))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Incompressible bytes that differ per seed, so chunks only dedup when intended.
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u8
+            })
+            .collect()
+    }
+
+    fn chunk_count(store: &BackupStore) -> usize {
+        WalkDir::new(store.chunks_dir())
+            .into_iter()
+            .filter(|e| e.as_ref().unwrap().file_type().is_file())
+            .count()
+    }
+
+    #[test]
+    fn restore_is_byte_identical() {
+        let temp = TempDir::new("restore");
+        let big = noise(CHUNK_SIZE + 12345, 1);
+        temp.write("TheIsland_WP/TheIsland_WP.ark", &big);
+        temp.write("TheIsland_WP/123.arkprofile", b"profile");
+        temp.write("empty.txt", b"");
+        fs::create_dir_all(temp.source().join("Logs/empty")).unwrap();
+        let store = temp.store();
+
+        let manifest = store.create_snapshot(&temp.source(), None).unwrap();
+        assert_eq!(manifest.total_size, big.len() as u64 + 7);
+        let ark = manifest
+            .files
+            .iter()
+            .find(|f| f.path == "TheIsland_WP/TheIsland_WP.ark")
+            .unwrap();
+        assert_eq!(ark.chunks.len(), 2);
+
+        let dest = temp.0.join("restored");
+        store.restore_snapshot(&manifest.id, &dest).unwrap();
+        store.verify_restore(&manifest.id, &dest).unwrap();
+        assert_eq!(
+            fs::read(dest.join("TheIsland_WP/TheIsland_WP.ark")).unwrap(),
+            big
+        );
+        assert_eq!(
+            fs::read(dest.join("TheIsland_WP/123.arkprofile")).unwrap(),
+            b"profile"
+        );
+        assert_eq!(fs::read(dest.join("empty.txt")).unwrap(), b"");
+        assert!(dest.join("Logs/empty").is_dir());
+    }
+
+    #[test]
+    fn unchanged_data_is_stored_once() {
+        let temp = TempDir::new("dedup");
+        temp.write("a.ark", &noise(1000, 2));
+        temp.write("copy-of-a.ark", &noise(1000, 2));
+        temp.write("b.ark", &noise(1000, 3));
+        let store = temp.store();
+
+        let first = store.create_snapshot(&temp.source(), None).unwrap();
+        assert_eq!(chunk_count(&store), 2);
+        let second = store.create_snapshot(&temp.source(), None).unwrap();
+        assert_ne!(first.id, second.id);
+        assert_eq!(second.new_data_size, 0);
+        assert_eq!(chunk_count(&store), 2);
+        assert_eq!(store.list_snapshots().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn garbage_collection_keeps_shared_chunks() {
+        let temp = TempDir::new("gc");
+        temp.write("shared.ark", &noise(1000, 4));
+        temp.write("changing.ark", &noise(1000, 5));
+        let store = temp.store();
+        let first = store.create_snapshot(&temp.source(), None).unwrap();
+        temp.write("changing.ark", &noise(1000, 6));
+        let second = store.create_snapshot(&temp.source(), None).unwrap();
+        assert_eq!(chunk_count(&store), 3);
+
+        // Only the old version of changing.ark is unreferenced now
+        let stats = store.delete_snapshot(&first.id).unwrap();
+        assert_eq!(stats.chunks_removed, 1);
+        assert_eq!(chunk_count(&store), 2);
+        let dest = temp.0.join("restored");
+        store.restore_snapshot(&second.id, &dest).unwrap();
+        assert_eq!(fs::read(dest.join("shared.ark")).unwrap(), noise(1000, 4));
+
+        let stats = store.delete_snapshot(&second.id).unwrap();
+        assert_eq!(stats.chunks_removed, 2);
+        assert_eq!(chunk_count(&store), 0);
+    }
+
+    #[test]
+    fn garbage_collection_stops_at_an_unreadable_manifest() {
+        let temp = TempDir::new("gc-corrupt");
+        temp.write("save.ark", &noise(1000, 7));
+        let store = temp.store();
+        store.create_snapshot(&temp.source(), None).unwrap();
+        fs::write(store.manifest_path("snapshot-broken"), b"{ not json").unwrap();
+
+        assert!(store.collect_garbage().is_err());
+        assert_eq!(chunk_count(&store), 1);
+    }
+
+    #[test]
+    fn snapshot_ids_cannot_leave_the_store() {
+        let temp = TempDir::new("ids");
+        let store = temp.store();
+        for id in ["../outside", "..", "a/b", "a\\b", ""] {
+            assert!(!is_valid_id(id), "{:?}", id);
+            assert!(!store.has_snapshot(id));
+            assert!(store.load_manifest(id).is_err());
+            assert!(store.restore_snapshot(id, &temp.0.join("dest")).is_err());
+        }
+        assert!(is_valid_id("snapshot-20240101-120000_2"));
+    }
+
+    #[test]
+    fn manifest_paths_cannot_escape_the_restore_target() {
+        let root = Path::new("/restore");
+        for path in ["../escape", "a/../../escape", "/etc/passwd"] {
+            assert!(safe_join(root, path).is_err(), "{:?}", path);
+        }
+        assert_eq!(safe_join(root, "a/./b").unwrap(), root.join("a/./b"));
+
+        // A tampered manifest is refused before anything is written outside `dest`
+        let temp = TempDir::new("escape");
+        temp.write("save.ark", b"data");
+        let store = temp.store();
+        let mut manifest = store.create_snapshot(&temp.source(), None).unwrap();
+        manifest.files[0].path = "../escaped.ark".to_string();
+        store.write_manifest(&manifest).unwrap();
+
+        let dest = temp.0.join("dest");
+        assert!(store.restore_snapshot(&manifest.id, &dest).is_err());
+        assert!(!temp.0.join("escaped.ark").exists());
+    }
+}
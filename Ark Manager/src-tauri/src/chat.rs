@@ -0,0 +1,379 @@
+// src-tauri/src/chat.rs
+//
+// In-game chat for admins who are not in game. Chat reaches us twice: as timestamped
+// lines in `ShooterGame.log` and through `getchat` over RCON, which a poller calls while
+// the server runs. Both feed `ChatHub::record`, which drops a message already seen from
+// the other source within `DEDUP_WINDOW`, appends it to the profile's history under
+// `<app data>/chat/` and emits `chat-message`. `send_chat` goes out through `ServerChat`
+// / `ServerChatTo` and is recorded the same way, so the echo from `getchat` is dropped.
+
+use crate::rcon::RconSession;
+use crate::rcon_audit::{CommandSource, RconAudit};
+use chrono::Local;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Window};
+use tokio_util::sync::CancellationToken;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// The same sender and text within this window is one message. A player repeating
+/// themselves that quickly is collapsed too, which is acceptable for a chat view.
+const DEDUP_WINDOW: Duration = Duration::from_secs(20);
+/// The history file is moved to `<profile>.1.jsonl` past this size, replacing the
+/// previous one, so each profile keeps at most twice this much chat.
+const HISTORY_MAX_BYTES: u64 = 4 * 1024 * 1024;
+const DEFAULT_HISTORY_LIMIT: usize = 200;
+const MAX_HISTORY_LIMIT: usize = 2000;
+const SERVER_SENDER: &str = "SERVER";
+/// What ARK answers to a command with no output, e.g. `getchat` when nobody talked.
+const EMPTY_RCON_RESPONSE: &str = "Server received, But no response!!";
+
+/// `Name (Character): text`, optionally with a `[Channel]` tag in front and a
+/// `[Tribe]` tag after the character (added by some chat mods), or `SERVER: text`.
+static CHAT_BODY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?:\[(?P<channel>Chat|Global|Tribe|Alliance|Local|Admin|Server)\]\s*)?(?P<sender>[^\[\]():]+?)(?:\s\((?P<character>[^()]+)\))?(?:\s\[(?P<tribe>[^\[\]]+)\])?:\s(?P<message>.*)$",
+    )
+    .unwrap()
+});
+
+/// The chat timestamp, after the optional `[2024.05.01-10.11.12:123][ 42]` engine prefix.
+static LOG_PREFIX_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:\[[^\]]*\]\[[^\]]*\])?(\d{4}\.\d{2}\.\d{2}_\d{2}\.\d{2}\.\d{2}):\s").unwrap()
+});
+
+static MESSAGE_SEQ: AtomicU64 = AtomicU64::new(0);
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChatChannel {
+    Global,
+    Tribe,
+    Alliance,
+    Local,
+    Admin,
+    /// Broadcast with `ServerChat`.
+    Server,
+    /// Sent to one player with `ServerChatTo`.
+    Direct,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChatSource {
+    Log,
+    Rcon,
+    Manager,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub id: String,
+    pub profile_id: String,
+    pub sender: String,
+    pub character: Option<String>,
+    pub tribe: Option<String>,
+    pub channel: ChatChannel,
+    /// Recipient id for `Direct` messages.
+    #[serde(default)]
+    pub recipient: Option<String>,
+    pub message: String,
+    /// When the manager received the message (RFC 3339).
+    pub timestamp: String,
+    /// Timestamp printed in the server log, when the message came from there.
+    #[serde(default)]
+    pub server_time: Option<String>,
+    pub source: ChatSource,
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatHistoryQuery {
+    /// Only messages strictly older than this timestamp, for paging backwards.
+    #[serde(default)]
+    pub before: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl ChatMessage {
+    fn new(profile_id: &str, sender: &str, channel: ChatChannel, message: &str) -> Self {
+        let now = Local::now();
+        ChatMessage {
+            id: format!(
+                "{:x}-{:x}",
+                now.timestamp_millis(),
+                MESSAGE_SEQ.fetch_add(1, Ordering::Relaxed)
+            ),
+            profile_id: profile_id.to_string(),
+            sender: sender.to_string(),
+            character: None,
+            tribe: None,
+            channel,
+            recipient: None,
+            message: message.to_string(),
+            timestamp: now.to_rfc3339(),
+            server_time: None,
+            source: ChatSource::Manager,
+        }
+    }
+
+    fn dedup_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.sender.trim().to_lowercase().hash(&mut hasher);
+        self.recipient.hash(&mut hasher);
+        self.message.trim().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Parses one chat line without the log timestamp. Bare `Name: text` lines are only
+/// accepted when `loose` is set, since the log is full of `Category: text` lines.
+fn parse_body(profile_id: &str, body: &str, loose: bool) -> Option<ChatMessage> {
+    let caps = CHAT_BODY_REGEX.captures(body.trim_end())?;
+    let sender = caps["sender"].trim();
+    let tag = caps.name("channel").map(|m| m.as_str());
+    let character = caps.name("character").map(|m| m.as_str().to_string());
+    let is_server = sender.eq_ignore_ascii_case(SERVER_SENDER);
+    if sender.is_empty() || !(loose || tag.is_some() || character.is_some() || is_server) {
+        return None;
+    }
+    // Admin commands are logged in the same shape
+    if sender.starts_with("AdminCmd") {
+        return None;
+    }
+
+    let channel = match tag {
+        Some("Tribe") => ChatChannel::Tribe,
+        Some("Alliance") => ChatChannel::Alliance,
+        Some("Local") => ChatChannel::Local,
+        Some("Admin") => ChatChannel::Admin,
+        Some("Server") => ChatChannel::Server,
+        _ if is_server => ChatChannel::Server,
+        _ => ChatChannel::Global,
+    };
+    let mut message = ChatMessage::new(profile_id, sender, channel, &caps["message"]);
+    message.character = character;
+    message.tribe = caps.name("tribe").map(|m| m.as_str().to_string());
+    Some(message)
+}
+
+/// Parses a `ShooterGame.log` line (`2024.05.01_10.11.12: Name (Character): text`).
+pub fn parse_log_line(profile_id: &str, line: &str) -> Option<ChatMessage> {
+    let prefix = LOG_PREFIX_REGEX.captures(line)?;
+    let server_time = prefix[1].to_string();
+    let mut message = parse_body(profile_id, &line[prefix[0].len()..], false)?;
+    message.server_time = Some(server_time);
+    message.source = ChatSource::Log;
+    Some(message)
+}
+
+/// Parses one line of a `getchat` response.
+pub fn parse_getchat_line(profile_id: &str, line: &str) -> Option<ChatMessage> {
+    let line = line.trim();
+    if line.is_empty() || line == EMPTY_RCON_RESPONSE {
+        return None;
+    }
+    let mut message = parse_body(profile_id, line, true)?;
+    message.source = ChatSource::Rcon;
+    Some(message)
+}
+
+/// Builds the RCON command for `send_chat` and the message to record for it.
+/// `recipient_id` is the player's EOS id for a private message.
+pub fn outgoing(
+    profile_id: &str,
+    text: &str,
+    recipient_id: Option<&str>,
+) -> Result<(String, ChatMessage), String> {
+    // A newline would end the RCON command early
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Err("Chat message is empty".to_string());
+    }
+    match recipient_id.map(str::trim) {
+        Some(id) => {
+            if id.is_empty() || id.contains(['"', ' ']) {
+                return Err(format!("'{}' is not a valid player id", id));
+            }
+            let mut message =
+                ChatMessage::new(profile_id, SERVER_SENDER, ChatChannel::Direct, &text);
+            message.recipient = Some(id.to_string());
+            Ok((format!("ServerChatTo \"{}\" {}", id, text), message))
+        }
+        None => Ok((
+            format!("ServerChat {}", text),
+            ChatMessage::new(profile_id, SERVER_SENDER, ChatChannel::Server, &text),
+        )),
+    }
+}
+
+pub struct ChatHub {
+    root: PathBuf,
+    recent: Mutex<HashMap<String, VecDeque<(u64, Instant)>>>,
+    // Serializes appends and rotation across the log tail and the poller
+    write_lock: Mutex<()>,
+}
+
+impl ChatHub {
+    pub fn new(root: PathBuf) -> Self {
+        ChatHub {
+            root,
+            recent: Mutex::new(HashMap::new()),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn history_path(&self, profile_id: &str) -> PathBuf {
+        self.root.join(format!(
+            "{}.jsonl",
+            crate::log_archive::profile_dir_name(profile_id)
+        ))
+    }
+
+    /// True when the message was not seen within `DEDUP_WINDOW`; remembers it either way.
+    fn is_new(&self, message: &ChatMessage) -> bool {
+        let key = message.dedup_key();
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap();
+        let seen = recent.entry(message.profile_id.clone()).or_default();
+        while seen
+            .front()
+            .is_some_and(|(_, at)| now.duration_since(*at) > DEDUP_WINDOW)
+        {
+            seen.pop_front();
+        }
+        if seen.iter().any(|(k, _)| *k == key) {
+            return false;
+        }
+        seen.push_back((key, now));
+        true
+    }
+
+    /// Stores and emits `message` unless it is a duplicate. Returns whether it was new.
+    pub fn record(&self, window: &Window, message: ChatMessage) -> bool {
+        if !self.is_new(&message) {
+            return false;
+        }
+        if let Err(e) = self.append(&message) {
+            println!(
+                "Failed to store chat message for profile {}: {}",
+                message.profile_id, e
+            );
+        }
+        let _ = window.emit(
+            "chat-message",
+            serde_json::json!({ "profile_id": message.profile_id, "message": message }),
+        );
+        true
+    }
+
+    fn append(&self, message: &ChatMessage) -> Result<(), String> {
+        let _guard = self.write_lock.lock().unwrap();
+        fs::create_dir_all(&self.root).map_err(|e| e.to_string())?;
+        let path = self.history_path(&message.profile_id);
+        if fs::metadata(&path).is_ok_and(|m| m.len() >= HISTORY_MAX_BYTES) {
+            fs::rename(&path, path.with_extension("1.jsonl")).map_err(|e| e.to_string())?;
+        }
+        let mut line = serde_json::to_string(message).map_err(|e| e.to_string())?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| e.to_string())
+    }
+
+    /// The newest stored messages matching `query`, oldest first.
+    pub fn history(&self, profile_id: &str, query: &ChatHistoryQuery) -> Vec<ChatMessage> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_HISTORY_LIMIT)
+            .clamp(1, MAX_HISTORY_LIMIT);
+        let path = self.history_path(profile_id);
+        let mut messages: VecDeque<ChatMessage> = VecDeque::with_capacity(limit);
+        let _guard = self.write_lock.lock().unwrap();
+        for file in [path.with_extension("1.jsonl"), path] {
+            for message in read_history(&file) {
+                // RFC 3339 timestamps from one clock compare correctly as strings
+                if query
+                    .before
+                    .as_deref()
+                    .is_some_and(|before| message.timestamp.as_str() >= before)
+                {
+                    continue;
+                }
+                if messages.len() == limit {
+                    messages.pop_front();
+                }
+                messages.push_back(message);
+            }
+        }
+        messages.into()
+    }
+}
+
+fn read_history(path: &Path) -> Vec<ChatMessage> {
+    let Ok(file) = File::open(path) else {
+        return vec![];
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// Polls `getchat` while the server runs. Failures are left to the session's backoff
+/// and the RCON health view; the log still delivers chat meanwhile.
+pub fn spawn_poller(
+    window: Window,
+    hub: Arc<ChatHub>,
+    audit: Arc<RconAudit>,
+    profile_id: String,
+    rcon: Arc<RconSession>,
+    cancellation_token: CancellationToken,
+) {
+    tokio::spawn(async move {
+        // Lines of the previous response, in case the server does not clear its buffer
+        let mut previous: HashSet<String> = HashSet::new();
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+            let response = match audit
+                .exec(
+                    &window,
+                    &profile_id,
+                    Some(&rcon),
+                    "getchat",
+                    CommandSource::ChatPoll,
+                    None,
+                )
+                .await
+            {
+                Ok(response) => response,
+                Err(_) => continue,
+            };
+            let lines: HashSet<String> = response.lines().map(|l| l.trim().to_string()).collect();
+            for line in response.lines().filter(|l| !previous.contains(l.trim())) {
+                if let Some(message) = parse_getchat_line(&profile_id, line) {
+                    hub.record(&window, message);
+                }
+            }
+            previous = lines;
+        }
+    });
+}
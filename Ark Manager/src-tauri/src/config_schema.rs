@@ -0,0 +1,895 @@
+// src-tauri/src/config_schema.rs
+//
+// Known ASA server settings and the validator built on them. ARK silently ignores
+// misspelled keys, unparsable values and settings in the wrong file, so we check the
+// INI files (and `?Key=Value` / `-Key=Value` launch options) against this table before
+// a server starts. Type and range problems are errors; unknown or misplaced keys are
+// warnings, since the table can never be complete, and so are bool values Unreal still
+// reads (`1`, `Yes`, `On`, an empty value) even though they aren't `True`/`False`.
+// Struct values our parser can't read are warnings too: mod and override structs vary
+// too much to refuse a start over them.
+
+use crate::ini::{IniFile, IniSection};
+use crate::ue_text::{self, UeValue};
+
+pub const SERVER_SETTINGS: &str = "ServerSettings";
+pub const SESSION_SETTINGS: &str = "SessionSettings";
+pub const GAME_SESSION: &str = "/Script/Engine.GameSession";
+pub const MESSAGE_OF_THE_DAY: &str = "MessageOfTheDay";
+pub const MULTIHOME: &str = "MultiHome";
+pub const GAME_MODE: &str = "/Script/ShooterGame.ShooterGameMode";
+
+/// Sections whose every key we expect to know; unknown keys elsewhere are not reported.
+const CHECKED_SECTIONS: &[(IniFile, &str)] = &[
+    (IniFile::GameUserSettings, SERVER_SETTINGS),
+    (IniFile::GameUserSettings, SESSION_SETTINGS),
+    (IniFile::Game, GAME_MODE),
+];
+
+/// Number of stat slots in the indexed `PerLevelStatsMultiplier_*` arrays.
+const STAT_COUNT: u32 = 12;
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingKind {
+    Bool,
+    Int,
+    Float,
+    String,
+    /// An Unreal struct literal, e.g. `(ItemClassString="...",Quantity=(...))`.
+    Struct,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SettingLocation {
+    /// Only read from the INI file.
+    Ini,
+    /// Read from the INI file or as a `?Key=Value` launch option.
+    Either,
+    /// Only honoured as a launch argument.
+    CommandLine,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingSpec {
+    pub name: &'static str,
+    /// `None` for launch-only settings.
+    pub file: Option<IniFile>,
+    pub section: Option<&'static str>,
+    pub kind: SettingKind,
+    pub default: &'static str,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub location: SettingLocation,
+    /// Indexed settings (`Name[0]=...`) accept indices below this.
+    pub index_count: Option<u32>,
+    /// Key may appear several times (Game.ini override lists).
+    pub repeatable: bool,
+    /// The launch argument to use instead, for INI keys ASA ignores.
+    pub command_line_name: Option<&'static str>,
+}
+
+const fn spec(
+    name: &'static str,
+    file: IniFile,
+    section: &'static str,
+    kind: SettingKind,
+    default: &'static str,
+) -> SettingSpec {
+    let location = match file {
+        IniFile::GameUserSettings => SettingLocation::Either,
+        IniFile::Game => SettingLocation::Ini,
+    };
+    SettingSpec {
+        name,
+        file: Some(file),
+        section: Some(section),
+        kind,
+        default,
+        min: None,
+        max: None,
+        location,
+        index_count: None,
+        repeatable: false,
+        command_line_name: None,
+    }
+}
+
+impl SettingSpec {
+    const fn range(mut self, min: f64, max: Option<f64>) -> Self {
+        self.min = Some(min);
+        self.max = max;
+        self
+    }
+
+    const fn ini_only(mut self) -> Self {
+        self.location = SettingLocation::Ini;
+        self
+    }
+
+    const fn indexed(mut self, count: u32) -> Self {
+        self.index_count = Some(count);
+        self
+    }
+
+    const fn repeatable(mut self) -> Self {
+        self.repeatable = true;
+        self
+    }
+
+    const fn ignored_use(mut self, command_line_name: &'static str) -> Self {
+        self.location = SettingLocation::CommandLine;
+        self.command_line_name = Some(command_line_name);
+        self
+    }
+}
+
+const fn launch(name: &'static str, kind: SettingKind) -> SettingSpec {
+    SettingSpec {
+        name,
+        file: None,
+        section: None,
+        kind,
+        default: "",
+        min: None,
+        max: None,
+        location: SettingLocation::CommandLine,
+        index_count: None,
+        repeatable: false,
+        command_line_name: None,
+    }
+}
+
+const fn gus_bool(name: &'static str, default: &'static str) -> SettingSpec {
+    spec(name, IniFile::GameUserSettings, SERVER_SETTINGS, SettingKind::Bool, default)
+}
+
+const fn gus_float(name: &'static str, default: &'static str) -> SettingSpec {
+    spec(name, IniFile::GameUserSettings, SERVER_SETTINGS, SettingKind::Float, default)
+        .range(0.0, None)
+}
+
+const fn gus_int(name: &'static str, default: &'static str) -> SettingSpec {
+    spec(name, IniFile::GameUserSettings, SERVER_SETTINGS, SettingKind::Int, default)
+        .range(0.0, None)
+}
+
+const fn gus_string(name: &'static str) -> SettingSpec {
+    spec(name, IniFile::GameUserSettings, SERVER_SETTINGS, SettingKind::String, "")
+}
+
+const fn game_bool(name: &'static str, default: &'static str) -> SettingSpec {
+    spec(name, IniFile::Game, GAME_MODE, SettingKind::Bool, default)
+}
+
+const fn game_float(name: &'static str, default: &'static str) -> SettingSpec {
+    spec(name, IniFile::Game, GAME_MODE, SettingKind::Float, default).range(0.0, None)
+}
+
+const fn game_int(name: &'static str, default: &'static str) -> SettingSpec {
+    spec(name, IniFile::Game, GAME_MODE, SettingKind::Int, default).range(0.0, None)
+}
+
+const fn game_struct(name: &'static str) -> SettingSpec {
+    spec(name, IniFile::Game, GAME_MODE, SettingKind::Struct, "").repeatable()
+}
+
+const fn game_stats(name: &'static str) -> SettingSpec {
+    game_float(name, "1.0").indexed(STAT_COUNT)
+}
+
+pub static SCHEMA: &[SettingSpec] = &[
+    // GameUserSettings.ini [ServerSettings]
+    gus_string("ActiveMods"),
+    gus_bool("AdminLogging", "False"),
+    gus_bool("AllowAnyoneBabyImprintCuddle", "False"),
+    gus_bool("AllowCaveBuildingPvE", "False"),
+    gus_bool("AllowCaveBuildingPvP", "True"),
+    gus_bool("AllowCrateSpawnsOnTopOfStructures", "False"),
+    gus_bool("AllowFlyerCarryPvE", "False"),
+    gus_bool("AllowFlyingStaminaRecovery", "False"),
+    gus_bool("AllowHideDamageSourceFromLogs", "True"),
+    gus_bool("AllowHitMarkers", "True"),
+    gus_bool("AllowIntegratedSPlusStructures", "True"),
+    gus_bool("AllowMultipleAttachedC4", "False"),
+    gus_bool("AllowRaidDinoFeeding", "False"),
+    gus_bool("AllowThirdPersonPlayer", "False"),
+    gus_bool("AlwaysAllowStructurePickup", "False"),
+    gus_bool("AutoDestroyDecayedDinos", "False"),
+    gus_float("AutoDestroyOldStructuresMultiplier", "0.0"),
+    gus_float("AutoSavePeriodMinutes", "15.0"),
+    gus_string("BanListURL"),
+    gus_float("DayCycleSpeedScale", "1.0"),
+    gus_float("DayTimeSpeedScale", "1.0"),
+    gus_float("DifficultyOffset", "1.0").range(0.0, Some(1.0)),
+    gus_float("DinoCharacterFoodDrainMultiplier", "1.0"),
+    gus_float("DinoCharacterHealthRecoveryMultiplier", "1.0"),
+    gus_float("DinoCharacterStaminaDrainMultiplier", "1.0"),
+    gus_float("DinoCountMultiplier", "1.0"),
+    gus_float("DinoDamageMultiplier", "1.0"),
+    gus_float("DinoResistanceMultiplier", "1.0"),
+    gus_bool("DisableDinoDecayPvE", "False"),
+    gus_bool("DisableImprintDinoBuff", "False"),
+    gus_bool("DisablePvEGamma", "False"),
+    gus_bool("DisableStructureDecayPvE", "False"),
+    gus_bool("DisableWeatherFog", "False"),
+    gus_bool("DontAlwaysNotifyPlayerJoined", "False"),
+    gus_bool("EnableExtraStructurePreventionVolumes", "False"),
+    gus_bool("EnablePvPGamma", "False"),
+    gus_bool("globalVoiceChat", "False"),
+    gus_float("HarvestAmountMultiplier", "1.0"),
+    gus_float("HarvestHealthMultiplier", "1.0"),
+    gus_float("ImplantSuicideCD", "28800.0"),
+    gus_float("KickIdlePlayersPeriod", "3600.0"),
+    gus_int("MaxPersonalTamedDinos", "0"),
+    gus_int("MaxPlatformSaddleStructureLimit", "75"),
+    gus_int("MaxTamedDinos", "5000"),
+    gus_int("MaxTamedDinos_SoftTameLimit", "5000"),
+    gus_int("MaxTributeDinos", "20"),
+    gus_int("MaxTributeItems", "50"),
+    gus_float("NightTimeSpeedScale", "1.0"),
+    gus_bool("noTributeDownloads", "False"),
+    gus_float("OverrideOfficialDifficulty", "0.0"),
+    gus_bool("OverrideStructurePlatformPrevention", "False"),
+    gus_float("OxygenSwimSpeedStatMultiplier", "1.0"),
+    gus_float("PerPlatformMaxStructuresMultiplier", "1.0"),
+    gus_float("PlatformSaddleBuildAreaBoundsMultiplier", "1.0"),
+    gus_float("PlayerCharacterFoodDrainMultiplier", "1.0"),
+    gus_float("PlayerCharacterHealthRecoveryMultiplier", "1.0"),
+    gus_float("PlayerCharacterStaminaDrainMultiplier", "1.0"),
+    gus_float("PlayerCharacterWaterDrainMultiplier", "1.0"),
+    gus_float("PlayerDamageMultiplier", "1.0"),
+    gus_float("PlayerResistanceMultiplier", "1.0"),
+    gus_bool("PreventDiseases", "False"),
+    gus_bool("PreventDownloadDinos", "False"),
+    gus_bool("PreventDownloadItems", "False"),
+    gus_bool("PreventDownloadSurvivors", "False"),
+    gus_bool("PreventOfflinePvP", "False"),
+    gus_float("PreventOfflinePvPInterval", "0.0"),
+    gus_bool("PreventSpawnAnimations", "False"),
+    gus_bool("PreventTribeAlliances", "False"),
+    gus_bool("ProximityChat", "False"),
+    gus_bool("PvEAllowStructuresAtSupplyDrops", "False"),
+    gus_float("PvEDinoDecayPeriodMultiplier", "1.0"),
+    gus_float("PvEStructureDecayPeriodMultiplier", "1.0"),
+    gus_float("RaidDinoCharacterFoodDrainMultiplier", "1.0"),
+    gus_bool("RandomSupplyCratePoints", "False"),
+    gus_bool("RCONEnabled", "False"),
+    gus_int("RCONPort", "27020").range(1.0, Some(65535.0)),
+    gus_int("RCONServerGameLogBuffer", "600"),
+    gus_float("ResourcesRespawnPeriodMultiplier", "1.0"),
+    gus_string("ServerAdminPassword"),
+    gus_bool("ServerCrosshair", "True"),
+    gus_bool("ServerForceNoHUD", "False"),
+    gus_bool("ServerHardcore", "False"),
+    gus_string("ServerPassword"),
+    gus_bool("ServerPVE", "False"),
+    gus_bool("ShowFloatingDamageText", "False"),
+    gus_bool("ShowMapPlayerLocation", "False"),
+    gus_string("SpectatorPassword"),
+    gus_float("StructureDamageMultiplier", "1.0"),
+    gus_float("StructurePickupHoldDuration", "0.5"),
+    gus_float("StructurePickupTimeAfterPlacement", "30.0"),
+    gus_float("StructurePreventResourceRadiusMultiplier", "1.0"),
+    gus_float("StructureResistanceMultiplier", "1.0"),
+    gus_float("TamedDinoDamageMultiplier", "1.0"),
+    gus_float("TamedDinoResistanceMultiplier", "1.0"),
+    gus_float("TamingSpeedMultiplier", "1.0"),
+    gus_int("TheMaxStructuresInRange", "10500"),
+    gus_float("TribeNameChangeCooldown", "15.0"),
+    gus_float("XPMultiplier", "1.0"),
+    // GameUserSettings.ini, other sections
+    spec("SessionName", IniFile::GameUserSettings, SESSION_SETTINGS, SettingKind::String, ""),
+    spec("Port", IniFile::GameUserSettings, SESSION_SETTINGS, SettingKind::Int, "7777")
+        .range(1.0, Some(65535.0)),
+    spec("QueryPort", IniFile::GameUserSettings, SESSION_SETTINGS, SettingKind::Int, "27015")
+        .range(1.0, Some(65535.0)),
+    spec("MultiHome", IniFile::GameUserSettings, MULTIHOME, SettingKind::String, "").ini_only(),
+    spec("MaxPlayers", IniFile::GameUserSettings, GAME_SESSION, SettingKind::Int, "70")
+        .range(1.0, None)
+        .ignored_use("-WinLiveMaxPlayers"),
+    spec("Message", IniFile::GameUserSettings, MESSAGE_OF_THE_DAY, SettingKind::String, "")
+        .ini_only(),
+    spec("Duration", IniFile::GameUserSettings, MESSAGE_OF_THE_DAY, SettingKind::Int, "20")
+        .range(0.0, None)
+        .ini_only(),
+    // Game.ini [/Script/ShooterGame.ShooterGameMode]
+    game_float("BabyCuddleGracePeriodMultiplier", "1.0"),
+    game_float("BabyCuddleIntervalMultiplier", "1.0"),
+    game_float("BabyCuddleLoseImprintQualitySpeedMultiplier", "1.0"),
+    game_float("BabyFoodConsumptionSpeedMultiplier", "1.0"),
+    game_float("BabyImprintAmountMultiplier", "1.0"),
+    game_float("BabyImprintingStatScaleMultiplier", "1.0"),
+    game_float("BabyMatureSpeedMultiplier", "1.0"),
+    game_bool("bAllowCustomRecipes", "True"),
+    game_bool("bAllowFlyerSpeedLeveling", "False"),
+    game_bool("bAllowPlatformSaddleMultiFloors", "False"),
+    game_bool("bAllowSpeedLeveling", "False"),
+    game_bool("bAllowUnlimitedRespecs", "False"),
+    game_bool("bAutoPvETimer", "False"),
+    game_bool("bDisableDinoRiding", "False"),
+    game_bool("bDisableDinoTaming", "False"),
+    game_bool("bDisableFriendlyFire", "False"),
+    game_bool("bDisableLootCrates", "False"),
+    game_bool("bDisableStructurePlacementCollision", "False"),
+    game_bool("bFlyerPlatformAllowUnalignedDinoBasing", "False"),
+    game_bool("bIncreasePvPRespawnInterval", "True"),
+    game_bool("bOnlyAllowSpecifiedEngrams", "False"),
+    game_bool("bPassiveDefensesDamageRiderlessDinos", "False"),
+    game_bool("bPvEDisableFriendlyFire", "False"),
+    game_bool("bShowCreativeMode", "False"),
+    game_bool("bUseCorpseLocator", "True"),
+    game_bool("bUseSingleplayerSettings", "False"),
+    game_bool("bUseTameLimitForStructuresOnly", "False"),
+    game_float("CraftXPMultiplier", "1.0"),
+    game_float("CropDecaySpeedMultiplier", "1.0"),
+    game_float("CropGrowthSpeedMultiplier", "1.0"),
+    game_float("CustomRecipeEffectivenessMultiplier", "1.0"),
+    game_float("CustomRecipeSkillMultiplier", "1.0"),
+    game_float("DinoHarvestingDamageMultiplier", "3.2"),
+    game_float("DinoTurretDamageMultiplier", "1.0"),
+    game_float("EggHatchSpeedMultiplier", "1.0"),
+    game_float("FishingLootQualityMultiplier", "1.0"),
+    game_float("FuelConsumptionIntervalMultiplier", "1.0"),
+    game_float("GenericXPMultiplier", "1.0"),
+    game_float("GlobalCorpseDecompositionTimeMultiplier", "1.0"),
+    game_float("GlobalItemDecompositionTimeMultiplier", "1.0"),
+    game_float("GlobalSpoilingTimeMultiplier", "1.0"),
+    game_float("HairGrowthSpeedMultiplier", "1.0"),
+    game_float("HarvestXPMultiplier", "1.0"),
+    game_float("KillXPMultiplier", "1.0"),
+    game_float("LayEggIntervalMultiplier", "1.0"),
+    game_float("MatingIntervalMultiplier", "1.0"),
+    game_float("MatingSpeedMultiplier", "1.0"),
+    game_int("MaxNumberOfPlayersInTribe", "0"),
+    game_int("MaxTribeLogs", "400"),
+    game_float("PassiveTameIntervalMultiplier", "1.0"),
+    game_float("PlayerHarvestingDamageMultiplier", "1.0"),
+    game_float("PoopIntervalMultiplier", "1.0"),
+    game_float("ResourceNoReplenishRadiusPlayers", "1.0"),
+    game_float("ResourceNoReplenishRadiusStructures", "1.0"),
+    game_float("SpecialXPMultiplier", "1.0"),
+    game_float("StructureDamageRepairCooldown", "180.0"),
+    game_float("SupplyCrateLootQualityMultiplier", "1.0").range(1.0, Some(5.0)),
+    game_float("TamedDinoCharacterFoodDrainMultiplier", "1.0"),
+    game_float("TamedDinoTorporDrainMultiplier", "1.0"),
+    game_float("WildDinoCharacterFoodDrainMultiplier", "1.0"),
+    game_float("WildDinoTorporDrainMultiplier", "1.0"),
+    game_stats("PerLevelStatsMultiplier_Player"),
+    game_stats("PerLevelStatsMultiplier_DinoTamed"),
+    game_stats("PerLevelStatsMultiplier_DinoTamed_Add"),
+    game_stats("PerLevelStatsMultiplier_DinoTamed_Affinity"),
+    game_stats("PerLevelStatsMultiplier_DinoWild"),
+    game_stats("PlayerBaseStatMultipliers"),
+    game_int("ExcludeItemIndices", "").repeatable(),
+    game_int("OverridePlayerLevelEngramPoints", "").repeatable(),
+    game_struct("ConfigAddNPCSpawnEntriesContainer"),
+    game_struct("ConfigOverrideItemCraftingCosts"),
+    game_struct("ConfigOverrideItemMaxQuantity"),
+    game_struct("ConfigOverrideNPCSpawnEntriesContainer"),
+    game_struct("ConfigOverrideSupplyCrateItems"),
+    game_struct("ConfigSubtractNPCSpawnEntriesContainer"),
+    game_struct("DinoClassDamageMultipliers"),
+    game_struct("DinoClassResistanceMultipliers"),
+    game_struct("DinoSpawnWeightMultipliers"),
+    game_struct("EngramEntryAutoUnlocks"),
+    game_struct("HarvestResourceItemAmountClassMultipliers"),
+    game_struct("LevelExperienceRampOverrides"),
+    game_struct("NPCReplacements"),
+    game_struct("OverrideEngramEntries"),
+    game_struct("OverrideNamedEngramEntries"),
+    game_struct("TamedDinoClassDamageMultipliers"),
+    game_struct("TamedDinoClassResistanceMultipliers"),
+    // Launch arguments only
+    launch("WinLiveMaxPlayers", SettingKind::Int).range(1.0, None),
+    launch("ServerPlatform", SettingKind::String),
+    launch("ClusterID", SettingKind::String),
+    launch("ClusterDirOverride", SettingKind::String),
+    launch("mods", SettingKind::String),
+];
+
+fn specs_named(name: &str) -> impl Iterator<Item = &'static SettingSpec> + '_ {
+    SCHEMA.iter().filter(move |s| s.name.eq_ignore_ascii_case(name))
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueKind {
+    UnknownKey,
+    InvalidType,
+    OutOfRange,
+    InvalidIndex,
+    DuplicateKey,
+    WrongSection,
+    WrongFile,
+    CommandLineOnly,
+    IniOnly,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    pub kind: IssueKind,
+    /// "GameUserSettings.ini", "Game.ini" or "command line".
+    pub source: String,
+    pub section: Option<String>,
+    pub key: String,
+    pub value: Option<String>,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub issues: Vec<ConfigIssue>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl ValidationReport {
+    fn push(&mut self, issue: ConfigIssue) {
+        match issue.severity {
+            IssueSeverity::Error => self.errors += 1,
+            IssueSeverity::Warning => self.warnings += 1,
+        }
+        self.issues.push(issue);
+    }
+
+    /// One line per error, for refusing a start.
+    pub fn error_summary(&self) -> String {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == IssueSeverity::Error)
+            .map(|i| i.message.clone())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Closest candidate within a typo-sized edit distance.
+fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (word.len() / 5).max(2);
+    candidates
+        .map(|c| (levenshtein(word, c), c))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+/// Spellings Unreal's bool import accepts, case-insensitively.
+const TRUE_WORDS: [&str; 4] = ["True", "Yes", "On", "1"];
+const FALSE_WORDS: [&str; 4] = ["False", "No", "Off", "0"];
+
+/// Problem found in a single value: (severity, kind, message, suggestion).
+type ValueIssue = (IssueSeverity, IssueKind, String, Option<String>);
+
+fn check_bool(spec: &SettingSpec, value: &str) -> Option<ValueIssue> {
+    let is_word = |w: &&str| w.eq_ignore_ascii_case(value);
+    if TRUE_WORDS.iter().any(is_word) || FALSE_WORDS.iter().any(is_word) {
+        return None;
+    }
+    // Unreal reads these rather than rejecting them, so they only look suspicious
+    if value.is_empty() {
+        return Some((
+            IssueSeverity::Warning,
+            IssueKind::InvalidType,
+            format!("{} is empty and will be read as False", spec.name),
+            Some("False".to_string()),
+        ));
+    }
+    if let Ok(n) = value.parse::<f64>() {
+        let read_as = if n != 0.0 { "True" } else { "False" };
+        return Some((
+            IssueSeverity::Warning,
+            IssueKind::InvalidType,
+            format!(
+                "{} expects True or False; '{}' will be read as {}",
+                spec.name, value, read_as
+            ),
+            Some(read_as.to_string()),
+        ));
+    }
+    let suggestion = closest(value, ["True", "False"].into_iter()).map(str::to_string);
+    Some((
+        IssueSeverity::Error,
+        IssueKind::InvalidType,
+        format!("{} expects True or False, got '{}'", spec.name, value),
+        suggestion,
+    ))
+}
+
+/// Checks `value` against the spec's type and range. Type and range problems are
+/// errors, except for bool spellings Unreal still reads and struct values we can't
+/// parse, which are warnings.
+fn check_value(spec: &SettingSpec, value: &str) -> Option<ValueIssue> {
+    let value = value.trim();
+    let error = |kind, message| Some((IssueSeverity::Error, kind, message, None));
+    let number = match spec.kind {
+        SettingKind::Bool => return check_bool(spec, value),
+        SettingKind::String => return None,
+        SettingKind::Struct => {
+            let message = match ue_text::parse(value) {
+                Ok(UeValue::Struct(_)) | Ok(UeValue::Array(_)) => return None,
+                Ok(_) => format!("{} expects a (...) struct value", spec.name),
+                Err(e) => format!("{} may not be a valid struct value: {}", spec.name, e),
+            };
+            return Some((IssueSeverity::Warning, IssueKind::InvalidType, message, None));
+        }
+        SettingKind::Int => match value.parse::<f64>() {
+            Ok(n) if n.fract() == 0.0 => n,
+            _ => {
+                return error(
+                    IssueKind::InvalidType,
+                    format!("{} expects a whole number, got '{}'", spec.name, value),
+                )
+            }
+        },
+        SettingKind::Float => match value.parse::<f64>() {
+            Ok(n) if n.is_finite() => n,
+            _ => {
+                return error(
+                    IssueKind::InvalidType,
+                    format!("{} expects a number, got '{}'", spec.name, value),
+                )
+            }
+        },
+    };
+
+    let below = spec.min.is_some_and(|min| number < min);
+    let above = spec.max.is_some_and(|max| number > max);
+    if below || above {
+        let range = match (spec.min, spec.max) {
+            (Some(min), Some(max)) => format!("between {} and {}", min, max),
+            (Some(min), None) => format!("at least {}", min),
+            (None, Some(max)) => format!("at most {}", max),
+            (None, None) => unreachable!(),
+        };
+        return error(
+            IssueKind::OutOfRange,
+            format!("{} must be {}, got {}", spec.name, range, value),
+        );
+    }
+    None
+}
+
+fn describe_place(spec: &SettingSpec) -> String {
+    match (spec.file, spec.section) {
+        (Some(file), Some(section)) => format!("[{}] in {}", section, file.file_name()),
+        _ => "the launch arguments".to_string(),
+    }
+}
+
+/// Validates one INI file's sections.
+pub fn validate_ini(file: IniFile, sections: &[IniSection], report: &mut ValidationReport) {
+    let source = file.file_name().to_string();
+    for section in sections {
+        let checked = CHECKED_SECTIONS
+            .iter()
+            .any(|(f, s)| *f == file && s.eq_ignore_ascii_case(&section.name));
+        let mut seen: Vec<String> = vec![];
+
+        for entry in &section.entries {
+            // Unreal array operators: +Key, -Key, .Key, !Key
+            let raw_key = entry.key.trim_start_matches(['+', '-', '.', '!']);
+            let (name, index) = ue_text::split_indexed_key(raw_key);
+            let issue = |severity, kind, message: String, suggestion: Option<String>| ConfigIssue {
+                severity,
+                kind,
+                source: source.clone(),
+                section: Some(section.name.clone()),
+                key: entry.key.clone(),
+                value: Some(entry.value.clone()),
+                message,
+                suggestion,
+            };
+
+            let candidates: Vec<&SettingSpec> = specs_named(name).collect();
+            let here = candidates.iter().find(|s| {
+                s.file == Some(file)
+                    && s.section.is_some_and(|sec| sec.eq_ignore_ascii_case(&section.name))
+            });
+
+            let spec = match (here, candidates.first()) {
+                (Some(spec), _) => *spec,
+                (None, Some(elsewhere)) => {
+                    let (kind, where_) = if elsewhere.file == Some(file) {
+                        (IssueKind::WrongSection, describe_place(elsewhere))
+                    } else if elsewhere.file.is_none() {
+                        (IssueKind::CommandLineOnly, describe_place(elsewhere))
+                    } else {
+                        (IssueKind::WrongFile, describe_place(elsewhere))
+                    };
+                    report.push(issue(
+                        IssueSeverity::Warning,
+                        kind,
+                        format!(
+                            "{} has no effect in [{}] of {}; it belongs in {}",
+                            name,
+                            section.name,
+                            file.file_name(),
+                            where_
+                        ),
+                        None,
+                    ));
+                    continue;
+                }
+                (None, None) => {
+                    if checked {
+                        let suggestion = closest(
+                            name,
+                            SCHEMA
+                                .iter()
+                                .filter(|s| s.file.is_some())
+                                .map(|s| s.name),
+                        );
+                        let message = match suggestion {
+                            Some(s) => format!("Unknown setting {} (did you mean {}?)", name, s),
+                            None => format!("Unknown setting {}", name),
+                        };
+                        report.push(issue(
+                            IssueSeverity::Warning,
+                            IssueKind::UnknownKey,
+                            message,
+                            suggestion.map(str::to_string),
+                        ));
+                    }
+                    continue;
+                }
+            };
+
+            if spec.location == SettingLocation::CommandLine {
+                let hint = spec.command_line_name.unwrap_or(spec.name);
+                report.push(issue(
+                    IssueSeverity::Warning,
+                    IssueKind::CommandLineOnly,
+                    format!("ASA ignores {} in the INI; pass {} on the command line", name, hint),
+                    Some(hint.to_string()),
+                ));
+                continue;
+            }
+
+            match (spec.index_count, index) {
+                (Some(count), Some(i)) if i >= count => report.push(issue(
+                    IssueSeverity::Error,
+                    IssueKind::InvalidIndex,
+                    format!("{} index {} is out of range (0-{})", name, i, count - 1),
+                    None,
+                )),
+                (None, Some(_)) => report.push(issue(
+                    IssueSeverity::Error,
+                    IssueKind::InvalidIndex,
+                    format!("{} is not an indexed setting", name),
+                    Some(name.to_string()),
+                )),
+                _ => {}
+            }
+
+            let key_id = raw_key.to_lowercase();
+            if !spec.repeatable && seen.contains(&key_id) {
+                report.push(issue(
+                    IssueSeverity::Warning,
+                    IssueKind::DuplicateKey,
+                    format!("{} is set more than once; only one value will be used", raw_key),
+                    None,
+                ));
+            }
+            seen.push(key_id);
+
+            if let Some((severity, kind, message, suggestion)) = check_value(spec, &entry.value) {
+                report.push(issue(severity, kind, message, suggestion));
+            }
+        }
+    }
+}
+
+/// Validates `?Key=Value` map options and `-Key=Value` arguments. Flags we don't know
+/// are left alone, since the launch argument list is open-ended.
+pub fn validate_launch_args(args: &[String], report: &mut ValidationReport) {
+    let mut options: Vec<(String, String)> = vec![];
+    for arg in args {
+        // Rendered args may carry UE-style quoting: "Map?A=b c" or -Key="a b"
+        let arg = arg.trim_matches('"');
+        if let Some(flag) = arg.strip_prefix('-') {
+            if let Some((key, value)) = flag.split_once('=') {
+                options.push((key.to_string(), value.trim_matches('"').to_string()));
+            }
+        } else {
+            // "TheIsland_WP?listen?SessionName=..."
+            for part in arg.split('?').skip(1) {
+                if let Some((key, value)) = part.split_once('=') {
+                    options.push((key.to_string(), value.to_string()));
+                }
+            }
+        }
+    }
+
+    for (key, value) in options {
+        let candidates: Vec<&SettingSpec> = specs_named(&key).collect();
+        let spec = match candidates
+            .iter()
+            .find(|s| s.location != SettingLocation::Ini)
+            .or(candidates.first())
+        {
+            Some(spec) => *spec,
+            None => continue,
+        };
+        let issue = |severity, kind, message: String| ConfigIssue {
+            severity,
+            kind,
+            source: "command line".to_string(),
+            section: None,
+            key: key.clone(),
+            value: Some(value.clone()),
+            message,
+            suggestion: None,
+        };
+
+        if spec.location == SettingLocation::Ini {
+            report.push(issue(
+                IssueSeverity::Warning,
+                IssueKind::IniOnly,
+                format!(
+                    "{} is not read from the command line; set it in {}",
+                    key,
+                    describe_place(spec)
+                ),
+            ));
+            continue;
+        }
+        if let Some((severity, kind, message, _)) = check_value(spec, &value) {
+            report.push(issue(severity, kind, message));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ini::IniDocument;
+
+    fn validate(file: IniFile, text: &str) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        validate_ini(file, &IniDocument::parse(text).sections(), &mut report);
+        report
+    }
+
+    fn only_issue(report: &ValidationReport) -> &ConfigIssue {
+        assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+        &report.issues[0]
+    }
+
+    #[test]
+    fn misspelled_key_suggests_the_known_setting() {
+        let report = validate(
+            IniFile::GameUserSettings,
+            "[ServerSettings]\nXPMultipler=2.0\n",
+        );
+        let issue = only_issue(&report);
+        assert_eq!(issue.kind, IssueKind::UnknownKey);
+        assert_eq!(issue.severity, IssueSeverity::Warning);
+        assert_eq!(issue.suggestion.as_deref(), Some("XPMultiplier"));
+        assert_eq!(report.errors, 0);
+    }
+
+    #[test]
+    fn misspelled_bool_is_an_error_with_a_suggestion() {
+        // The stray `b` prefix makes the key itself unknown...
+        let report = validate(
+            IniFile::GameUserSettings,
+            "[ServerSettings]\nbAllowFlyerCarryPVE=Ture\n",
+        );
+        let issue = only_issue(&report);
+        assert_eq!(issue.kind, IssueKind::UnknownKey);
+        assert_eq!(issue.suggestion.as_deref(), Some("AllowFlyerCarryPvE"));
+
+        // ...and once the key is right, the value is checked case-insensitively by key
+        let report = validate(
+            IniFile::GameUserSettings,
+            "[ServerSettings]\nAllowFlyerCarryPVE=Ture\n",
+        );
+        let issue = only_issue(&report);
+        assert_eq!(issue.kind, IssueKind::InvalidType);
+        assert_eq!(issue.severity, IssueSeverity::Error);
+        assert_eq!(issue.suggestion.as_deref(), Some("True"));
+        assert_eq!(report.errors, 1);
+    }
+
+    #[test]
+    fn bool_spellings_unreal_reads_are_accepted_or_warned() {
+        let report = validate(
+            IniFile::GameUserSettings,
+            "[ServerSettings]\nAllowFlyerCarryPvE=yes\nAdminLogging=OFF\n",
+        );
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+
+        let report = validate(
+            IniFile::GameUserSettings,
+            "[ServerSettings]\nAllowFlyerCarryPvE=2\nAdminLogging=\n",
+        );
+        assert_eq!(report.errors, 0);
+        assert_eq!(report.warnings, 2);
+        let suggestions: Vec<_> = report
+            .issues
+            .iter()
+            .map(|i| i.suggestion.as_deref())
+            .collect();
+        assert_eq!(suggestions, [Some("True"), Some("False")]);
+    }
+
+    #[test]
+    fn out_of_range_values_are_errors() {
+        let report = validate(
+            IniFile::GameUserSettings,
+            "[ServerSettings]\nDifficultyOffset=1.5\nRCONPort=0\nXPMultiplier=2.5\n",
+        );
+        assert_eq!(report.errors, 2, "{:?}", report.issues);
+        assert!(report
+            .issues
+            .iter()
+            .all(|i| i.kind == IssueKind::OutOfRange));
+        assert_eq!(
+            report.error_summary(),
+            "DifficultyOffset must be between 0 and 1, got 1.5\n\
+             RCONPort must be between 1 and 65535, got 0"
+        );
+    }
+
+    #[test]
+    fn settings_in_the_wrong_place_are_reported() {
+        let report = validate(
+            IniFile::GameUserSettings,
+            "[SessionSettings]\nXPMultiplier=2\n",
+        );
+        let issue = only_issue(&report);
+        assert_eq!(issue.kind, IssueKind::WrongSection);
+        assert!(
+            issue.message.contains("[ServerSettings]"),
+            "{}",
+            issue.message
+        );
+
+        let report = validate(
+            IniFile::Game,
+            "[/Script/ShooterGame.ShooterGameMode]\nXPMultiplier=2\n",
+        );
+        assert_eq!(only_issue(&report).kind, IssueKind::WrongFile);
+    }
+
+    #[test]
+    fn unparsable_structs_are_warnings() {
+        let report = validate(
+            IniFile::Game,
+            "[/Script/ShooterGame.ShooterGameMode]\n\
+             ConfigOverrideItemMaxQuantity=(ItemClassString=\"PrimalItemResource_Stone_C\"\n\
+             ConfigOverrideItemCraftingCosts=plain\n",
+        );
+        assert_eq!(report.errors, 0, "{:?}", report.issues);
+        assert_eq!(report.warnings, 2);
+    }
+}
@@ -0,0 +1,465 @@
+// src-tauri/src/cron.rs
+//
+// Five-field cron expressions (`minute hour day-of-month month day-of-week`) in local
+// time, as used by scheduled tasks. Fields take `*`, values, ranges, lists and steps
+// (`*/15`, `1-5`, `0,30`, `10-50/10`); months and weekdays also take three-letter names,
+// and Sunday is 0 or 7. `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are
+// accepted as shorthands.
+//
+// As in Vixie cron, when both day fields are restricted a day matches if either does.
+// Local times skipped by a DST change never fire; repeated ones fire once.
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike,
+};
+
+/// How far ahead `next_after` looks before deciding an expression never fires
+/// (e.g. `0 0 30 2 *`).
+const MAX_YEARS_AHEAD: i32 = 5;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+struct Field {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+    /// Value of the first name; months are 1-based, weekdays 0-based.
+    names_start: u32,
+}
+
+const MINUTE: Field = Field {
+    name: "minute",
+    min: 0,
+    max: 59,
+    names: &[],
+    names_start: 0,
+};
+const HOUR: Field = Field {
+    name: "hour",
+    min: 0,
+    max: 23,
+    names: &[],
+    names_start: 0,
+};
+const DAY: Field = Field {
+    name: "day of month",
+    min: 1,
+    max: 31,
+    names: &[],
+    names_start: 0,
+};
+const MONTH: Field = Field {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &MONTH_NAMES,
+    names_start: 1,
+};
+// 7 is accepted as Sunday and folded onto 0 after parsing
+const WEEKDAY: Field = Field {
+    name: "day of week",
+    min: 0,
+    max: 7,
+    names: &WEEKDAY_NAMES,
+    names_start: 0,
+};
+
+impl Field {
+    fn value(&self, text: &str) -> Result<u32, String> {
+        let value = match self
+            .names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(text))
+        {
+            Some(index) => index as u32 + self.names_start,
+            None => text
+                .parse()
+                .map_err(|_| format!("'{}' is not a valid {}", text, self.name))?,
+        };
+        if value < self.min || value > self.max {
+            return Err(format!(
+                "{} {} is out of range ({}-{})",
+                self.name, value, self.min, self.max
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Parses one field into a bit set of the values it allows.
+    fn parse(&self, text: &str) -> Result<u64, String> {
+        let mut bits = 0u64;
+        for part in text.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step.parse().ok().filter(|s| *s > 0).ok_or_else(|| {
+                        format!("'{}' is not a valid step in the {}", step, self.name)
+                    })?;
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+            let (start, end) = match range {
+                "*" => (self.min, self.max),
+                _ => match range.split_once('-') {
+                    Some((start, end)) => (self.value(start)?, self.value(end)?),
+                    // `5/15` runs from 5 to the end of the range
+                    None if step > 1 => (self.value(range)?, self.max),
+                    None => {
+                        let value = self.value(range)?;
+                        (value, value)
+                    }
+                },
+            };
+            if start > end {
+                return Err(format!("Range {} in the {} is reversed", range, self.name));
+            }
+            for value in (start..=end).step_by(step as usize) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(bits)
+    }
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+        let expanded = match expression.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            _ => expression,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "'{}' must have 5 fields (minute hour day month weekday)",
+                expression
+            ));
+        };
+
+        let mut weekdays = WEEKDAY.parse(weekday)?;
+        if has(weekdays, 7) {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+        Ok(CronSchedule {
+            minutes: MINUTE.parse(minute)?,
+            hours: HOUR.parse(hour)?,
+            days: DAY.parse(day)?,
+            months: MONTH.parse(month)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// The first time strictly after `after` the schedule fires, if any within
+    /// `MAX_YEARS_AHEAD` years, in `after`'s time zone.
+    pub fn next_after<Tz: TimeZone>(&self, after: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let zone = after.timezone();
+        let start = after.naive_local();
+        let mut t = start.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let last_year = start.year() + MAX_YEARS_AHEAD;
+        while t.year() <= last_year {
+            if !has(self.months, t.month()) {
+                t = first_of_next_month(t)?;
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+                continue;
+            }
+            if !has(self.hours, t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !has(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+                continue;
+            }
+            match zone.from_local_datetime(&t) {
+                LocalResult::Single(time) => return Some(time),
+                // chrono doesn't promise which of the two instants comes first
+                LocalResult::Ambiguous(a, b) => return Some(a.min(b)),
+                // Skipped by a DST change
+                LocalResult::None => t += Duration::minutes(1),
+            }
+        }
+        None
+    }
+
+    /// The next `count` times after `after`.
+    pub fn upcoming(&self, after: DateTime<Local>, count: usize) -> Vec<DateTime<Local>> {
+        let mut times = vec![];
+        let mut from = after;
+        while times.len() < count {
+            match self.next_after(from) {
+                Some(next) => {
+                    times.push(next);
+                    from = next;
+                }
+                None => break,
+            }
+        }
+        times
+    }
+}
+
+fn first_of_next_month(t: NaiveDateTime) -> Option<NaiveDateTime> {
+    let (year, month) = if t.month() == 12 {
+        (t.year() + 1, 1)
+    } else {
+        (t.year(), t.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Offset};
+
+    /// Central European time for 2024 without depending on the host's zone: UTC+1,
+    /// and UTC+2 from 31 March 01:00 UTC until 27 October 01:00 UTC.
+    #[derive(Clone, Debug)]
+    struct Cet2024;
+
+    impl Cet2024 {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+    }
+
+    impl TimeZone for Cet2024 {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Cet2024
+        }
+
+        fn offset_from_local_date(&self, _: &NaiveDate) -> LocalResult<FixedOffset> {
+            unimplemented!("only used with date-times")
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            // Listed later instant first, so callers can't rely on chrono's order
+            let valid: Vec<FixedOffset> = [Self::winter(), Self::summer()]
+                .into_iter()
+                .filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+                .collect();
+            match valid[..] {
+                [offset] => LocalResult::Single(offset),
+                [a, b] => LocalResult::Ambiguous(a, b),
+                _ => LocalResult::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, _: &NaiveDate) -> FixedOffset {
+            unimplemented!("only used with date-times")
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let start = at("2024-03-31 01:00");
+            let end = at("2024-10-27 01:00");
+            if (start..end).contains(utc) {
+                Self::summer()
+            } else {
+                Self::winter()
+            }
+        }
+    }
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn fixed(text: &str) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(0)
+            .unwrap()
+            .from_local_datetime(&at(text))
+            .unwrap()
+    }
+
+    fn cet(text: &str) -> DateTime<Cet2024> {
+        Cet2024.from_local_datetime(&at(text)).unwrap()
+    }
+
+    /// The next `count` firing times after `after`, as local "YYYY-MM-DD HH:MM" text.
+    fn next<Tz: TimeZone>(expression: &str, after: DateTime<Tz>, count: usize) -> Vec<String> {
+        let schedule = CronSchedule::parse(expression).unwrap();
+        let mut times = vec![];
+        let mut from = after;
+        while times.len() < count {
+            let Some(time) = schedule.next_after(from) else {
+                break;
+            };
+            times.push(time.naive_local().format("%Y-%m-%d %H:%M").to_string());
+            from = time;
+        }
+        times
+    }
+
+    #[test]
+    fn fires_strictly_after_the_given_time() {
+        assert_eq!(
+            next("*/15 * * * *", fixed("2024-05-01 10:15"), 3),
+            ["2024-05-01 10:30", "2024-05-01 10:45", "2024-05-01 11:00"]
+        );
+        assert_eq!(
+            next("0 4 * * *", fixed("2024-12-31 04:00"), 1),
+            ["2025-01-01 04:00"]
+        );
+    }
+
+    #[test]
+    fn steps_ranges_and_lists() {
+        assert_eq!(
+            next("10-50/20 8,20 * * *", fixed("2024-05-01 00:00"), 4),
+            [
+                "2024-05-01 08:10",
+                "2024-05-01 08:30",
+                "2024-05-01 08:50",
+                "2024-05-01 20:10"
+            ]
+        );
+        // A single start with a step runs to the end of the range
+        assert_eq!(
+            next("50/5 0 * * *", fixed("2024-05-01 00:00"), 3),
+            ["2024-05-01 00:50", "2024-05-01 00:55", "2024-05-02 00:50"]
+        );
+    }
+
+    #[test]
+    fn month_and_weekday_names() {
+        // 2024-05-01 is a Wednesday
+        assert_eq!(
+            next("0 6 * * mon-Fri", fixed("2024-05-03 07:00"), 2),
+            ["2024-05-06 06:00", "2024-05-07 06:00"]
+        );
+        assert_eq!(
+            CronSchedule::parse("0 0 1 JAN,jul *").unwrap(),
+            CronSchedule::parse("0 0 1 1,7 *").unwrap()
+        );
+        assert_eq!(
+            CronSchedule::parse("0 0 * * 7").unwrap(),
+            CronSchedule::parse("0 0 * * SUN").unwrap()
+        );
+        assert_eq!(
+            CronSchedule::parse("@daily").unwrap(),
+            CronSchedule::parse("0 0 * * *").unwrap()
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either_day() {
+        // The 13th of the month or any Friday (2024-09-13 is a Friday)
+        assert_eq!(
+            next("0 0 13 * 5", fixed("2024-09-01 00:00"), 4),
+            [
+                "2024-09-06 00:00",
+                "2024-09-13 00:00",
+                "2024-09-20 00:00",
+                "2024-09-27 00:00"
+            ]
+        );
+        assert_eq!(
+            next("0 0 13 * 1", fixed("2024-09-10 00:00"), 2),
+            ["2024-09-13 00:00", "2024-09-16 00:00"]
+        );
+        // With `*` in the weekday field only the day of month counts
+        assert_eq!(
+            next("0 0 13 * *", fixed("2024-09-01 00:00"), 1),
+            ["2024-09-13 00:00"]
+        );
+    }
+
+    #[test]
+    fn impossible_dates_never_fire() {
+        let schedule = CronSchedule::parse("0 0 30 2 *").unwrap();
+        assert_eq!(schedule.next_after(fixed("2024-01-01 00:00")), None);
+        // Leap days are years apart but still found
+        assert_eq!(
+            next("0 0 29 2 *", fixed("2024-03-01 00:00"), 1),
+            ["2028-02-29 00:00"]
+        );
+    }
+
+    #[test]
+    fn skipped_local_times_never_fire() {
+        // 02:00-02:59 doesn't exist on 31 March
+        let after = cet("2024-03-30 12:00");
+        assert_eq!(
+            next("30 2 * * *", after, 2),
+            ["2024-04-01 02:30", "2024-04-02 02:30"]
+        );
+        assert_eq!(
+            next("*/20 * * * *", cet("2024-03-31 01:30"), 3),
+            ["2024-03-31 01:40", "2024-03-31 03:00", "2024-03-31 03:20"]
+        );
+    }
+
+    #[test]
+    fn repeated_local_times_fire_once_at_the_first_instant() {
+        // 02:00-02:59 happens twice on 27 October, first in summer time
+        let after = cet("2024-10-27 00:00");
+        let schedule = CronSchedule::parse("30 2 * * *").unwrap();
+        let first = schedule.next_after(after).unwrap();
+        assert_eq!(first.naive_utc(), at("2024-10-27 00:30"));
+        assert_eq!(first.offset().fix(), Cet2024::summer());
+        let second = schedule.next_after(first).unwrap();
+        assert_eq!(second.naive_local(), at("2024-10-28 02:30"));
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "30-10 * * * *",
+            "* * * FOO *",
+        ] {
+            assert!(CronSchedule::parse(expression).is_err(), "{}", expression);
+        }
+    }
+}
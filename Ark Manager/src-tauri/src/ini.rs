@@ -0,0 +1,576 @@
+// src-tauri/src/ini.rs
+//
+// Lossless INI engine for GameUserSettings.ini and Game.ini. Every line is kept
+// verbatim (including its line ending), so a file that is parsed and written back
+// without edits is byte-for-byte identical. Section and key lookups are
+// case-insensitive like Unreal's own config system, and repeated keys are first-class
+// because Game.ini relies on them.
+
+use std::fs;
+use std::path::Path;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum IniFile {
+    GameUserSettings,
+    Game,
+}
+
+impl IniFile {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            IniFile::GameUserSettings => "GameUserSettings.ini",
+            IniFile::Game => "Game.ini",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum LineKind {
+    Blank,
+    Comment,
+    Section(String),
+    Entry { key: String, value: String },
+    /// Anything else; kept as-is and otherwise ignored.
+    Other,
+}
+
+#[derive(Clone, Debug)]
+struct Line {
+    text: String,
+    ending: String,
+    kind: LineKind,
+}
+
+impl Line {
+    fn parse(text: &str, ending: &str) -> Self {
+        let trimmed = text.trim();
+        let kind = if trimmed.is_empty() {
+            LineKind::Blank
+        } else if trimmed.starts_with(';') || trimmed.starts_with('#') {
+            LineKind::Comment
+        } else if trimmed.starts_with('[') && trimmed.ends_with(']') {
+            LineKind::Section(trimmed[1..trimmed.len() - 1].trim().to_string())
+        } else if let Some((key, value)) = trimmed.split_once('=') {
+            LineKind::Entry {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+            }
+        } else {
+            LineKind::Other
+        };
+        Line {
+            text: text.to_string(),
+            ending: ending.to_string(),
+            kind,
+        }
+    }
+
+    fn entry(key: &str, value: &str, ending: &str) -> Self {
+        Line {
+            text: format!("{}={}", key, value),
+            ending: ending.to_string(),
+            kind: LineKind::Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+            },
+        }
+    }
+
+    fn is_key(&self, wanted: &str) -> bool {
+        matches!(&self.kind, LineKind::Entry { key, .. } if key.eq_ignore_ascii_case(wanted))
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IniEntry {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IniSection {
+    pub name: String,
+    pub entries: Vec<IniEntry>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IniFileContents {
+    pub path: String,
+    pub exists: bool,
+    pub raw: String,
+    pub sections: Vec<IniSection>,
+}
+
+impl IniFileContents {
+    pub fn new(path: &Path, doc: &IniDocument) -> Self {
+        IniFileContents {
+            path: path.to_string_lossy().to_string(),
+            exists: path.exists(),
+            raw: doc.to_text(),
+            sections: doc.sections(),
+        }
+    }
+}
+
+/// One edit to apply to a document. Keys and sections match case-insensitively.
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum IniPatch {
+    /// Sets a single value, dropping any other occurrences of the key.
+    Set {
+        section: String,
+        key: String,
+        value: String,
+    },
+    /// Replaces every occurrence of a repeated key with `values`, in order.
+    SetAll {
+        section: String,
+        key: String,
+        values: Vec<String>,
+    },
+    /// Adds another occurrence of a (possibly repeated) key.
+    Add {
+        section: String,
+        key: String,
+        value: String,
+    },
+    Remove {
+        section: String,
+        key: String,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct IniDocument {
+    lines: Vec<Line>,
+    encoding: Encoding,
+    default_ending: String,
+}
+
+impl IniDocument {
+    pub fn parse(text: &str) -> Self {
+        Self::parse_with_encoding(text, Encoding::Utf8)
+    }
+
+    fn parse_with_encoding(text: &str, encoding: Encoding) -> Self {
+        let mut lines = vec![];
+        for raw in text.split_inclusive('\n') {
+            let (body, ending) = if let Some(body) = raw.strip_suffix("\r\n") {
+                (body, "\r\n")
+            } else if let Some(body) = raw.strip_suffix('\n') {
+                (body, "\n")
+            } else {
+                (raw, "")
+            };
+            lines.push(Line::parse(body, ending));
+        }
+        let default_ending = lines
+            .iter()
+            .map(|l| l.ending.as_str())
+            .find(|e| !e.is_empty())
+            .unwrap_or("\r\n")
+            .to_string();
+        IniDocument {
+            lines,
+            encoding,
+            default_ending,
+        }
+    }
+
+    /// Parses `text` as a replacement for this document, keeping its encoding.
+    pub fn reparse(&self, text: &str) -> Self {
+        Self::parse_with_encoding(text, self.encoding)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+            if rest.len() % 2 != 0 {
+                return Err("Invalid UTF-16 file: odd number of bytes.".to_string());
+            }
+            let units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            let text = String::from_utf16(&units).map_err(|e| e.to_string())?;
+            return Ok(Self::parse_with_encoding(&text, Encoding::Utf16Le));
+        }
+        if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+            let text = std::str::from_utf8(rest).map_err(|e| e.to_string())?;
+            return Ok(Self::parse_with_encoding(text, Encoding::Utf8Bom));
+        }
+        let text = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+        Ok(Self::parse_with_encoding(text, Encoding::Utf8))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for line in &self.lines {
+            out.push_str(&line.text);
+            out.push_str(&line.ending);
+        }
+        out
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let text = self.to_text();
+        match self.encoding {
+            Encoding::Utf8 => text.into_bytes(),
+            Encoding::Utf8Bom => {
+                let mut bytes = vec![0xEF, 0xBB, 0xBF];
+                bytes.extend_from_slice(text.as_bytes());
+                bytes
+            }
+            Encoding::Utf16Le => {
+                let mut bytes = vec![0xFF, 0xFE];
+                for unit in text.encode_utf16() {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+                bytes
+            }
+        }
+    }
+
+    /// Section name each line belongs to ("" before the first header).
+    fn line_sections(&self) -> Vec<&str> {
+        let mut current = "";
+        self.lines
+            .iter()
+            .map(|line| {
+                if let LineKind::Section(name) = &line.kind {
+                    current = name.as_str();
+                }
+                current
+            })
+            .collect()
+    }
+
+    /// Indices of the key's entries across every section matching `section`.
+    fn key_indices(&self, section: &str, key: &str) -> Vec<usize> {
+        self.line_sections()
+            .iter()
+            .enumerate()
+            .filter(|(i, name)| name.eq_ignore_ascii_case(section) && self.lines[*i].is_key(key))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Sections in file order; repeated headers (in any case) are merged into the first.
+    pub fn sections(&self) -> Vec<IniSection> {
+        let mut sections: Vec<IniSection> = vec![];
+        let names = self.line_sections();
+        for (line, name) in self.lines.iter().zip(names) {
+            let index = match sections
+                .iter()
+                .position(|s| s.name.eq_ignore_ascii_case(name))
+            {
+                Some(index) => index,
+                None => {
+                    if name.is_empty() && !matches!(line.kind, LineKind::Entry { .. }) {
+                        continue;
+                    }
+                    sections.push(IniSection {
+                        name: name.to_string(),
+                        entries: vec![],
+                    });
+                    sections.len() - 1
+                }
+            };
+            if let LineKind::Entry { key, value } = &line.kind {
+                sections[index].entries.push(IniEntry {
+                    key: key.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+        sections
+    }
+
+    /// Where a new entry for `section` goes: after its last entry (or its header), so
+    /// comments and blank lines leading into the next section stay with that section.
+    /// Creates the section at the end of the file if it does not exist yet.
+    fn insertion_point(&mut self, section: &str) -> usize {
+        let names = self.line_sections();
+        let last = names
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(i, name)| {
+                name.eq_ignore_ascii_case(section)
+                    && !matches!(self.lines[*i].kind, LineKind::Blank | LineKind::Comment)
+            })
+            .map(|(i, _)| i);
+        if let Some(last) = last {
+            return last + 1;
+        }
+
+        if section.is_empty() {
+            return 0;
+        }
+        if let Some(previous) = self.lines.last() {
+            if previous.kind != LineKind::Blank {
+                self.push_line(Line::parse("", ""));
+            }
+        }
+        self.push_line(Line::parse(&format!("[{}]", section), ""));
+        self.lines.len()
+    }
+
+    /// Appends a line, giving the previous last line an ending if it had none.
+    fn push_line(&mut self, line: Line) {
+        let ending = self.default_ending.clone();
+        if let Some(previous) = self.lines.last_mut() {
+            if previous.ending.is_empty() {
+                previous.ending = ending.clone();
+            }
+        }
+        self.lines.push(Line {
+            ending,
+            ..line
+        });
+    }
+
+    fn insert_line(&mut self, index: usize, line: Line) {
+        if index >= self.lines.len() {
+            self.push_line(line);
+        } else {
+            self.lines.insert(index, line);
+        }
+    }
+
+    fn set_value(&mut self, index: usize, value: &str) {
+        let line = &mut self.lines[index];
+        if let LineKind::Entry { value: old, .. } = &mut line.kind {
+            if old != value {
+                // Keep the key's spelling and the spacing around '=' untouched
+                let eq = line.text.find('=').map_or(line.text.len(), |i| i + 1);
+                let rest = &line.text[eq..];
+                let spacing = &rest[..rest.len() - rest.trim_start().len()];
+                line.text = format!("{}{}{}", &line.text[..eq], spacing, value);
+                *old = value.to_string();
+            }
+        }
+    }
+
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        self.set_all(section, key, &[value.to_string()]);
+    }
+
+    pub fn set_all(&mut self, section: &str, key: &str, values: &[String]) {
+        let indices = self.key_indices(section, key);
+        let ending = self.default_ending.clone();
+
+        // Reuse existing lines in place, then drop or add the difference
+        for (index, value) in indices.iter().zip(values) {
+            self.set_value(*index, value);
+        }
+        for index in indices.iter().skip(values.len()).rev() {
+            self.lines.remove(*index);
+        }
+        if values.len() > indices.len() {
+            let at = match indices.last() {
+                Some(last) => last + 1,
+                None => self.insertion_point(section),
+            };
+            for (offset, value) in values[indices.len()..].iter().enumerate() {
+                self.insert_line(at + offset, Line::entry(key, value, &ending));
+            }
+        }
+    }
+
+    pub fn add(&mut self, section: &str, key: &str, value: &str) {
+        let ending = self.default_ending.clone();
+        let at = match self.key_indices(section, key).last() {
+            Some(last) => last + 1,
+            None => self.insertion_point(section),
+        };
+        self.insert_line(at, Line::entry(key, value, &ending));
+    }
+
+    pub fn remove(&mut self, section: &str, key: &str) {
+        for index in self.key_indices(section, key).into_iter().rev() {
+            self.lines.remove(index);
+        }
+    }
+
+    pub fn apply(&mut self, patch: &IniPatch) {
+        match patch {
+            IniPatch::Set {
+                section,
+                key,
+                value,
+            } => self.set(section, key, value),
+            IniPatch::SetAll {
+                section,
+                key,
+                values,
+            } => self.set_all(section, key, values),
+            IniPatch::Add {
+                section,
+                key,
+                value,
+            } => self.add(section, key, value),
+            IniPatch::Remove { section, key } => self.remove(section, key),
+        }
+    }
+}
+
+pub fn read_document(path: &Path) -> Result<IniDocument, String> {
+    if !path.exists() {
+        return Ok(IniDocument::parse(""));
+    }
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    IniDocument::from_bytes(&bytes).map_err(|e| format!("Failed to decode {:?}: {}", path, e))
+}
+
+/// Writes through a temp file in the same directory and renames it over the target.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp_path = path.with_extension("ini.tmp");
+    fs::write(&tmp_path, bytes).map_err(|e| format!("Failed to write {:?}: {}", tmp_path, e))?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to replace {:?}: {}", path, e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "[ServerSettings]\r\n\
+        ServerPassword=\r\n\
+        XPMultiplier = 1.5\r\n\
+        ; Überschrift für Spieler\r\n\
+        \r\n\
+        [/Script/ShooterGame.ShooterGameMode]\r\n\
+        ConfigOverrideItemMaxQuantity=(ItemClassString=\"A\")\r\n\
+        ConfigOverrideItemMaxQuantity=(ItemClassString=\"B\")\r\n\
+        bDisableStructurePlacementCollision=False";
+
+    fn round_trip(bytes: &[u8]) -> Vec<u8> {
+        IniDocument::from_bytes(bytes).unwrap().to_bytes()
+    }
+
+    #[test]
+    fn utf8_files_are_written_back_unchanged() {
+        assert_eq!(round_trip(SAMPLE.as_bytes()), SAMPLE.as_bytes());
+        let unix = SAMPLE.replace("\r\n", "\n") + "\n";
+        assert_eq!(round_trip(unix.as_bytes()), unix.as_bytes());
+        assert_eq!(round_trip(b""), b"");
+    }
+
+    #[test]
+    fn utf8_bom_files_are_written_back_unchanged() {
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend_from_slice(SAMPLE.as_bytes());
+        assert_eq!(round_trip(&bytes), bytes);
+    }
+
+    #[test]
+    fn utf16_files_are_written_back_unchanged() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in SAMPLE.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        assert_eq!(round_trip(&bytes), bytes);
+
+        // Edits keep the encoding
+        let mut doc = IniDocument::from_bytes(&bytes).unwrap();
+        doc.set("ServerSettings", "XPMultiplier", "2.0");
+        assert_eq!(&doc.to_bytes()[..2], &[0xFF, 0xFE]);
+        assert!(doc.to_text().contains("XPMultiplier = 2.0\r\n"));
+    }
+
+    #[test]
+    fn set_replaces_in_place_and_drops_duplicates() {
+        let mut doc =
+            IniDocument::parse("[serversettings]\nxpmultiplier = 1\nA=1\nXPMultiplier=3\n");
+        doc.set("ServerSettings", "XPMultiplier", "2");
+        assert_eq!(doc.to_text(), "[serversettings]\nxpmultiplier = 2\nA=1\n");
+    }
+
+    #[test]
+    fn set_appends_new_keys_after_the_last_entry() {
+        let mut doc = IniDocument::parse(SAMPLE);
+        doc.set("ServerSettings", "AdminLogging", "True");
+        assert_eq!(
+            doc.to_text(),
+            SAMPLE.replace(
+                "XPMultiplier = 1.5\r\n",
+                "XPMultiplier = 1.5\r\nAdminLogging=True\r\n"
+            )
+        );
+
+        let mut doc = IniDocument::parse("[A]\nX=1\n\n; About section B\n[B]\nY=2");
+        doc.set("A", "Z", "3");
+        assert_eq!(
+            doc.to_text(),
+            "[A]\nX=1\nZ=3\n\n; About section B\n[B]\nY=2"
+        );
+
+        let mut doc = IniDocument::parse("[A]\n; Nothing here yet\n[B]\n");
+        doc.set("A", "Z", "3");
+        assert_eq!(doc.to_text(), "[A]\nZ=3\n; Nothing here yet\n[B]\n");
+    }
+
+    #[test]
+    fn set_creates_missing_sections_at_the_end() {
+        let mut doc = IniDocument::parse("[A]\r\nX=1");
+        doc.set("B", "Y", "2");
+        assert_eq!(doc.to_text(), "[A]\r\nX=1\r\n\r\n[B]\r\nY=2\r\n");
+    }
+
+    #[test]
+    fn set_all_reuses_adds_and_drops_lines() {
+        let section = "/Script/ShooterGame.ShooterGameMode";
+        let key = "ConfigOverrideItemMaxQuantity";
+        let mut doc = IniDocument::parse(SAMPLE);
+        let values = ["(A)", "(B)", "(C)"].map(String::from);
+        doc.set_all(section, key, &values);
+        let entries = &doc.sections()[1].entries;
+        let got: Vec<&str> = entries
+            .iter()
+            .filter(|e| e.key == key)
+            .map(|e| e.value.as_str())
+            .collect();
+        assert_eq!(got, ["(A)", "(B)", "(C)"]);
+        // New occurrences follow the existing ones
+        assert_eq!(entries[2].value, "(C)");
+
+        doc.set_all(section, key, &values[..1]);
+        assert_eq!(doc.sections()[1].entries.len(), 2);
+        assert!(doc
+            .to_text()
+            .contains("ConfigOverrideItemMaxQuantity=(A)\r\nbDisable"));
+    }
+
+    #[test]
+    fn add_appends_after_the_last_occurrence() {
+        let mut doc = IniDocument::parse("[G]\n+Item=1\nOther=x\n+Item=2\n; end\n");
+        doc.add("g", "+item", "3");
+        assert_eq!(
+            doc.to_text(),
+            "[G]\n+Item=1\nOther=x\n+Item=2\n+item=3\n; end\n"
+        );
+    }
+
+    #[test]
+    fn remove_drops_every_occurrence_in_merged_sections() {
+        let mut doc = IniDocument::parse("[A]\nX=1\nY=2\n[B]\nX=3\n[a]\nx=4\n");
+        doc.remove("A", "X");
+        assert_eq!(doc.to_text(), "[A]\nY=2\n[B]\nX=3\n[a]\n");
+    }
+}
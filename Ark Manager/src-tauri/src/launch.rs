@@ -0,0 +1,421 @@
+// src-tauri/src/launch.rs
+//
+// Typed launch spec for the ASA server. The backend renders the map URL
+// (`TheIsland_WP?SessionName=...?RCONPort=...`) and the `-Flag=Value` list itself, so
+// every launch can be validated and logged, and the UI can preview the exact command
+// line before starting.
+//
+// A native Windows server gets raw command-line text: UE parses the process command
+// line itself, so a value with spaces has to look like `-Key="a b"`, not `"-Key=a b"`.
+// Wine and Proton take ordinary argv elements and build the Windows command line
+// themselves, quoting and escaping as they go, so for them every token is rendered
+// unquoted.
+
+use crate::config_schema::ValidationReport;
+use tokio::process::Command;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchOption {
+    pub key: String,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchSpec {
+    pub map: String,
+    #[serde(default)]
+    pub session_name: Option<String>,
+    #[serde(default)]
+    pub server_pve: Option<bool>,
+    #[serde(default)]
+    pub game_port: Option<u16>,
+    #[serde(default)]
+    pub query_port: Option<u16>,
+    #[serde(default)]
+    pub rcon_enabled: bool,
+    #[serde(default)]
+    pub rcon_port: Option<u16>,
+    #[serde(default)]
+    pub server_password: Option<String>,
+    #[serde(default)]
+    pub admin_password: Option<String>,
+    #[serde(default)]
+    pub max_players: Option<u32>,
+    #[serde(default)]
+    pub multihome: Option<String>,
+    #[serde(default)]
+    pub server_platform: Option<String>,
+    /// CurseForge project IDs.
+    #[serde(default)]
+    pub mods: Vec<String>,
+    #[serde(default)]
+    pub cluster_id: Option<String>,
+    #[serde(default)]
+    pub cluster_dir_override: Option<String>,
+    #[serde(default)]
+    pub no_transfer_from_filtering: bool,
+    #[serde(default)]
+    pub no_battleye: bool,
+    #[serde(default)]
+    pub server_game_log: bool,
+    /// Additional `?Key=Value` (or bare `?Key`) map URL options.
+    #[serde(default)]
+    pub extra_options: Vec<LaunchOption>,
+    /// Additional `-Flag` / `-Flag=Value` arguments, passed through as written.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchPreview {
+    pub program: String,
+    pub args: Vec<String>,
+    pub command_line: String,
+    pub validation: ValidationReport,
+}
+
+fn is_blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|v| v.trim().is_empty())
+}
+
+fn check_value(name: &str, value: &str, in_url: bool) -> Result<(), String> {
+    if value.contains('"') {
+        return Err(format!("{} may not contain double quotes", name));
+    }
+    if in_url && value.contains('?') {
+        return Err(format!("{} may not contain '?'", name));
+    }
+    if value.contains(['\r', '\n']) {
+        return Err(format!("{} may not contain line breaks", name));
+    }
+    Ok(())
+}
+
+/// Renders `-Key=Value`; as a raw token the value is quoted when it contains whitespace.
+fn flag(key: &str, value: &str, raw: bool) -> Result<String, String> {
+    check_value(key, value, false)?;
+    if raw && value.chars().any(char::is_whitespace) {
+        Ok(format!("-{}=\"{}\"", key, value))
+    } else {
+        Ok(format!("-{}={}", key, value))
+    }
+}
+
+impl LaunchSpec {
+    fn url_options(&self) -> Result<Vec<(String, Option<String>)>, String> {
+        let mut options = vec![];
+        if let Some(name) = self.session_name.as_ref().filter(|n| !n.trim().is_empty()) {
+            options.push(("SessionName".to_string(), Some(name.clone())));
+        }
+        if let Some(pve) = self.server_pve {
+            let pve = if pve { "True" } else { "False" };
+            options.push(("ServerPVE".to_string(), Some(pve.to_string())));
+        }
+        if self.rcon_enabled {
+            options.push(("RCONEnabled".to_string(), Some("True".to_string())));
+            if let Some(port) = self.rcon_port {
+                options.push(("RCONPort".to_string(), Some(port.to_string())));
+            }
+        }
+        for option in &self.extra_options {
+            let key = option.key.trim();
+            if key.is_empty() || key.contains(['?', '=', ' ', '"']) {
+                return Err(format!("Invalid map option name '{}'", option.key));
+            }
+            options.push((key.to_string(), option.value.clone()));
+        }
+        for (key, value) in &options {
+            if let Some(value) = value {
+                check_value(key, value, true)?;
+            }
+        }
+        Ok(options)
+    }
+
+    /// Renders the argument list, as raw command-line tokens when `raw` is set (see
+    /// `ServerRuntime::takes_raw_args`) and as plain argv elements otherwise.
+    pub fn render(&self, raw: bool) -> Result<Vec<String>, String> {
+        let map = self.map.trim();
+        if map.is_empty() || map.contains(['?', '"']) || map.chars().any(char::is_whitespace) {
+            return Err(format!("Invalid map name '{}'", self.map));
+        }
+
+        let mut url = map.to_string();
+        for (key, value) in self.url_options()? {
+            url.push('?');
+            url.push_str(&key);
+            if let Some(value) = value {
+                url.push('=');
+                url.push_str(&value);
+            }
+        }
+        // The whole URL is one token; UE strips surrounding quotes from it
+        if raw && url.chars().any(char::is_whitespace) {
+            url = format!("\"{}\"", url);
+        }
+
+        let mut args = vec![url];
+        if let Some(port) = self.game_port {
+            args.push(format!("-Port={}", port));
+        }
+        if let Some(port) = self.query_port {
+            args.push(format!("-QueryPort={}", port));
+        }
+        if let Some(max_players) = self.max_players {
+            args.push(format!("-WinLiveMaxPlayers={}", max_players));
+        }
+        if !is_blank(&self.multihome) {
+            args.push(flag(
+                "MultiHome",
+                self.multihome.as_deref().unwrap_or_default().trim(),
+                raw,
+            )?);
+        }
+        if !is_blank(&self.server_platform) {
+            args.push(flag(
+                "ServerPlatform",
+                self.server_platform.as_deref().unwrap_or_default().trim(),
+                raw,
+            )?);
+        }
+        if self.server_game_log {
+            args.push("-servergamelog".to_string());
+        }
+        if !is_blank(&self.server_password) {
+            args.push(flag(
+                "ServerPassword",
+                self.server_password.as_deref().unwrap_or_default(),
+                raw,
+            )?);
+        }
+        if !is_blank(&self.admin_password) {
+            args.push(flag(
+                "ServerAdminPassword",
+                self.admin_password.as_deref().unwrap_or_default(),
+                raw,
+            )?);
+        }
+
+        let mods: Vec<&str> = self
+            .mods
+            .iter()
+            .map(|m| m.trim())
+            .filter(|m| !m.is_empty())
+            .collect();
+        if let Some(bad) = mods.iter().find(|m| !m.chars().all(|c| c.is_ascii_digit())) {
+            return Err(format!("Invalid mod ID '{}'; mod IDs are numeric", bad));
+        }
+        if !mods.is_empty() {
+            args.push(format!("-mods={}", mods.join(",")));
+        }
+
+        if !is_blank(&self.cluster_id) {
+            args.push(flag(
+                "ClusterID",
+                self.cluster_id.as_deref().unwrap_or_default().trim(),
+                raw,
+            )?);
+            if !is_blank(&self.cluster_dir_override) {
+                args.push(flag(
+                    "ClusterDirOverride",
+                    self.cluster_dir_override
+                        .as_deref()
+                        .unwrap_or_default()
+                        .trim(),
+                    raw,
+                )?);
+            }
+            if self.no_transfer_from_filtering {
+                args.push("-NoTransferFromFiltering".to_string());
+            }
+        }
+        if self.no_battleye {
+            args.push("-NoBattlEye".to_string());
+        }
+
+        for extra in &self.extra_args {
+            let extra = extra.trim();
+            if extra.is_empty() {
+                continue;
+            }
+            if !extra.starts_with('-') {
+                return Err(format!("Extra argument '{}' must start with '-'", extra));
+            }
+            args.push(extra.to_string());
+        }
+        Ok(args)
+    }
+}
+
+/// Masks password values in a rendered token, for logging.
+pub fn redact(arg: &str) -> String {
+    let quoted = arg.len() > 1 && arg.starts_with('"') && arg.ends_with('"');
+    let inner = if quoted { &arg[1..arg.len() - 1] } else { arg };
+    let parts: Vec<String> = inner
+        .split('?')
+        .enumerate()
+        .map(|(i, part)| match part.split_once('=') {
+            Some((key, _))
+                if (i > 0 || key.starts_with('-')) && key.to_lowercase().contains("password") =>
+            {
+                format!("{}=****", key)
+            }
+            _ => part.to_string(),
+        })
+        .collect();
+    let redacted = parts.join("?");
+    if quoted {
+        format!("\"{}\"", redacted)
+    } else {
+        redacted
+    }
+}
+
+/// Describes the command `cmd` will run. Raw tokens are shown as-is; other arguments
+/// are quoted when they contain whitespace.
+pub fn preview(cmd: &Command, validation: ValidationReport) -> LaunchPreview {
+    let std_cmd = cmd.as_std();
+    let program = std_cmd.get_program().to_string_lossy().to_string();
+    let args: Vec<String> = std_cmd
+        .get_args()
+        .map(|a| a.to_string_lossy().to_string())
+        .collect();
+
+    let quote = |s: &str| {
+        if s.chars().any(char::is_whitespace) && !s.contains('"') {
+            format!("\"{}\"", s)
+        } else {
+            s.to_string()
+        }
+    };
+    let mut command_line = quote(&program);
+    for arg in &args {
+        command_line.push(' ');
+        command_line.push_str(&quote(arg));
+    }
+
+    LaunchPreview {
+        program,
+        args,
+        command_line,
+        validation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::ServerRuntime;
+
+    fn spec() -> LaunchSpec {
+        LaunchSpec {
+            map: "TheIsland_WP".to_string(),
+            session_name: Some("My Server".to_string()),
+            game_port: Some(7777),
+            rcon_enabled: true,
+            rcon_port: Some(27020),
+            server_password: Some("a b".to_string()),
+            admin_password: Some("secret".to_string()),
+            cluster_id: Some("c1".to_string()),
+            cluster_dir_override: Some("/srv/ark clusters".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn command_args(cmd: &Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn raw_tokens_quote_values_with_spaces() {
+        assert_eq!(
+            spec().render(true).unwrap(),
+            vec![
+                "\"TheIsland_WP?SessionName=My Server?RCONEnabled=True?RCONPort=27020\"",
+                "-Port=7777",
+                "-ServerPassword=\"a b\"",
+                "-ServerAdminPassword=secret",
+                "-ClusterID=c1",
+                "-ClusterDirOverride=\"/srv/ark clusters\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn argv_elements_are_unquoted() {
+        assert_eq!(
+            spec().render(false).unwrap(),
+            vec![
+                "TheIsland_WP?SessionName=My Server?RCONEnabled=True?RCONPort=27020",
+                "-Port=7777",
+                "-ServerPassword=a b",
+                "-ServerAdminPassword=secret",
+                "-ClusterID=c1",
+                "-ClusterDirOverride=/srv/ark clusters",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_native_windows_takes_raw_args() {
+        let wine = ServerRuntime::Wine {
+            wine_binary: None,
+            prefix: None,
+        };
+        let proton = ServerRuntime::Proton {
+            proton_path: "/opt/proton".to_string(),
+            compat_data_path: None,
+            steam_client_path: None,
+        };
+        assert!(!wine.takes_raw_args());
+        assert!(!proton.takes_raw_args());
+        assert_eq!(
+            ServerRuntime::Native.takes_raw_args(),
+            cfg!(target_os = "windows")
+        );
+    }
+
+    #[test]
+    fn wine_gets_each_token_as_one_argv_element() {
+        let runtime = ServerRuntime::Wine {
+            wine_binary: None,
+            prefix: None,
+        };
+        let args = spec().render(runtime.takes_raw_args()).unwrap();
+        let cmd = runtime
+            .server_command("/srv/ark", "/srv/ark/ArkAscendedServer.exe", &args, false)
+            .unwrap();
+        let mut expected = vec!["/srv/ark/ArkAscendedServer.exe".to_string()];
+        expected.extend(args);
+        assert_eq!(command_args(&cmd), expected);
+        assert!(command_args(&cmd).iter().all(|a| !a.contains('"')));
+    }
+
+    #[test]
+    fn proton_gets_each_token_as_one_argv_element() {
+        let dir = std::env::temp_dir().join(format!("launch-proton-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("proton"), "").unwrap();
+        let runtime = ServerRuntime::Proton {
+            proton_path: dir.to_string_lossy().to_string(),
+            compat_data_path: Some(dir.join("prefix").to_string_lossy().to_string()),
+            steam_client_path: Some(dir.to_string_lossy().to_string()),
+        };
+        let args = spec().render(runtime.takes_raw_args()).unwrap();
+        let cmd = runtime
+            .server_command("/srv/ark", "ArkAscendedServer.exe", &args, false)
+            .unwrap();
+        let mut expected = vec!["run".to_string(), "ArkAscendedServer.exe".to_string()];
+        expected.extend(args);
+        assert_eq!(command_args(&cmd), expected);
+        assert!(command_args(&cmd).iter().all(|a| !a.contains('"')));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,143 @@
+// src-tauri/src/layout.rs
+//
+// Where things live inside an install. Two layouts exist in the wild: the server
+// installed under the manager's own SteamCMD (`steamcmd/steamapps/common/ARK Survival
+// Ascended Dedicated Server`) and the server installed directly into the install path.
+// Everything that needs a server path resolves it through `InstallLayout` instead of
+// guessing.
+
+use std::path::{Path, PathBuf};
+
+const SERVER_APP_ID: &str = "2430930";
+const STEAMCMD_SERVER_DIR: &str = "ARK Survival Ascended Dedicated Server";
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LayoutKind {
+    /// Server files under `<install>/steamcmd/steamapps/common/...`.
+    SteamCmd,
+    /// Server files directly in `<install>`.
+    Direct,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallLayout {
+    pub kind: LayoutKind,
+    pub install_path: PathBuf,
+    /// Directory SteamCMD installs the server into (contains `ShooterGame`).
+    pub server_root: PathBuf,
+    pub executable: PathBuf,
+    pub saved_dir: PathBuf,
+    pub logs_dir: PathBuf,
+    pub log_file: PathBuf,
+    pub config_dir: PathBuf,
+    pub mods_dir: PathBuf,
+    /// The first app manifest found, if any.
+    pub manifest_path: Option<PathBuf>,
+    /// Every place the manifest was looked for, in order.
+    pub manifest_candidates: Vec<PathBuf>,
+    pub executable_found: bool,
+    /// Human readable problems found while validating the install.
+    pub issues: Vec<String>,
+}
+
+fn executable_in(server_root: &Path) -> PathBuf {
+    server_root
+        .join("ShooterGame")
+        .join("Binaries")
+        .join("Win64")
+        .join("ArkAscendedServer.exe")
+}
+
+impl InstallLayout {
+    /// Detects which layout `install_path` uses. Prefers the layout whose executable
+    /// exists; with no server installed yet it falls back to the direct layout, which is
+    /// where our SteamCMD jobs install to.
+    pub fn detect(install_path: &str) -> Self {
+        let install = PathBuf::from(install_path);
+        let steamcmd_root = install
+            .join("steamcmd")
+            .join("steamapps")
+            .join("common")
+            .join(STEAMCMD_SERVER_DIR);
+
+        let kind = if executable_in(&steamcmd_root).exists() {
+            LayoutKind::SteamCmd
+        } else if executable_in(&install).exists() {
+            LayoutKind::Direct
+        } else if steamcmd_root.join("ShooterGame").exists() {
+            LayoutKind::SteamCmd
+        } else {
+            LayoutKind::Direct
+        };
+
+        let server_root = match kind {
+            LayoutKind::SteamCmd => steamcmd_root,
+            LayoutKind::Direct => install.clone(),
+        };
+        Self::for_root(kind, install, server_root)
+    }
+
+    fn for_root(kind: LayoutKind, install_path: PathBuf, server_root: PathBuf) -> Self {
+        let shooter_game = server_root.join("ShooterGame");
+        let saved_dir = shooter_game.join("Saved");
+        let logs_dir = saved_dir.join("Logs");
+        let executable = executable_in(&server_root);
+        let manifest_name = format!("appmanifest_{}.acf", SERVER_APP_ID);
+
+        let mut manifest_candidates = vec![];
+        match kind {
+            LayoutKind::SteamCmd => {
+                manifest_candidates.push(
+                    install_path
+                        .join("steamcmd")
+                        .join("steamapps")
+                        .join(&manifest_name),
+                );
+                // Written by jobs that force_install_dir into the server root
+                manifest_candidates.push(server_root.join("steamapps").join(&manifest_name));
+            }
+            LayoutKind::Direct => {
+                manifest_candidates.push(install_path.join("steamapps").join(&manifest_name));
+                // Installed inside a Steam library: <library>/steamapps/common/<server>
+                if let Some(steamapps) = install_path.parent().and_then(|p| p.parent()) {
+                    manifest_candidates.push(steamapps.join(&manifest_name));
+                }
+            }
+        }
+        let manifest_path = manifest_candidates.iter().find(|p| p.exists()).cloned();
+
+        let executable_found = executable.exists();
+        let mut issues = vec![];
+        if !install_path.exists() {
+            issues.push(format!("Install path {:?} does not exist.", install_path));
+        }
+        if !executable_found {
+            issues.push(format!("Server executable not found at {:?}.", executable));
+        }
+        if manifest_path.is_none() {
+            issues.push("App manifest not found; the installed build is unknown.".to_string());
+        }
+
+        InstallLayout {
+            kind,
+            install_path,
+            log_file: logs_dir.join("ShooterGame.log"),
+            config_dir: saved_dir.join("Config").join("WindowsServer"),
+            mods_dir: shooter_game
+                .join("Binaries")
+                .join("Win64")
+                .join("ShooterGame")
+                .join("Mods"),
+            server_root,
+            executable,
+            saved_dir,
+            logs_dir,
+            manifest_path,
+            manifest_candidates,
+            executable_found,
+            issues,
+        }
+    }
+}
@@ -0,0 +1,449 @@
+// src-tauri/src/log_archive.rs
+//
+// On-disk archive of everything a profile's console shows: server log lines, manager
+// lines and RCON traffic. Each server session writes JSON-lines segments under
+// `<app data>/logs/<profile>/`; a segment is gzipped when it reaches
+// `SEGMENT_MAX_BYTES` or the session ends, and the oldest segments are pruned once a
+// profile's archive passes `PROFILE_MAX_BYTES`. `search` scans the segments so
+// incidents can be investigated after the fact.
+
+use chrono::{DateTime, FixedOffset, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use regex::RegexBuilder;
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const SEGMENT_MAX_BYTES: u64 = 8 * 1024 * 1024;
+const PROFILE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+const ACTIVE_EXT: &str = "jsonl";
+const COMPRESSED_EXT: &str = "jsonl.gz";
+const DEFAULT_PAGE_SIZE: usize = 200;
+const MAX_PAGE_SIZE: usize = 2000;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogSource {
+    Server,
+    Manager,
+    Rcon,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Error,
+    Warning,
+    Info,
+}
+
+impl LogLevel {
+    /// Best-effort level from the line text: UE categories print `Error:` / `Warning:`,
+    /// manager lines use the ❌ / ⚠️ markers.
+    fn infer(line: &str) -> Self {
+        if line.contains("Error:")
+            || line.contains("Fatal")
+            || line.contains("Assertion failed")
+            || line.contains('❌')
+        {
+            LogLevel::Error
+        } else if line.contains("Warning:") || line.contains('⚠') {
+            LogLevel::Warning
+        } else {
+            LogLevel::Info
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedLine {
+    /// RFC 3339 time the manager saw the line.
+    pub ts: String,
+    pub session: String,
+    pub source: LogSource,
+    pub level: LogLevel,
+    pub line: String,
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSearchQuery {
+    /// RFC 3339 bounds, inclusive.
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub regex: Option<String>,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    #[serde(default)]
+    pub levels: Option<Vec<LogLevel>>,
+    #[serde(default)]
+    pub sources: Option<Vec<LogSource>>,
+    #[serde(default)]
+    pub session: Option<String>,
+    #[serde(default)]
+    pub offset: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSearchPage {
+    pub lines: Vec<ArchivedLine>,
+    /// Matching lines across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub has_more: bool,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSessionInfo {
+    pub session: String,
+    pub segments: usize,
+    pub bytes: u64,
+    pub active: bool,
+}
+
+struct ActiveSegment {
+    session: String,
+    seq: u32,
+    path: PathBuf,
+    file: File,
+    bytes: u64,
+}
+
+pub struct LogArchive {
+    root: PathBuf,
+    active: Mutex<HashMap<String, ActiveSegment>>,
+}
+
+/// Profile ids come from the UI; keep them to safe file name characters.
+pub fn profile_dir_name(profile_id: &str) -> String {
+    profile_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn segment_name(session: &str, seq: u32, ext: &str) -> String {
+    format!("{}_{:04}.{}", session, seq, ext)
+}
+
+/// `20240501-101112_0003.jsonl.gz` -> ("20240501-101112", compressed)
+fn parse_segment_name(name: &str) -> Option<(&str, bool)> {
+    let (stem, compressed) = match name.strip_suffix(&format!(".{}", COMPRESSED_EXT)) {
+        Some(stem) => (stem, true),
+        None => (name.strip_suffix(&format!(".{}", ACTIVE_EXT))?, false),
+    };
+    let (session, _seq) = stem.rsplit_once('_')?;
+    Some((session, compressed))
+}
+
+/// Gzips `path` to `<path>.gz` and removes the original.
+fn compress_segment(path: &Path) -> io::Result<()> {
+    let gz_path = path.with_extension(COMPRESSED_EXT);
+    let tmp_path = gz_path.with_extension("gz.tmp");
+    {
+        let mut input = File::open(path)?;
+        let mut encoder = GzEncoder::new(File::create(&tmp_path)?, Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+    }
+    fs::rename(&tmp_path, &gz_path)?;
+    fs::remove_file(path)
+}
+
+fn list_segments(dir: &Path) -> Vec<PathBuf> {
+    let mut segments: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .and_then(parse_segment_name)
+                        .is_some()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // Session names and zero-padded sequence numbers sort chronologically
+    segments.sort();
+    segments
+}
+
+fn segment_session(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    parse_segment_name(name).map(|(session, _)| session.to_string())
+}
+
+fn parse_time(value: &Option<String>, name: &str) -> Result<Option<DateTime<FixedOffset>>, String> {
+    value
+        .as_deref()
+        .map(|v| {
+            DateTime::parse_from_rfc3339(v)
+                .map_err(|e| format!("Invalid '{}' time '{}': {}", name, v, e))
+        })
+        .transpose()
+}
+
+impl LogArchive {
+    pub fn new(root: PathBuf) -> Self {
+        LogArchive {
+            root,
+            active: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn profile_dir(&self, profile_id: &str) -> PathBuf {
+        self.root.join(profile_dir_name(profile_id))
+    }
+
+    fn open_segment(
+        &self,
+        profile_id: &str,
+        session: String,
+        seq: u32,
+    ) -> io::Result<ActiveSegment> {
+        let dir = self.profile_dir(profile_id);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(segment_name(&session, seq, ACTIVE_EXT));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let bytes = file.metadata()?.len();
+        Ok(ActiveSegment {
+            session,
+            seq,
+            path,
+            file,
+            bytes,
+        })
+    }
+
+    /// Starts a new session for `profile_id`, closing the previous one. Segments left
+    /// uncompressed by an earlier run of the manager are compressed now.
+    pub fn start_session(&self, profile_id: &str) {
+        let mut active = self.active.lock().unwrap();
+        if let Some(previous) = active.remove(profile_id) {
+            let _ = compress_segment(&previous.path);
+        }
+        for leftover in list_segments(&self.profile_dir(profile_id))
+            .iter()
+            .filter(|p| p.extension().is_some_and(|e| e == ACTIVE_EXT))
+        {
+            let _ = compress_segment(leftover);
+        }
+
+        let session = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        match self.open_segment(profile_id, session, 0) {
+            Ok(segment) => {
+                active.insert(profile_id.to_string(), segment);
+            }
+            Err(e) => println!("Failed to open log archive for {}: {}", profile_id, e),
+        }
+    }
+
+    /// Closes and compresses the profile's current session.
+    pub fn end_session(&self, profile_id: &str) {
+        let segment = self.active.lock().unwrap().remove(profile_id);
+        if let Some(segment) = segment {
+            drop(segment.file);
+            if let Err(e) = compress_segment(&segment.path) {
+                println!("Failed to compress log segment {:?}: {}", segment.path, e);
+            }
+        }
+    }
+
+    /// Archives one line. Lines arriving with no session open start one.
+    pub fn append(&self, profile_id: &str, source: LogSource, line: &str) {
+        if !self.active.lock().unwrap().contains_key(profile_id) {
+            self.start_session(profile_id);
+        }
+        let mut active = self.active.lock().unwrap();
+        let Some(segment) = active.get_mut(profile_id) else {
+            return;
+        };
+
+        let record = ArchivedLine {
+            ts: Local::now().to_rfc3339(),
+            session: segment.session.clone(),
+            source,
+            level: LogLevel::infer(line),
+            line: line.to_string(),
+        };
+        let Ok(mut json) = serde_json::to_string(&record) else {
+            return;
+        };
+        json.push('\n');
+        if let Err(e) = segment.file.write_all(json.as_bytes()) {
+            println!("Failed to archive log line for {}: {}", profile_id, e);
+            return;
+        }
+        segment.bytes += json.len() as u64;
+
+        if segment.bytes >= SEGMENT_MAX_BYTES {
+            let (session, seq, path) = (segment.session.clone(), segment.seq, segment.path.clone());
+            active.remove(profile_id);
+            if let Err(e) = compress_segment(&path) {
+                println!("Failed to compress log segment {:?}: {}", path, e);
+            }
+            match self.open_segment(profile_id, session, seq + 1) {
+                Ok(next) => {
+                    active.insert(profile_id.to_string(), next);
+                }
+                Err(e) => println!("Failed to rotate log archive for {}: {}", profile_id, e),
+            }
+            drop(active);
+            self.prune(profile_id);
+        }
+    }
+
+    /// Deletes the oldest closed segments until the profile fits in `PROFILE_MAX_BYTES`.
+    fn prune(&self, profile_id: &str) {
+        let segments = list_segments(&self.profile_dir(profile_id));
+        let sizes: Vec<u64> = segments
+            .iter()
+            .map(|p| fs::metadata(p).map_or(0, |m| m.len()))
+            .collect();
+        let mut total: u64 = sizes.iter().sum();
+        for (path, size) in segments.iter().zip(sizes) {
+            if total <= PROFILE_MAX_BYTES {
+                break;
+            }
+            // Never delete the segment being written
+            if path.extension().is_some_and(|e| e == ACTIVE_EXT) {
+                continue;
+            }
+            if fs::remove_file(path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+
+    pub fn sessions(&self, profile_id: &str) -> Vec<LogSessionInfo> {
+        let active_session = self
+            .active
+            .lock()
+            .unwrap()
+            .get(profile_id)
+            .map(|s| s.session.clone());
+        let mut sessions: Vec<LogSessionInfo> = vec![];
+        for path in list_segments(&self.profile_dir(profile_id)) {
+            let Some(session) = segment_session(&path) else {
+                continue;
+            };
+            let bytes = fs::metadata(&path).map_or(0, |m| m.len());
+            match sessions.last_mut() {
+                Some(last) if last.session == session => {
+                    last.segments += 1;
+                    last.bytes += bytes;
+                }
+                _ => sessions.push(LogSessionInfo {
+                    active: active_session.as_deref() == Some(session.as_str()),
+                    session,
+                    segments: 1,
+                    bytes,
+                }),
+            }
+        }
+        sessions
+    }
+
+    /// Scans the archive oldest-first and returns one page of matching lines.
+    pub fn search(
+        &self,
+        profile_id: &str,
+        query: &LogSearchQuery,
+    ) -> Result<LogSearchPage, String> {
+        let from = parse_time(&query.from, "from")?;
+        let to = parse_time(&query.to, "to")?;
+        let regex = query
+            .regex
+            .as_deref()
+            .filter(|r| !r.is_empty())
+            .map(|r| {
+                RegexBuilder::new(r)
+                    .case_insensitive(query.case_insensitive)
+                    .build()
+                    .map_err(|e| format!("Invalid search pattern: {}", e))
+            })
+            .transpose()?;
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+
+        let mut lines = vec![];
+        let mut total = 0;
+        for path in list_segments(&self.profile_dir(profile_id)) {
+            if let Some(session) = &query.session {
+                if segment_session(&path).as_ref() != Some(session) {
+                    continue;
+                }
+            }
+            let file = File::open(&path).map_err(|e| e.to_string())?;
+            let reader: Box<dyn Read> = if path.extension().is_some_and(|e| e == "gz") {
+                Box::new(GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+
+            for raw in BufReader::new(reader).lines() {
+                // A gzip cut short by a crash still yields the lines before the damage
+                let Ok(raw) = raw else { break };
+                // The active segment may end in a partially written line
+                let Ok(record) = serde_json::from_str::<ArchivedLine>(&raw) else {
+                    continue;
+                };
+                if let Some(levels) = &query.levels {
+                    if !levels.contains(&record.level) {
+                        continue;
+                    }
+                }
+                if let Some(sources) = &query.sources {
+                    if !sources.contains(&record.source) {
+                        continue;
+                    }
+                }
+                if from.is_some() || to.is_some() {
+                    let Ok(ts) = DateTime::parse_from_rfc3339(&record.ts) else {
+                        continue;
+                    };
+                    if from.is_some_and(|from| ts < from) || to.is_some_and(|to| ts > to) {
+                        continue;
+                    }
+                }
+                if regex.as_ref().is_some_and(|r| !r.is_match(&record.line)) {
+                    continue;
+                }
+
+                if total >= query.offset && lines.len() < limit {
+                    lines.push(record);
+                }
+                total += 1;
+            }
+        }
+
+        Ok(LogSearchPage {
+            has_more: total > query.offset + lines.len(),
+            lines,
+            total,
+            offset: query.offset,
+        })
+    }
+}
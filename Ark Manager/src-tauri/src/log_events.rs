@@ -0,0 +1,466 @@
+// src-tauri/src/log_events.rs
+//
+// Structured events from `ShooterGame.log`. Every line runs through a list of matchers,
+// so one line can produce several events. Built-in matchers cover the lines we know
+// (startup, joins, chat, tribe log, kills, crashes, mod failures, saves, memory) and each
+// kind is emitted on its own `log-*` channel. Users can add their own regex rules; named
+// captures become event fields and a rule can also POST the event to a webhook.
+//
+// The events the UI listened for before (`server-running`, `player-joined`,
+// `player-left`, `log-stats-update`) are still emitted alongside the typed ones.
+
+use chrono::Local;
+use once_cell::sync::Lazy;
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+const RULES_FILE: &str = "log_rules.json";
+const CUSTOM_CHANNEL: &str = "log-custom";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// ASA prefixes most lines with `[2024.05.01-10.11.12:123][ 42]` or `2024.05.01_10.11.12: `.
+static SERVER_TIME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[?(\d{4}\.\d{2}\.\d{2}[-_]\d{2}\.\d{2}\.\d{2})").unwrap());
+
+static WEBHOOK_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum LogEventKind {
+    StartupComplete,
+    PlayerJoined,
+    PlayerLeft,
+    Chat,
+    TribeLog,
+    Kill,
+    Crash,
+    ModLoadFailed,
+    SaveCompleted,
+    Memory,
+    Custom,
+}
+
+impl LogEventKind {
+    pub fn channel(self) -> &'static str {
+        match self {
+            LogEventKind::StartupComplete => "log-startup-complete",
+            LogEventKind::PlayerJoined => "log-player-joined",
+            LogEventKind::PlayerLeft => "log-player-left",
+            LogEventKind::Chat => "log-chat",
+            LogEventKind::TribeLog => "log-tribe",
+            LogEventKind::Kill => "log-kill",
+            LogEventKind::Crash => "log-crash",
+            LogEventKind::ModLoadFailed => "log-mod-load-failed",
+            LogEventKind::SaveCompleted => "log-save-completed",
+            LogEventKind::Memory => "log-memory",
+            LogEventKind::Custom => CUSTOM_CHANNEL,
+        }
+    }
+}
+
+/// Built-in matchers: kind, pattern, and whether it fires only once per server session.
+const BUILTIN_MATCHERS: &[(LogEventKind, &str, bool)] = &[
+    (
+        LogEventKind::StartupComplete,
+        r"Server has completed startup and is now advertising for join\.",
+        true,
+    ),
+    (
+        LogEventKind::PlayerJoined,
+        r"\d{4}\.\d{2}\.\d{2}_\d{2}\.\d{2}\.\d{2}:\s(?P<name>.*?)\s+\[UniqueNetId:(?P<id>[a-fA-F0-9]+)[^\]]*\]\s+joined\s+this\s+ARK!",
+        false,
+    ),
+    (
+        LogEventKind::PlayerLeft,
+        r"\d{4}\.\d{2}\.\d{2}_\d{2}\.\d{2}\.\d{2}:\s(?P<name>.*?)\s+\[UniqueNetId:(?P<id>[a-fA-F0-9]+)[^\]]*\]\s+left\s+this\s+ARK!",
+        false,
+    ),
+    (
+        LogEventKind::Chat,
+        r"\d{4}\.\d{2}\.\d{2}_\d{2}\.\d{2}\.\d{2}:\s(?P<player>[^\[\]():]+?)\s\((?P<character>[^()]+)\):\s(?P<message>.*)$",
+        false,
+    ),
+    (
+        LogEventKind::TribeLog,
+        r"Tribe (?P<tribe>.+?), ID (?P<tribe_id>\d+): Day (?P<day>\d+), (?P<time>\d{1,2}:\d{2}:\d{2}): (?P<message>.*)$",
+        false,
+    ),
+    (
+        LogEventKind::Kill,
+        r"(?P<victim>[^>:]+?) - Lvl (?P<victim_level>\d+)(?: \([^)]*\))? was killed(?: by (?P<killer>.+?) - Lvl (?P<killer_level>\d+))?",
+        false,
+    ),
+    (
+        LogEventKind::Crash,
+        r"(?P<reason>Assertion failed:.*|Fatal error!.*|Unhandled Exception:.*|=== Critical error: ===.*|LowLevelFatalError.*)",
+        false,
+    ),
+    (
+        LogEventKind::ModLoadFailed,
+        r"(?i)(?:failed to (?:load|mount|install|download|find)\s+mod|mod\s+(?:load|download|install)(?:ing)?\s+failed|error (?:loading|mounting) mod)(?:\D{0,40}(?P<mod_id>\d{5,}))?",
+        false,
+    ),
+    (
+        LogEventKind::SaveCompleted,
+        r"(?i)world save complete(?:.*?(?P<seconds>\d+(?:\.\d+)?)\s*(?:seconds|secs?|s)\b)?",
+        false,
+    ),
+    (
+        LogEventKind::Memory,
+        r"\((?P<memory_gb>\d+\.?\d*)\s*GB\s+Mem\)",
+        false,
+    ),
+    (
+        LogEventKind::Memory,
+        r"LogMemory:.*?Current/Peak\s*(?P<memory_mb>[\d.]+)\s*MB",
+        false,
+    ),
+];
+
+struct BuiltinMatcher {
+    kind: LogEventKind,
+    regex: Regex,
+    once_per_session: bool,
+}
+
+static BUILTINS: Lazy<Vec<BuiltinMatcher>> = Lazy::new(|| {
+    BUILTIN_MATCHERS
+        .iter()
+        .map(|(kind, pattern, once_per_session)| BuiltinMatcher {
+            kind: *kind,
+            regex: Regex::new(pattern).unwrap(),
+            once_per_session: *once_per_session,
+        })
+        .collect()
+});
+
+/// A user-defined matcher. Named captures in `pattern` become event fields.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRule {
+    pub id: String,
+    pub name: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Only match this profile's log; all profiles when `None`.
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    /// Channel to emit on instead of `log-custom`.
+    #[serde(default)]
+    pub event_name: Option<String>,
+    /// POST every match as JSON to this URL.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEvent {
+    pub profile_id: String,
+    pub kind: LogEventKind,
+    /// The custom rule that matched, if any.
+    pub rule_id: Option<String>,
+    pub rule_name: Option<String>,
+    pub line: String,
+    pub fields: BTreeMap<String, String>,
+    /// Timestamp printed by the server, when the line has one.
+    pub server_time: Option<String>,
+    pub received_at: String,
+    #[serde(skip)]
+    channel: String,
+    #[serde(skip)]
+    webhook_url: Option<String>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PlayerEventPayload {
+    profile_id: String,
+    player_name: String,
+    player_id: String,
+}
+
+struct CompiledRule {
+    rule: LogRule,
+    regex: Regex,
+}
+
+fn compile(rule: &LogRule) -> Result<Regex, String> {
+    RegexBuilder::new(&rule.pattern)
+        .case_insensitive(rule.case_insensitive)
+        .build()
+        .map_err(|e| format!("Invalid pattern for rule '{}': {}", rule.name, e))
+}
+
+fn validate_rule(rule: &LogRule) -> Result<Regex, String> {
+    if rule.id.trim().is_empty() {
+        return Err("Rule id is required".to_string());
+    }
+    if let Some(event_name) = &rule.event_name {
+        let valid = !event_name.is_empty()
+            && event_name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == ':');
+        if !valid {
+            return Err(format!(
+                "Event name '{}' may only contain lowercase letters, digits, '-' and ':'",
+                event_name
+            ));
+        }
+    }
+    if let Some(url) = &rule.webhook_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!(
+                "Webhook URL '{}' must start with http:// or https://",
+                url
+            ));
+        }
+    }
+    compile(rule)
+}
+
+fn captures(regex: &Regex, line: &str) -> Option<BTreeMap<String, String>> {
+    let caps = regex.captures(line)?;
+    Some(
+        regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| {
+                caps.name(name)
+                    .map(|m| (name.to_string(), m.as_str().trim().to_string()))
+            })
+            .collect(),
+    )
+}
+
+/// Custom rules shared by every log tail; edits apply to running tails immediately.
+#[derive(Default)]
+pub struct LogRules {
+    rules: RwLock<Vec<CompiledRule>>,
+}
+
+fn rules_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(RULES_FILE))
+}
+
+impl LogRules {
+    /// Loads persisted rules; rules whose pattern no longer compiles are skipped.
+    pub fn load(&self, app: &AppHandle) {
+        let persisted: Vec<LogRule> = rules_path(app)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let compiled = persisted
+            .into_iter()
+            .filter_map(|rule| match compile(&rule) {
+                Ok(regex) => Some(CompiledRule { rule, regex }),
+                Err(e) => {
+                    println!("Skipping log rule: {}", e);
+                    None
+                }
+            })
+            .collect();
+        *self.rules.write().unwrap() = compiled;
+    }
+
+    pub fn list(&self) -> Vec<LogRule> {
+        self.rules
+            .read()
+            .unwrap()
+            .iter()
+            .map(|compiled| compiled.rule.clone())
+            .collect()
+    }
+
+    /// Adds `rule`, or replaces the rule with the same id.
+    pub fn set_rule(&self, app: &AppHandle, rule: LogRule) -> Result<(), String> {
+        let regex = validate_rule(&rule)?;
+        {
+            let mut rules = self.rules.write().unwrap();
+            let compiled = CompiledRule { rule, regex };
+            match rules.iter_mut().find(|c| c.rule.id == compiled.rule.id) {
+                Some(existing) => *existing = compiled,
+                None => rules.push(compiled),
+            }
+        }
+        self.save(app)
+    }
+
+    pub fn remove_rule(&self, app: &AppHandle, id: &str) -> Result<(), String> {
+        self.rules.write().unwrap().retain(|c| c.rule.id != id);
+        self.save(app)
+    }
+
+    fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = rules_path(app).ok_or("Could not resolve app data dir")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(&self.list()).map_err(|e| e.to_string())?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+}
+
+/// Tries `rule` against `line` without saving it. Returns the captured fields on a match.
+pub fn test_rule(rule: &LogRule, line: &str) -> Result<Option<BTreeMap<String, String>>, String> {
+    Ok(captures(&compile(rule)?, line))
+}
+
+/// Per-tail matcher state.
+pub struct LogPipeline {
+    profile_id: String,
+    rules: Arc<LogRules>,
+    fired: HashSet<LogEventKind>,
+}
+
+impl LogPipeline {
+    pub fn new(profile_id: String, rules: Arc<LogRules>) -> Self {
+        LogPipeline {
+            profile_id,
+            rules,
+            fired: HashSet::new(),
+        }
+    }
+
+    /// Forgets once-per-session events, e.g. after the log was truncated by a restart.
+    pub fn reset(&mut self) {
+        self.fired.clear();
+    }
+
+    pub fn process(&mut self, line: &str) -> Vec<LogEvent> {
+        let server_time = SERVER_TIME_REGEX
+            .captures(line)
+            .map(|caps| caps[1].to_string());
+        let received_at = Local::now().to_rfc3339();
+        let event = |kind: LogEventKind,
+                     rule: Option<&LogRule>,
+                     fields: BTreeMap<String, String>| LogEvent {
+            profile_id: self.profile_id.clone(),
+            kind,
+            rule_id: rule.map(|r| r.id.clone()),
+            rule_name: rule.map(|r| r.name.clone()),
+            line: line.to_string(),
+            fields,
+            server_time: server_time.clone(),
+            received_at: received_at.clone(),
+            channel: rule
+                .and_then(|r| r.event_name.clone())
+                .unwrap_or_else(|| kind.channel().to_string()),
+            webhook_url: rule.and_then(|r| r.webhook_url.clone()),
+        };
+
+        let mut events = vec![];
+        for matcher in BUILTINS.iter() {
+            if matcher.once_per_session && self.fired.contains(&matcher.kind) {
+                continue;
+            }
+            if let Some(fields) = captures(&matcher.regex, line) {
+                events.push(event(matcher.kind, None, fields));
+            }
+        }
+
+        for compiled in self.rules.rules.read().unwrap().iter() {
+            let rule = &compiled.rule;
+            if !rule.enabled
+                || rule
+                    .profile_id
+                    .as_ref()
+                    .is_some_and(|id| *id != self.profile_id)
+            {
+                continue;
+            }
+            if let Some(fields) = captures(&compiled.regex, line) {
+                events.push(event(LogEventKind::Custom, Some(rule), fields));
+            }
+        }
+
+        self.fired.extend(events.iter().map(|e| e.kind));
+        events
+    }
+}
+
+/// Emits `event` on its channel (plus the legacy event, if any) and fires its webhook.
+pub fn dispatch(window: &Window, event: &LogEvent) {
+    let _ = window.emit(&event.channel, event);
+
+    match event.kind {
+        LogEventKind::StartupComplete => {
+            let _ = window.emit(
+                "server-running",
+                serde_json::json!({ "profile_id": event.profile_id }),
+            );
+        }
+        LogEventKind::PlayerJoined | LogEventKind::PlayerLeft => {
+            let payload = PlayerEventPayload {
+                profile_id: event.profile_id.clone(),
+                player_name: event.fields.get("name").cloned().unwrap_or_default(),
+                player_id: event.fields.get("id").cloned().unwrap_or_default(),
+            };
+            let name = if event.kind == LogEventKind::PlayerJoined {
+                "player-joined"
+            } else {
+                "player-left"
+            };
+            let _ = window.emit(name, &payload);
+        }
+        LogEventKind::Memory => {
+            let memory_mb = match (event.fields.get("memory_gb"), event.fields.get("memory_mb")) {
+                (Some(gb), _) => gb.parse::<f64>().ok().map(|gb| gb * 1024.0),
+                (_, Some(mb)) => mb.parse::<f64>().ok(),
+                _ => None,
+            };
+            if let Some(memory_mb) = memory_mb {
+                let _ = window.emit(
+                    "log-stats-update",
+                    serde_json::json!({ "profile_id": event.profile_id, "memoryMb": memory_mb }),
+                );
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(url) = event.webhook_url.clone() {
+        let window = window.clone();
+        let event = event.clone();
+        tokio::spawn(async move {
+            let result = WEBHOOK_CLIENT
+                .post(&url)
+                .json(&event)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                let _ = window.emit(
+                    "log-webhook-failed",
+                    serde_json::json!({
+                        "profile_id": event.profile_id,
+                        "rule_id": event.rule_id,
+                        "error": e.to_string(),
+                    }),
+                );
+            }
+        });
+    }
+}
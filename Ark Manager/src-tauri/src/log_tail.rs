@@ -0,0 +1,371 @@
+// src-tauri/src/log_tail.rs
+//
+// Follows `ShooterGame.log` across rotations. ARK renames the log to
+// `ShooterGame_backup_*.log` and starts a new file, so the tailer keeps the file it is
+// reading open and compares its identity (inode / file index) with whatever is at the
+// path now: when they differ, the old file is drained to the end before switching.
+// A file that shrinks in place is treated as truncated and re-read from the start.
+//
+// `LogTailer` is synchronous and does no waiting; `TailWaker` wakes the caller on
+// filesystem notifications, with a polling fallback for filesystems (network shares,
+// Wine drives) where notifications do not arrive.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use same_file::Handle;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+const READ_CHUNK: usize = 64 * 1024;
+/// Safety-net poll while notifications work.
+const NOTIFY_FALLBACK_INTERVAL: Duration = Duration::from_secs(2);
+/// Poll interval when no watcher could be created.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+#[derive(Debug, PartialEq)]
+pub enum TailEvent<'a> {
+    /// One complete line, without its line ending. Invalid UTF-8 is replaced.
+    Line(Cow<'a, str>),
+    /// The path now points at a new file; the old one was read to the end first.
+    Rotated,
+    /// The file shrank in place; reading restarts at the top.
+    Truncated,
+}
+
+struct OpenLog {
+    file: File,
+    handle: Handle,
+    position: u64,
+}
+
+pub struct LogTailer {
+    path: PathBuf,
+    current: Option<OpenLog>,
+    /// Offset to start the first file at; later files are always read from the top.
+    start_offset: u64,
+    /// Bytes of a line whose newline has not been written yet.
+    partial: Vec<u8>,
+    buf: Vec<u8>,
+}
+
+impl LogTailer {
+    pub fn new(path: PathBuf, start_offset: u64) -> Self {
+        LogTailer {
+            path,
+            current: None,
+            start_offset,
+            partial: Vec::new(),
+            buf: vec![0; READ_CHUNK],
+        }
+    }
+
+    fn open(&self, position: u64) -> io::Result<OpenLog> {
+        let file = File::open(&self.path)?;
+        let handle = Handle::from_file(file.try_clone()?)?;
+        Ok(OpenLog {
+            file,
+            handle,
+            position,
+        })
+    }
+
+    /// Reads everything written since the last call and reports it through `on_event`.
+    /// A missing file is not an error; the tailer waits for it to appear.
+    pub fn poll(&mut self, mut on_event: impl FnMut(TailEvent)) -> io::Result<()> {
+        if self.current.is_none() {
+            match self.open(self.start_offset) {
+                Ok(log) => {
+                    // Never start past the end, e.g. when the file was replaced meanwhile
+                    let len = log.file.metadata()?.len();
+                    let mut log = log;
+                    if log.position > len {
+                        log.position = 0;
+                    }
+                    self.current = Some(log);
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let len = self.current.as_ref().unwrap().file.metadata()?.len();
+        if len < self.current.as_ref().unwrap().position {
+            self.current.as_mut().unwrap().position = 0;
+            self.partial.clear();
+            on_event(TailEvent::Truncated);
+        }
+        self.drain(&mut on_event)?;
+
+        // Has the path been pointed at a different file?
+        let rotated = match Handle::from_path(&self.path) {
+            Ok(handle) => handle != self.current.as_ref().unwrap().handle,
+            // Renamed away and the new file is not there yet
+            Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+            Err(e) => return Err(e),
+        };
+        if rotated {
+            // The old file is finished; a line without a newline is still a line
+            if !self.partial.is_empty() {
+                let line = std::mem::take(&mut self.partial);
+                emit_line(&line, &mut on_event);
+            }
+            self.current = Some(self.open(0)?);
+            on_event(TailEvent::Rotated);
+            self.drain(&mut on_event)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the current file from its position to the end.
+    fn drain(&mut self, on_event: &mut impl FnMut(TailEvent)) -> io::Result<()> {
+        let log = self.current.as_mut().unwrap();
+        log.file.seek(SeekFrom::Start(log.position))?;
+        loop {
+            let read = log.file.read(&mut self.buf)?;
+            if read == 0 {
+                return Ok(());
+            }
+            let mut chunk = &self.buf[..read];
+            if log.position == 0 && chunk.starts_with(UTF8_BOM) {
+                chunk = &chunk[UTF8_BOM.len()..];
+            }
+            log.position += read as u64;
+
+            let mut rest = chunk;
+            while let Some(newline) = rest.iter().position(|&b| b == b'\n') {
+                let (line, after) = rest.split_at(newline);
+                if self.partial.is_empty() {
+                    emit_line(line, on_event);
+                } else {
+                    self.partial.extend_from_slice(line);
+                    emit_line(&self.partial, on_event);
+                    self.partial.clear();
+                }
+                rest = &after[1..];
+            }
+            self.partial.extend_from_slice(rest);
+        }
+    }
+}
+
+fn emit_line(line: &[u8], on_event: &mut impl FnMut(TailEvent)) {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    on_event(TailEvent::Line(String::from_utf8_lossy(line)));
+}
+
+/// Wakes a tail loop when the log's directory changes, or on a timer as a fallback.
+pub struct TailWaker {
+    // Dropping the watcher stops the notifications
+    _watcher: Option<RecommendedWatcher>,
+    events: mpsc::UnboundedReceiver<()>,
+    interval: Duration,
+}
+
+impl TailWaker {
+    pub fn new(path: &Path) -> Self {
+        let (tx, events) = mpsc::unbounded_channel();
+        let watcher = path.parent().and_then(|dir| {
+            let mut watcher = notify::recommended_watcher(move |_| {
+                let _ = tx.send(());
+            })
+            .ok()?;
+            watcher.watch(dir, RecursiveMode::NonRecursive).ok()?;
+            Some(watcher)
+        });
+        if watcher.is_none() {
+            println!(
+                "File notifications unavailable for {:?}; polling every {:?}",
+                path, POLL_INTERVAL
+            );
+        }
+        TailWaker {
+            interval: if watcher.is_some() {
+                NOTIFY_FALLBACK_INTERVAL
+            } else {
+                POLL_INTERVAL
+            },
+            _watcher: watcher,
+            events,
+        }
+    }
+
+    /// Waits for the next change. Returns false once `cancellation_token` fires.
+    pub async fn wait(&mut self, cancellation_token: &CancellationToken) -> bool {
+        tokio::select! {
+            _ = cancellation_token.cancelled() => return false,
+            _ = self.events.recv() => {}
+            _ = tokio::time::sleep(self.interval) => {}
+        }
+        // Coalesce a burst of notifications into one read
+        while self.events.try_recv().is_ok() {}
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    struct TempLog {
+        dir: PathBuf,
+        path: PathBuf,
+    }
+
+    impl TempLog {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "ark-manager-log-tail-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let path = dir.join("ShooterGame.log");
+            TempLog { dir, path }
+        }
+
+        fn append(&self, bytes: &[u8]) {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .unwrap();
+            file.write_all(bytes).unwrap();
+        }
+    }
+
+    impl Drop for TempLog {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn collect(tailer: &mut LogTailer) -> Vec<String> {
+        let mut out = vec![];
+        tailer
+            .poll(|event| match event {
+                TailEvent::Line(line) => out.push(line.into_owned()),
+                TailEvent::Rotated => out.push("<rotated>".to_string()),
+                TailEvent::Truncated => out.push("<truncated>".to_string()),
+            })
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn waits_for_missing_file() {
+        let log = TempLog::new("missing");
+        let mut tailer = LogTailer::new(log.path.clone(), 0);
+        assert!(collect(&mut tailer).is_empty());
+        log.append(b"first\n");
+        assert_eq!(collect(&mut tailer), ["first"]);
+    }
+
+    #[test]
+    fn starts_at_offset_and_joins_partial_lines() {
+        let log = TempLog::new("partial");
+        log.append(b"old line\n");
+        let mut tailer = LogTailer::new(log.path.clone(), 9);
+        log.append(b"new li");
+        assert!(collect(&mut tailer).is_empty());
+        log.append(b"ne\r\nsecond\n");
+        assert_eq!(collect(&mut tailer), ["new line", "second"]);
+    }
+
+    #[test]
+    fn strips_bom_and_replaces_invalid_utf8() {
+        let log = TempLog::new("utf8");
+        log.append(b"\xEF\xBB\xBFstart\nbad \xFF byte\n");
+        let mut tailer = LogTailer::new(log.path.clone(), 0);
+        assert_eq!(collect(&mut tailer), ["start", "bad \u{FFFD} byte"]);
+    }
+
+    #[test]
+    fn truncation_restarts_from_the_top() {
+        let log = TempLog::new("truncate");
+        log.append(b"one\ntwo\n");
+        let mut tailer = LogTailer::new(log.path.clone(), 0);
+        assert_eq!(collect(&mut tailer), ["one", "two"]);
+
+        // Truncate in place, keeping the same file
+        OpenOptions::new()
+            .write(true)
+            .open(&log.path)
+            .unwrap()
+            .set_len(0)
+            .unwrap();
+        log.append(b"x\n");
+        assert_eq!(collect(&mut tailer), ["<truncated>", "x"]);
+    }
+
+    #[test]
+    fn rotation_drains_old_file_before_switching() {
+        let log = TempLog::new("rotate");
+        log.append(b"a\n");
+        let mut tailer = LogTailer::new(log.path.clone(), 0);
+        assert_eq!(collect(&mut tailer), ["a"]);
+
+        // The server writes a little more, then ARK rotates the log
+        log.append(b"b\nunterminated");
+        fs::rename(&log.path, log.dir.join("ShooterGame_backup_2024.log")).unwrap();
+        // Between the rename and the new file nothing is lost or reported
+        assert_eq!(collect(&mut tailer), ["b"]);
+
+        // The new file is larger than the old one, so a size check alone would miss it
+        log.append(b"new session line 1\nnew session line 2\n");
+        assert_eq!(
+            collect(&mut tailer),
+            [
+                "unterminated",
+                "<rotated>",
+                "new session line 1",
+                "new session line 2"
+            ]
+        );
+        log.append(b"c\n");
+        assert_eq!(collect(&mut tailer), ["c"]);
+    }
+
+    #[test]
+    fn long_lines_span_read_chunks() {
+        let log = TempLog::new("long");
+        let long = "x".repeat(READ_CHUNK * 2 + 10);
+        log.append(format!("{}\nend\n", long).as_bytes());
+        let mut tailer = LogTailer::new(log.path.clone(), 0);
+        let lines = collect(&mut tailer);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), long.len());
+        assert_eq!(lines[1], "end");
+    }
+
+    #[tokio::test]
+    async fn waker_wakes_on_write() {
+        let log = TempLog::new("waker");
+        log.append(b"");
+        let mut waker = TailWaker::new(&log.path);
+        let token = CancellationToken::new();
+        let path = log.path.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            OpenOptions::new()
+                .append(true)
+                .open(path)
+                .unwrap()
+                .write_all(b"x\n")
+                .unwrap();
+        });
+        let started = std::time::Instant::now();
+        assert!(waker.wait(&token).await);
+        assert!(started.elapsed() < NOTIFY_FALLBACK_INTERVAL);
+
+        token.cancel();
+        assert!(!waker.wait(&token).await);
+    }
+}
@@ -1,28 +1,74 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod a2s;
+mod access_lists;
+mod adopt;
+mod backup_scheduler;
+mod backup_store;
+mod chat;
+mod config_schema;
+mod cron;
+mod ini;
+mod launch;
+mod layout;
+mod log_archive;
+mod log_events;
+mod log_tail;
+mod platform;
+mod players;
+mod ports;
+mod rcon;
+mod rcon_audit;
+mod steamcmd;
+mod steamcmd_progress;
+mod task_scheduler;
+mod ue_text;
+mod watchdog;
+
+use a2s::ServerQueryStatus;
+use adopt::ProfileCandidate;
+use access_lists::{
+    AccessAction, AccessAudit, AccessAuditEntry, AccessAuditQuery, AccessChange, AccessList,
+    AccessListChange, AccessListContents, LiveApplication,
+};
+use chat::{ChatHistoryQuery, ChatHub, ChatMessage};
+use backup_scheduler::{BackupPolicy, BackupScheduler, BackupTrigger};
+use backup_store::BackupStore;
+use config_schema::{SettingSpec, ValidationReport};
+use ini::{IniFile, IniFileContents, IniPatch};
+use launch::{LaunchPreview, LaunchSpec};
+use layout::InstallLayout;
+use log_archive::{LogArchive, LogSearchPage, LogSearchQuery, LogSessionInfo, LogSource};
+use log_events::{LogEventKind, LogPipeline, LogRule, LogRules};
+use log_tail::{LogTailer, TailEvent, TailWaker};
 use chrono::Local;
 use local_ip_address;
-use once_cell::sync::Lazy;
+use platform::ServerRuntime;
+use players::{PlayerRecord, PlayerRegistry, PlayerScope, PlayerSession, PlayerSummary};
+use ports::{PortCheckReport, ProfilePorts, RunningPorts, ServerPorts};
 use regex::Regex;
+use rcon::{RconHealth, RconSession};
+use rcon_audit::{CommandPolicy, CommandSource, RconAudit, RconAuditEntry, RconAuditQuery};
 use rercon::{Connection, Settings};
+use steamcmd::{JobInfo, JobKind, SteamCmdJobs};
+use task_scheduler::{ScheduledTask, ScheduledTaskStatus, TaskAction, TaskRun, TaskScheduler};
 use std::collections::HashMap;
 use std::fs;
-use std::io::{Cursor, Read, Write};
+use std::io::Cursor;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use sysinfo::{Pid, System};
 use tauri::menu::{MenuBuilder, MenuItemBuilder};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconEvent};
-use tauri::{Emitter, Manager, State, Window};
-use tokio::fs::File;
-use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader, SeekFrom};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
 use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
-use walkdir::WalkDir;
-use zip::write::{FileOptions, ZipWriter};
+use ue_text::{StructuredSection, UeValue};
+use watchdog::{classify_exit, CrashTracker, RestartPolicy, StopReason, WatchedProcess};
 use zip::ZipArchive;
 
 // --- STATE MANAGEMENT ---
@@ -30,11 +76,17 @@ use zip::ZipArchive;
 #[derive(Clone)]
 struct ServerProcessInfo {
     pid: u32,
-    rcon_ip: String,
-    rcon_port: u16,
-    rcon_password: Option<String>,
-    rcon_enabled: bool,
+    // Under Proton `pid` starts out as the launcher script's and is swapped for the
+    // server's once it shows up; the launcher's is kept here for forced stops
+    launcher_pid: Option<u32>,
+    // Shared RCON session, `None` when RCON is disabled for the profile
+    rcon: Option<Arc<RconSession>>,
+    // Set by `stop_server` so the watchdog can tell a requested stop from a crash
+    stop_requested: Arc<AtomicBool>,
     cancellation_token: CancellationToken,
+    launch: LaunchParams,
+    // True when the process was found running after a manager restart
+    adopted: bool,
 }
 
 struct ServerProcesses(Arc<Mutex<HashMap<String, ServerProcessInfo>>>);
@@ -44,21 +96,25 @@ struct BackupInfo {
     filename: String,
     created_at: String,
     size: u64,
+    // "snapshot" for the deduplicated store, "zip" for older full-zip backups
+    kind: String,
+    label: Option<String>,
+    file_count: usize,
 }
 
 #[derive(serde::Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct ServerStats {
-    uptime_seconds: u64,
-    memory_bytes: u64,
+struct RunningServerInfo {
+    profile_id: String,
+    pid: u32,
+    adopted: bool,
 }
 
-#[derive(serde::Serialize, Clone, Debug)]
+#[derive(serde::Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct PlayerEventPayload {
-    profile_id: String,
-    player_name: String,
-    player_id: String,
+struct ServerStats {
+    uptime_seconds: u64,
+    memory_bytes: u64,
 }
 
 #[derive(serde::Serialize, Clone, Debug)]
@@ -121,46 +177,6 @@ fn unzip_file(zip_path: &Path, dest_dir: &Path) -> Result<(), Box<dyn std::error
     Ok(())
 }
 
-async fn ensure_steamcmd(
-    window: &Window,
-    log_event: &str,
-    steamcmd_dir: &Path,
-    steamcmd_exe: &Path,
-) -> Result<(), String> {
-    if !steamcmd_exe.exists() {
-        log_to_frontend(
-            window,
-            log_event,
-            "  > steamcmd.exe not found. Setting up...",
-        );
-        if let Err(e) = std::fs::create_dir_all(steamcmd_dir) {
-            return Err(format!(
-                "❌ ERROR: Failed to create steamcmd directory: {}",
-                e
-            ));
-        }
-
-        let steamcmd_zip_path = steamcmd_dir.join("steamcmd.zip");
-        let url = "https://steamcdn-a.akamaihd.net/client/installer/steamcmd.zip";
-
-        if let Err(e) = download_file(url, &steamcmd_zip_path).await {
-            return Err(format!("❌ ERROR: Failed to download steamcmd.zip: {}", e));
-        }
-        log_to_frontend(window, log_event, "  > Download complete. Unzipping...");
-
-        if let Err(e) = unzip_file(&steamcmd_zip_path, steamcmd_dir) {
-            return Err(format!("❌ ERROR: Failed to unzip steamcmd.zip: {}", e));
-        }
-        log_to_frontend(
-            window,
-            log_event,
-            "  > Unzip complete. Deleting zip file...",
-        );
-        let _ = std::fs::remove_file(&steamcmd_zip_path);
-    }
-    Ok(())
-}
-
 // --- TAURI COMMANDS ---
 
 #[tauri::command]
@@ -178,93 +194,60 @@ fn get_local_ips() -> Result<Vec<String>, String> {
     }
 }
 
-static STARTUP_MEMORY_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\((\d+\.?\d*)\s*GB\s+Mem\)").unwrap());
-static MEMORY_LOGMEMORY_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"LogMemory:.*?Current/Peak\s*([\d.]+)\s*MB").unwrap());
-static PLAYER_EVENT_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\d{4}\.\d{2}\.\d{2}_\d{2}\.\d{2}\.\d{2}:\s(?P<name>.*?)\s+\[UniqueNetId:(?P<id>[a-fA-F0-9]+)[^\]]*\]\s+(?P<action>joined|left)\s+this\s+ARK!").unwrap()
-});
-
-#[tauri::command]
-async fn start_ark_server(
+/// Everything needed to (re)launch a server, kept so the watchdog can restart it
+/// with the original arguments.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+struct LaunchParams {
     profile_id: String,
     install_path: String,
     server_path: String,
     args: Vec<String>,
     rcon_ip: String,
     rcon_port: u16,
+    // Never written to the running-server registry; re-adopted servers get it back from
+    // their saved profile (see `adopt_profile_servers`)
+    #[serde(skip)]
     rcon_password: Option<String>,
-    b_enable_rcon: bool,
-    processes: State<'_, ServerProcesses>,
-    window: Window,
-) -> Result<u32, String> {
-    {
-        let procs = processes.0.lock().await;
-        if procs.contains_key(&profile_id) {
-            return Err("Server is already running for this profile".to_string());
-        }
-    }
-
-    // SPAWN DIRECTLY (No "cmd /C") to get the actual game process ID
-    let mut child = Command::new(&server_path)
-        .args(&args)
-        .stdout(std::process::Stdio::null())
-        .stderr(std::process::Stdio::null())
-        .spawn()
-        .map_err(|e| format!("Failed to start server: {}", e))?;
-
-    let pid = child.id().ok_or("Failed to get process ID")?;
-    let cancellation_token = CancellationToken::new();
+    rcon_enabled: bool,
+    #[serde(default)]
+    runtime: ServerRuntime,
+    /// `args` were rendered from a `LaunchSpec` and are raw command-line tokens.
+    #[serde(default)]
+    raw_args: bool,
+    // Crash restart settings, kept so relaunches and re-adopted servers use them too
+    #[serde(default)]
+    auto_restart: Option<bool>,
+    #[serde(default)]
+    max_crashes: Option<u32>,
+    #[serde(default)]
+    crash_window_seconds: Option<u64>,
+}
 
-    {
-        let mut procs = processes.0.lock().await;
-        let process_info = ServerProcessInfo {
-            pid,
-            rcon_ip,
-            rcon_port,
-            rcon_password,
-            rcon_enabled: b_enable_rcon,
-            cancellation_token: cancellation_token.clone(),
-        };
-        procs.insert(profile_id.clone(), process_info);
+impl LaunchParams {
+    fn crash_tracker(&self) -> CrashTracker {
+        CrashTracker::new(RestartPolicy::new(
+            self.auto_restart,
+            self.max_crashes,
+            self.crash_window_seconds,
+        ))
     }
+}
 
-    let win_clone = window.clone();
-    let profile_id_clone = profile_id.clone();
-    let log_file_path_new = PathBuf::from(&install_path)
-        .join("steamcmd")
-        .join("steamapps")
-        .join("common")
-        .join("ARK Survival Ascended Dedicated Server")
-        .join("ShooterGame")
-        .join("Saved")
-        .join("Logs")
-        .join("ShooterGame.log");
-
-    let log_file_path_old = PathBuf::from(&install_path)
-        .join("ShooterGame")
-        .join("Saved")
-        .join("Logs")
-        .join("ShooterGame.log");
-
-    // Determine which structure is in use by checking the server_path that was passed in
-    let log_file_path = if server_path.contains("steamapps\\common\\ARK Survival Ascended Dedicated Server") {
-        println!("Detected NEW server structure, using new log path");
-        log_file_path_new
-    } else {
-        println!("Detected OLD server structure, using old log path");
-        log_file_path_old
-    };
-
+/// `launched_at` is set when we just started the server, `None` when re-adopting one.
+fn spawn_log_tail(
+    window: Window,
+    profile_id: String,
+    log_file_path: PathBuf,
+    launched_at: Option<SystemTime>,
+    cancellation_token: CancellationToken,
+) {
     println!("Log file path: {:?}", log_file_path);
 
-    let cancellation_token_clone = cancellation_token.clone();
     tokio::spawn(async move {
         let mut wait_attempts = 0;
         const MAX_WAIT_ATTEMPTS: u32 = 60;
         while !log_file_path.exists() && wait_attempts < MAX_WAIT_ATTEMPTS {
-            if cancellation_token_clone.is_cancelled() {
+            if cancellation_token.is_cancelled() {
                 return;
             }
             tokio::time::sleep(Duration::from_secs(1)).await;
@@ -272,742 +255,1466 @@ async fn start_ark_server(
         }
 
         if !log_file_path.exists() {
-            let _ = win_clone.emit("manager-log-line", serde_json::json!({
-                "profile_id": profile_id_clone,
-                "line": "[Manager] ⚠️ Warning: ShooterGame.log not found after 60 seconds. Log streaming disabled.",
-            }));
+            emit_manager_line(
+                &window,
+                &profile_id,
+                LogSource::Manager,
+                "[Manager] ⚠️ Warning: ShooterGame.log not found after 60 seconds. Log streaming disabled.",
+            );
             return;
         }
 
-        let initial_position = tokio::fs::metadata(&log_file_path)
-            .await
-            .map_or(0, |m| m.len());
-        let _ = win_clone.emit("manager-log-line", serde_json::json!({
-            "profile_id": profile_id_clone,
-            "line": format!("[Manager] ✅ ShooterGame.log found. Starting log stream from offset: {} bytes...", initial_position),
-        }));
-
-        let mut server_is_running = false;
-        let mut last_position = initial_position;
+        // A log created by the server we just launched is read from the top, so the
+        // start-up lines reach the console and the archive too
+        let initial_position = match tokio::fs::metadata(&log_file_path).await {
+            Ok(metadata) => {
+                let created = metadata.created().or_else(|_| metadata.modified());
+                let fresh = launched_at
+                    .is_some_and(|launched| created.is_ok_and(|created| created >= launched));
+                if fresh {
+                    0
+                } else {
+                    metadata.len()
+                }
+            }
+            Err(_) => 0,
+        };
+        emit_manager_line(
+            &window,
+            &profile_id,
+            LogSource::Manager,
+            &format!(
+                "[Manager] ✅ ShooterGame.log found. Starting log stream from offset: {} bytes...",
+                initial_position
+            ),
+        );
+
+        let rules = window.state::<Arc<LogRules>>().inner().clone();
+        let chat_hub = window.try_state::<Arc<ChatHub>>().map(|hub| hub.inner().clone());
+        let players = window.state::<Arc<PlayerRegistry>>().inner().clone();
+        let mut pipeline = LogPipeline::new(profile_id.clone(), rules);
+        let mut tailer = LogTailer::new(log_file_path.clone(), initial_position);
+        let mut waker = TailWaker::new(&log_file_path);
         let mut consecutive_errors = 0;
         const MAX_CONSECUTIVE_ERRORS: u32 = 10;
 
         loop {
-            if cancellation_token_clone.is_cancelled() {
-                break;
-            }
-
-            match File::open(&log_file_path).await {
-                Ok(file) => {
-                    consecutive_errors = 0;
-                    let mut reader = BufReader::new(file);
-
-                    if let Ok(metadata) = tokio::fs::metadata(&log_file_path).await {
-                        if metadata.len() < last_position {
-                            last_position = 0;
-                            server_is_running = false;
+            let result = tailer.poll(|event| match event {
+                TailEvent::Line(line) => {
+                    let _ = window.emit(
+                        "server-log-line",
+                        serde_json::json!({
+                            "profile_id": profile_id,
+                            "line": line,
+                        }),
+                    );
+                    archive_line(&window, &profile_id, LogSource::Server, &line);
+
+                    for event in pipeline.process(&line) {
+                        let player_id = event.fields.get("id").map(String::as_str);
+                        match (event.kind, player_id) {
+                            (LogEventKind::PlayerJoined, Some(id)) => players.player_joined(
+                                &profile_id,
+                                id,
+                                event.fields.get("name").map_or("", String::as_str),
+                            ),
+                            (LogEventKind::PlayerLeft, Some(id)) => {
+                                players.player_left(&profile_id, id)
+                            }
+                            _ => {}
                         }
+                        log_events::dispatch(&window, &event);
                     }
-
-                    if reader.seek(SeekFrom::Start(last_position)).await.is_err() {
-                        last_position = 0;
-                        server_is_running = false;
-                        let _ = reader.seek(SeekFrom::Start(0)).await;
-                    }
-
-                    loop {
-                        let mut line = String::new();
-                        match reader.read_line(&mut line).await {
-                            Ok(0) => {
-                                last_position =
-                                    reader.stream_position().await.unwrap_or(last_position);
-                                break;
-                            }
-                            Ok(_) => {
-                                last_position =
-                                    reader.stream_position().await.unwrap_or(last_position);
-                                let trimmed_line = line.trim_end().to_string();
-
-                                let _ = win_clone.emit(
-                                    "server-log-line",
-                                    serde_json::json!({
-                                        "profile_id": profile_id_clone,
-                                        "line": trimmed_line,
-                                    }),
-                                );
-
-                                if !server_is_running && trimmed_line.contains("Server has completed startup and is now advertising for join.") {
-                                    let _ = win_clone.emit("server-running", serde_json::json!({ "profile_id": profile_id_clone }));
-                                    server_is_running = true;
-
-                                    // Parse memory from the startup line
-                                    if let Some(caps) = STARTUP_MEMORY_REGEX.captures(&trimmed_line) {
-                                        if let Some(mem_str) = caps.get(1) {
-                                            if let Ok(mem_gb) = mem_str.as_str().parse::<f64>() {
-                                                let mem_mb = mem_gb * 1024.0;
-                                                println!("[LOG PARSER] Parsed startup memory: {}GB = {}MB", mem_gb, mem_mb);
-                                                let _ = win_clone.emit("log-stats-update", serde_json::json!({ 
-                                                    "profile_id": profile_id_clone, 
-                                                    "memoryMb": mem_mb 
-                                                }));
-                                            }
-                                        }
-                                    }
-                                } else if let Some(caps) = MEMORY_LOGMEMORY_REGEX.captures(&trimmed_line) {
-                                    // Fallback to LogMemory format if present
-                                    if let Some(mem_str) = caps.get(1) {
-                                        if let Ok(mem_mb) = mem_str.as_str().parse::<f64>() {
-                                            println!("[LOG PARSER] Parsed LogMemory: {}MB", mem_mb);
-                                            let _ = win_clone.emit("log-stats-update", serde_json::json!({ 
-                                                "profile_id": profile_id_clone, 
-                                                "memoryMb": mem_mb 
-                                            }));
-                                        }
-                                    }
-                                } else if let Some(caps) = PLAYER_EVENT_REGEX.captures(&trimmed_line) {
-                                    if let (Some(name), Some(id), Some(action)) = (caps.name("name"), caps.name("id"), caps.name("action")) {
-                                        let event_name = if action.as_str() == "joined" { "player-joined" } else { "player-left" };
-                                        let payload = PlayerEventPayload {
-                                            profile_id: profile_id_clone.clone(),
-                                            player_name: name.as_str().to_string(),
-                                            player_id: id.as_str().to_string(),
-                                        };
-                                        let _ = win_clone.emit(event_name, &payload);
-                                    }
-                                }
-                            }
-                            Err(_) => {
-                                consecutive_errors += 1;
-                                if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
-                                    return;
-                                }
-                                break;
-                            }
+                    if let Some(message) = chat::parse_log_line(&profile_id, &line) {
+                        if let Some(hub) = &chat_hub {
+                            hub.record(&window, message);
                         }
                     }
                 }
-                Err(_) => {
+                TailEvent::Rotated | TailEvent::Truncated => pipeline.reset(),
+            });
+            match result {
+                Ok(()) => consecutive_errors = 0,
+                Err(e) => {
                     consecutive_errors += 1;
                     if consecutive_errors >= MAX_CONSECUTIVE_ERRORS {
+                        emit_manager_line(
+                            &window,
+                            &profile_id,
+                            LogSource::Manager,
+                            &format!("[Manager] ❌ Log streaming stopped: {}", e),
+                        );
                         return;
                     }
                 }
             }
 
-            tokio::select! {
-                _ = cancellation_token_clone.cancelled() => break,
-                _ = tokio::time::sleep(Duration::from_millis(500)) => {}
+            if !waker.wait(&cancellation_token).await {
+                break;
             }
         }
     });
+}
 
-    let profile_id_clone_2 = profile_id.clone();
-    let win_clone_2 = window.clone();
-    let processes_clone = processes.0.clone();
-    tokio::spawn(async move {
-        let status = child.wait().await;
+fn archive_line(window: &Window, profile_id: &str, source: LogSource, line: &str) {
+    if let Some(archive) = window.try_state::<Arc<LogArchive>>() {
+        archive.append(profile_id, source, line);
+    }
+}
 
-        let mut procs = processes_clone.lock().await;
-        if let Some(info) = procs.remove(&profile_id_clone_2) {
-            info.cancellation_token.cancel();
-        }
+/// Shows a line in the profile's console and archives it.
+fn emit_manager_line(window: &Window, profile_id: &str, source: LogSource, line: &str) {
+    archive_line(window, profile_id, source, line);
+    let _ = window.emit(
+        "manager-log-line",
+        serde_json::json!({ "profile_id": profile_id, "line": line }),
+    );
+}
 
-        let _ = win_clone_2.emit(
-            "server-stopped",
-            serde_json::json!({
-                "profile_id": profile_id_clone_2,
-                "exit_code": status.ok().and_then(|s| s.code())
-            }),
-        );
-    });
+fn query_address(params: &LaunchParams) -> Option<std::net::SocketAddr> {
+    let ports = ServerPorts::from_launch_args(&params.args, None);
+    ports
+        .query_port
+        .map(|port| a2s::query_address(ports.bind_ip.as_deref(), port))
+}
 
-    Ok(pid)
+/// Polls the server's query port for the lifetime of the process (see `a2s`).
+fn spawn_query_poller(
+    window: Window,
+    params: &LaunchParams,
+    cancellation_token: CancellationToken,
+) {
+    if let Some(addr) = query_address(params) {
+        a2s::spawn_poller(window, params.profile_id.clone(), addr, cancellation_token);
+    }
 }
 
-#[tauri::command]
-async fn stop_ark_server(
-    profile_id: String,
-    processes: State<'_, ServerProcesses>,
-) -> Result<(), String> {
-    println!("Stopping ARK server for profile: {}", profile_id);
+/// Polls `getchat` for the lifetime of the process when RCON is enabled (see `chat`).
+fn spawn_chat_poller(
+    window: Window,
+    profile_id: &str,
+    rcon: Option<Arc<RconSession>>,
+    cancellation_token: CancellationToken,
+) {
+    let (Some(rcon), Some(hub)) = (rcon, window.try_state::<Arc<ChatHub>>()) else {
+        return;
+    };
+    let hub = hub.inner().clone();
+    let audit = window.state::<Arc<RconAudit>>().inner().clone();
+    chat::spawn_poller(
+        window,
+        hub,
+        audit,
+        profile_id.to_string(),
+        rcon,
+        cancellation_token,
+    );
+}
 
-    let process_info = {
-        let procs = processes.0.lock().await;
-        procs.get(&profile_id).cloned()
+fn new_process_info(
+    params: &LaunchParams,
+    pid: u32,
+    cancellation_token: CancellationToken,
+    adopted: bool,
+) -> ServerProcessInfo {
+    let rcon = if params.rcon_enabled {
+        Some(Arc::new(RconSession::new(
+            &params.rcon_ip,
+            params.rcon_port,
+            params.rcon_password.clone(),
+        )))
+    } else {
+        None
     };
+    ServerProcessInfo {
+        pid,
+        launcher_pid: None,
+        rcon,
+        stop_requested: Arc::new(AtomicBool::new(false)),
+        cancellation_token,
+        launch: params.clone(),
+        adopted,
+    }
+}
 
-    if let Some(info) = process_info {
-        #[cfg(target_os = "windows")]
-        {
-            use std::process::Command as StdCommand;
-            StdCommand::new("taskkill")
-                .args(&["/PID", &info.pid.to_string(), "/F", "/T"])
-                .output()
-                .map_err(|e| format!("Failed to stop server: {}", e))?;
+/// Mirrors `ServerProcesses` to disk so running servers can be re-adopted after the
+/// manager restarts. Callers hold the process table lock, so only the snapshot is
+/// taken here; the file is written on a blocking thread.
+fn persist_running_servers<M: Manager<tauri::Wry>>(
+    manager: &M,
+    procs: &HashMap<String, ServerProcessInfo>,
+) {
+    let app_data_dir = match manager.path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            println!("Could not resolve app data dir: {}", e);
+            return;
         }
-
-        #[cfg(not(target_os = "windows"))]
-        {
-            use std::process::Command as StdCommand;
-            StdCommand::new("kill")
-                .args(&["-9", &info.pid.to_string()])
-                .output()
-                .map_err(|e| format!("Failed to stop server: {}", e))?;
+    };
+    let snapshot = adopt::snapshot_registry(procs);
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = adopt::registry_path(&app_data_dir);
+        if let Err(e) = adopt::save_registry(&path, snapshot) {
+            println!("Failed to save running server registry: {}", e);
         }
-        println!("Server stop signal sent successfully");
-        Ok(())
-    } else {
-        Err("No running server found for this profile".to_string())
-    }
+    });
 }
 
-#[tauri::command]
-async fn send_rcon_command(
-    profile_id: String,
-    command: String,
-    processes: State<'_, ServerProcesses>,
-    window: Window,
-) -> Result<(), String> {
-    println!("=== RCON DEBUG: send_rcon_command called ===");
-    println!("Profile ID: {}", profile_id);
-    println!("Command: {}", command);
+/// Spawns the server process, registers it in `ServerProcesses` and starts log tailing.
+async fn spawn_server_process(
+    params: &LaunchParams,
+    processes: &Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    window: &Window,
+) -> Result<(u32, tokio::process::Child), String> {
+    // The watchdog, scheduled tasks and a manual start can race to launch the same
+    // profile, so the check, the spawn and the insert all happen under one lock
+    let launched_at = SystemTime::now();
+    let cancellation_token = CancellationToken::new();
+    let (pid, child, rcon) = {
+        let mut procs = processes.lock().await;
+        if procs.contains_key(&params.profile_id) {
+            return Err("Server is already running for this profile".to_string());
+        }
 
-    let server_info = {
-        let procs = processes.0.lock().await;
-        procs.get(&profile_id).cloned()
+        // SPAWN DIRECTLY (No "cmd /C") to get the actual game process ID
+        let child = params
+            .runtime
+            .server_command(
+                &params.install_path,
+                &params.server_path,
+                &params.args,
+                params.raw_args,
+            )?
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start server: {}", e))?;
+        let pid = child.id().ok_or("Failed to get process ID")?;
+
+        let process_info = new_process_info(params, pid, cancellation_token.clone(), false);
+        let rcon = process_info.rcon.clone();
+        procs.insert(params.profile_id.clone(), process_info);
+        persist_running_servers(window, &procs);
+        (pid, child, rcon)
     };
 
-    if let Some(info) = server_info {
-        println!("Server info found for profile: {}", profile_id);
-        println!("RCON Enabled: {}", info.rcon_enabled);
-
-        if !info.rcon_enabled {
-            println!("ERROR: RCON is not enabled");
-            return Err("RCON is not enabled for this server profile.".into());
-        }
+    if let Some(archive) = window.try_state::<Arc<LogArchive>>() {
+        archive.start_session(&params.profile_id);
+    }
+    window.state::<Arc<PlayerRegistry>>().server_started(
+        &params.profile_id,
+        players::cluster_id(&params.args),
+        false,
+    );
 
-        let addr = format!("{}:{}", info.rcon_ip, info.rcon_port);
-        let pass = info.rcon_password.as_deref().unwrap_or_default();
+    let redacted: Vec<String> = params.args.iter().map(|a| launch::redact(a)).collect();
+    emit_manager_line(
+        window,
+        &params.profile_id,
+        LogSource::Manager,
+        &format!(
+            "[Manager] Launching: {} {}",
+            params.server_path,
+            redacted.join(" ")
+        ),
+    );
 
-        println!(
-            "Connecting to: {} with password length: {}",
-            addr,
-            pass.len()
+    spawn_log_tail(
+        window.clone(),
+        params.profile_id.clone(),
+        InstallLayout::detect(&params.install_path).log_file,
+        Some(launched_at),
+        cancellation_token.clone(),
+    );
+    spawn_query_poller(window.clone(), params, cancellation_token.clone());
+    if params.runtime.wraps_server() {
+        spawn_server_pid_resolver(
+            window.clone(),
+            processes.clone(),
+            params.profile_id.clone(),
+            pid,
+            cancellation_token.clone(),
         );
+    }
+    spawn_chat_poller(window.clone(), &params.profile_id, rcon, cancellation_token);
 
-        // Use rercon's Connection::open with default settings
-        match Connection::open(&addr, pass, Settings::default()).await {
-            Ok(mut conn) => {
-                println!("✓ RCON connection established with rercon!");
-                println!("Sending command: {}", command);
-
-                match conn.exec(&command).await {
-                    Ok(response) => {
-                        println!("✓ Command executed successfully!");
-                        println!("Response length: {} bytes", response.len());
-                        println!("Response: '{}'", response);
-
-                        let response_text = if response.trim().is_empty() {
-                            format!("✓ Command '{}' executed successfully", command)
-                        } else {
-                            response.trim().to_string()
-                        };
-
-                        let _ = window.emit(
-                            "manager-log-line",
-                            serde_json::json!({
-                                "profile_id": profile_id,
-                                "line": response_text
-                            }),
-                        );
+    Ok((pid, child))
+}
 
-                        println!("=== RCON DEBUG: Success ===\n");
-                        Ok(())
-                    }
-                    Err(e) => {
-                        println!("✗ Command execution failed: {}", e);
-
-                        let error_msg = format!("❌ Command failed: {}", e);
-                        let _ = window.emit(
-                            "manager-log-line",
-                            serde_json::json!({
-                                "profile_id": profile_id,
-                                "line": error_msg
-                            }),
-                        );
+/// How long to look for the server behind a launcher before settling for the launcher PID.
+const SERVER_PID_TIMEOUT: Duration = Duration::from_secs(120);
 
-                        println!("=== RCON DEBUG: Command Failed ===\n");
-                        Err(format!("RCON command failed: {}", e))
-                    }
+/// Swaps a launcher's PID in `ServerProcesses` for the server's once the server process
+/// appears, so stats and re-adoption follow the server rather than the launcher. The
+/// watchdog keeps waiting on the launcher, which exits along with the server.
+fn spawn_server_pid_resolver(
+    window: Window,
+    processes: Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    profile_id: String,
+    launcher_pid: u32,
+    cancellation_token: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let deadline = tokio::time::Instant::now() + SERVER_PID_TIMEOUT;
+        let server_pid = loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                _ = tokio::time::sleep(Duration::from_secs(2)) => {}
+            }
+            let found =
+                tokio::task::spawn_blocking(move || platform::find_server_process(launcher_pid))
+                    .await
+                    .ok()
+                    .flatten();
+            if let Some(pid) = found {
+                break pid;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                emit_manager_line(
+                    &window,
+                    &profile_id,
+                    LogSource::Manager,
+                    &format!(
+                        "[Manager] ⚠️ Server process not found behind launcher PID {}; \
+                         stats will show the launcher.",
+                        launcher_pid
+                    ),
+                );
+                return;
+            }
+        };
+
+        {
+            let mut procs = processes.lock().await;
+            match procs.get_mut(&profile_id) {
+                Some(info) if info.pid == launcher_pid => {
+                    info.pid = server_pid;
+                    info.launcher_pid = Some(launcher_pid);
                 }
+                _ => return,
             }
-            Err(e) => {
-                println!("✗ RCON connection failed: {}", e);
+            persist_running_servers(&window, &procs);
+        }
+        emit_manager_line(
+            &window,
+            &profile_id,
+            LogSource::Manager,
+            &format!(
+                "[Manager] Server running as PID {} (launcher PID {}).",
+                server_pid, launcher_pid
+            ),
+        );
+        let _ = window.emit(
+            "server-pid-resolved",
+            serde_json::json!({
+                "profile_id": profile_id,
+                "pid": server_pid,
+                "launcher_pid": launcher_pid,
+            }),
+        );
+    });
+}
+
+/// Waits for the server to exit, works out why it stopped and restarts it after a
+/// crash unless the crash-loop limit has been reached.
+fn spawn_watchdog(
+    params: LaunchParams,
+    child: WatchedProcess,
+    processes: Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    window: Window,
+    mut crash_tracker: CrashTracker,
+) {
+    tokio::spawn(async move {
+        let mut child = child;
+        loop {
+            let status = child.wait().await;
+
+            let stop_requested = {
+                let mut procs = processes.lock().await;
+                let removed = procs.remove(&params.profile_id);
+                persist_running_servers(&window, &procs);
+                match removed {
+                    Some(info) => {
+                        info.cancellation_token.cancel();
+                        info.stop_requested.load(Ordering::SeqCst)
+                    }
+                    None => false,
+                }
+            };
+
+            let exit_code = status
+                .as_ref()
+                .and_then(|s| s.as_ref().ok())
+                .and_then(|s| s.code());
+            let reason = classify_exit(stop_requested, status.as_ref());
+            println!(
+                "Server for profile {} stopped: {:?} (exit code {:?})",
+                params.profile_id, reason, exit_code
+            );
+            window.state::<Arc<PlayerRegistry>>().server_stopped(
+                &params.profile_id,
+                matches!(reason, StopReason::Crashed | StopReason::KilledExternally),
+            );
+
+            if matches!(reason, StopReason::Crashed | StopReason::KilledExternally) {
+                let crash_count = if reason == StopReason::Crashed {
+                    crash_tracker.record_crash()
+                } else {
+                    0
+                };
+                let will_restart =
+                    reason == StopReason::Crashed && crash_tracker.should_restart(crash_count);
 
-                let error_msg = format!("❌ RCON connection failed: {}", e);
                 let _ = window.emit(
-                    "manager-log-line",
+                    "server-crashed",
                     serde_json::json!({
-                        "profile_id": profile_id,
-                        "line": error_msg
+                        "profile_id": params.profile_id,
+                        "reason": reason,
+                        "exit_code": exit_code,
+                        "crash_count": crash_count,
+                        "max_crashes": crash_tracker.policy().max_crashes,
+                        "window_seconds": crash_tracker.policy().window.as_secs(),
+                        "restarting": will_restart,
                     }),
                 );
 
-                println!("=== RCON DEBUG: Connection Failed ===\n");
-                Err(format!("RCON connection failed: {}", e))
+                if will_restart {
+                    emit_manager_line(
+                        &window,
+                        &params.profile_id,
+                        LogSource::Manager,
+                        &format!(
+                            "[Manager] ⚠️ Server crashed (exit code {:?}). Restarting ({}/{} crashes in {}s)...",
+                            exit_code,
+                            crash_count,
+                            crash_tracker.policy().max_crashes,
+                            crash_tracker.policy().window.as_secs()
+                        ),
+                    );
+
+                    match spawn_server_process(&params, &processes, &window).await {
+                        Ok((pid, new_child)) => {
+                            child = WatchedProcess::Child(new_child);
+                            let _ = window.emit(
+                                "server-restarted",
+                                serde_json::json!({
+                                    "profile_id": params.profile_id,
+                                    "pid": pid,
+                                    "crash_count": crash_count,
+                                }),
+                            );
+                            continue;
+                        }
+                        Err(e) => {
+                            emit_manager_line(
+                                &window,
+                                &params.profile_id,
+                                LogSource::Manager,
+                                &format!("[Manager] ❌ Automatic restart failed: {}", e),
+                            );
+                        }
+                    }
+                } else if reason == StopReason::Crashed && crash_tracker.policy().enabled {
+                    emit_manager_line(
+                        &window,
+                        &params.profile_id,
+                        LogSource::Manager,
+                        &format!(
+                            "[Manager] ❌ Server crashed {} times within {}s. Automatic restart disabled until it is started again.",
+                            crash_count,
+                            crash_tracker.policy().window.as_secs()
+                        ),
+                    );
+                }
+            } else if reason == StopReason::Unknown {
+                emit_manager_line(
+                    &window,
+                    &params.profile_id,
+                    LogSource::Manager,
+                    "[Manager] Server process exited. Its exit status is unknown, so it will not \
+                     be restarted automatically.",
+                );
+            }
+
+            if let Some(archive) = window.try_state::<Arc<LogArchive>>() {
+                archive.end_session(&params.profile_id);
             }
+            let _ = window.emit(
+                "server-stopped",
+                serde_json::json!({
+                    "profile_id": params.profile_id,
+                    "exit_code": exit_code,
+                    "reason": reason,
+                }),
+            );
+            break;
         }
-    } else {
-        println!("ERROR: No server info found for profile: {}", profile_id);
-        Err("Server is not running for this profile.".into())
-    }
+    });
 }
 
 #[tauri::command]
-async fn update_server_files(window: Window, install_path: String) -> Result<(), String> {
-    let root_path = PathBuf::from(install_path);
-    let steamcmd_dir = root_path.join("steamcmd");
-    let steamcmd_exe = steamcmd_dir.join("steamcmd.exe");
-
-    if let Err(e) = ensure_steamcmd(&window, "update-log", &steamcmd_dir, &steamcmd_exe).await {
-        log_to_frontend(&window, "update-log", &e);
-        window
-            .emit("update-finished", &serde_json::json!({ "success": false }))
-            .unwrap();
-        return Err(e);
+async fn start_ark_server(
+    profile_id: String,
+    install_path: String,
+    server_path: String,
+    args: Option<Vec<String>>,
+    launch_spec: Option<LaunchSpec>,
+    rcon_ip: String,
+    rcon_port: u16,
+    rcon_password: Option<String>,
+    b_enable_rcon: bool,
+    runtime: Option<ServerRuntime>,
+    auto_restart: Option<bool>,
+    max_crashes: Option<u32>,
+    crash_window_seconds: Option<u64>,
+    skip_validation: Option<bool>,
+    other_profiles: Option<Vec<ProfilePorts>>,
+    skip_port_check: Option<bool>,
+    processes: State<'_, ServerProcesses>,
+    window: Window,
+) -> Result<u32, String> {
+    let runtime = runtime.unwrap_or_default();
+    let (args, raw_args) = launch_args(args, launch_spec.as_ref(), &runtime)?;
+    let report = validate_install_config(&install_path, &args)?;
+    let _ = window.emit(
+        "config-validation",
+        serde_json::json!({ "profile_id": profile_id, "report": report }),
+    );
+    if report.errors > 0 && !skip_validation.unwrap_or(false) {
+        return Err(format!(
+            "Config validation failed:\n{}",
+            report.error_summary()
+        ));
     }
 
-    log_to_frontend(
-        &window,
-        "update-log",
-        "✅ SteamCMD is ready. Starting server file update...",
+    let ports = ServerPorts::from_launch_args(&args, b_enable_rcon.then_some(rcon_port));
+    let port_report = {
+        let procs = processes.0.lock().await;
+        ports::check(
+            &profile_id,
+            &ports,
+            &running_ports(&procs),
+            &other_profiles.unwrap_or_default(),
+        )?
+    };
+    let _ = window.emit(
+        "port-conflicts",
+        serde_json::json!({ "profile_id": profile_id, "report": port_report }),
     );
+    if port_report.blocking > 0 && !skip_port_check.unwrap_or(false) {
+        return Err(format!(
+            "Port conflict:\n{}",
+            port_report.blocking_summary()
+        ));
+    }
+
+    let server_path = resolve_server_path(&install_path, Some(server_path))?;
+
+    let params = LaunchParams {
+        profile_id,
+        install_path,
+        server_path,
+        args,
+        rcon_ip,
+        rcon_port,
+        rcon_password,
+        rcon_enabled: b_enable_rcon,
+        runtime,
+        raw_args,
+        auto_restart,
+        max_crashes,
+        crash_window_seconds,
+    };
+
+    launch_server(params, &processes.0, &window).await
+}
 
-    let install_dir_arg = root_path.to_string_lossy().replace("\\", "/");
-    let script_content = format!(
-        "force_install_dir \"{}\"\nlogin anonymous\napp_update 2430930 validate\nquit\n",
-        install_dir_arg
+/// Starts the server and its watchdog.
+async fn launch_server(
+    params: LaunchParams,
+    processes: &Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    window: &Window,
+) -> Result<u32, String> {
+    let (pid, child) = spawn_server_process(&params, processes, window).await?;
+    let crash_tracker = params.crash_tracker();
+    spawn_watchdog(
+        params,
+        WatchedProcess::Child(child),
+        processes.clone(),
+        window.clone(),
+        crash_tracker,
     );
+    Ok(pid)
+}
 
-    let script_path = steamcmd_dir.join("update_script.txt");
-    if let Err(e) = std::fs::write(&script_path, &script_content) {
-        let error_msg = format!("❌ ERROR: Failed to write SteamCMD script file: {}", e);
-        log_to_frontend(&window, "update-log", &error_msg);
-        return Err(error_msg);
-    }
+fn main_window(app: &AppHandle) -> Option<Window> {
+    let w = app.get_webview_window("main")?;
+    let webview: &tauri::Webview = w.as_ref();
+    Some(webview.window())
+}
 
-    const MAX_RETRIES: u32 = 3;
-    let mut last_error = String::new();
+/// Takes back servers that were started by a previous manager session and are still
+/// running, based on the registry written by `persist_running_servers`.
+async fn adopt_running_servers(app: AppHandle) {
+    let app_data_dir = match app.path().app_data_dir() {
+        Ok(dir) => dir,
+        Err(_) => return,
+    };
 
-    for attempt in 1..=MAX_RETRIES {
-        if attempt > 1 {
-            log_to_frontend(
-                &window,
-                "update-log",
-                &format!(
-                    "\n⚠️ Update attempt {}/{} failed. Retrying in 2 seconds...",
-                    attempt - 1,
-                    MAX_RETRIES
-                ),
-            );
-            tokio::time::sleep(Duration::from_secs(2)).await;
-        }
+    let entries = adopt::load_registry(&adopt::registry_path(&app_data_dir));
+    if !entries.is_empty() {
+        adopt_servers(&app, entries).await;
+    }
+}
 
-        let mut cmd = Command::new(&steamcmd_exe);
-        cmd.current_dir(&steamcmd_dir)
-            .arg("+runscript")
-            .arg(&script_path);
-        cmd.stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped());
-
-        match cmd.spawn() {
-            Ok(mut child) => {
-                let stdout = child.stdout.take().expect("Failed to capture stdout");
-                let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-                let window_clone_out = window.clone();
-                tokio::spawn(async move {
-                    let mut reader = BufReader::new(stdout).lines();
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        log_to_frontend(&window_clone_out, "update-log", &line);
-                    }
-                });
+/// Looks for running servers of the given saved profiles that the manager does not
+/// track, e.g. ones started by hand or missing from the registry, and adopts them.
+/// Servers already re-adopted from the registry get their RCON password back from the
+/// profile, since the registry doesn't store it. Returns the profiles that were adopted.
+#[tauri::command]
+async fn adopt_profile_servers(
+    app: AppHandle,
+    profiles: Vec<ProfileCandidate>,
+) -> Result<Vec<String>, String> {
+    let sessions: Vec<(Arc<RconSession>, Option<String>)> = {
+        let processes = app.state::<ServerProcesses>().0.clone();
+        let mut procs = processes.lock().await;
+        profiles
+            .iter()
+            .filter(|profile| profile.rcon_password.is_some())
+            .filter_map(|profile| {
+                let info = procs.get_mut(&profile.profile_id)?;
+                if info.launch.rcon_password.is_some() {
+                    return None;
+                }
+                info.launch.rcon_password = profile.rcon_password.clone();
+                Some((info.rcon.clone()?, profile.rcon_password.clone()))
+            })
+            .collect()
+    };
+    // Outside the process lock, since a session may be busy with a command
+    for (session, password) in sessions {
+        session.set_password(password).await;
+    }
 
-                let window_clone_err = window.clone();
-                tokio::spawn(async move {
-                    let mut reader = BufReader::new(stderr).lines();
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        log_to_frontend(&window_clone_err, "update-log", &line);
-                    }
-                });
+    let mut entries = vec![];
+    for profile in profiles {
+        let runtime = profile.runtime.unwrap_or_default();
+        let (args, raw_args) =
+            launch_args(profile.args, profile.launch_spec.as_ref(), &runtime)?;
+        let server_path = resolve_server_path(&profile.install_path, profile.server_path)?;
+        entries.push(adopt::RegistryEntry {
+            // No PID to go by; the process is matched on its command line
+            pid: 0,
+            launch: LaunchParams {
+                profile_id: profile.profile_id,
+                install_path: profile.install_path,
+                server_path,
+                args,
+                rcon_ip: profile.rcon_ip,
+                rcon_port: profile.rcon_port,
+                rcon_password: profile.rcon_password,
+                rcon_enabled: profile.rcon_enabled,
+                runtime,
+                raw_args,
+                auto_restart: None,
+                max_crashes: None,
+                crash_window_seconds: None,
+            },
+        });
+    }
+    Ok(adopt_servers(&app, entries).await)
+}
 
-                let status = child.wait().await.map_err(|e| e.to_string());
+/// Matches `entries` to running ARK processes and puts each match back under
+/// management: process info, log tailing from the current end of the log, pollers and
+/// the watchdog. Returns the adopted profiles.
+async fn adopt_servers(app: &AppHandle, entries: Vec<adopt::RegistryEntry>) -> Vec<String> {
+    let Some(window) = main_window(app) else {
+        return vec![];
+    };
+    let running = adopt::scan_ark_processes();
+    let processes = app.state::<ServerProcesses>().0.clone();
+    let mut procs = processes.lock().await;
+    let mut adopted = vec![];
 
-                match status {
-                    Ok(s) => {
-                        if s.success() {
-                            let _ = std::fs::remove_file(&script_path);
-                            log_to_frontend(
-                                &window,
-                                "update-log",
-                                "\n✅ Server file update completed successfully!",
-                            );
-                            window
-                                .emit("update-finished", &serde_json::json!({ "success": true }))
-                                .unwrap();
-                            return Ok(());
-                        } else {
-                            last_error = "Process finished with non-zero exit code.".to_string();
-                        }
-                    }
-                    Err(e) => {
-                        last_error = e;
-                    }
-                }
-            }
-            Err(e) => {
-                last_error = format!("Failed to start SteamCMD process: {}", e);
+    for entry in entries {
+        let profile_id = entry.launch.profile_id.clone();
+        if procs.contains_key(&profile_id) {
+            continue;
+        }
+
+        let pid = match adopt::find_process(&entry, &running) {
+            Some(process) => process.pid,
+            None => {
+                println!("No running server found for profile {}", profile_id);
+                continue;
             }
+        };
+        if procs.values().any(|info| info.pid == pid) {
+            continue;
         }
+
+        println!("Re-adopting server for profile {} (PID {})", profile_id, pid);
+        let cancellation_token = CancellationToken::new();
+        let process_info = new_process_info(&entry.launch, pid, cancellation_token.clone(), true);
+        let rcon = process_info.rcon.clone();
+        procs.insert(profile_id.clone(), process_info);
+        window.state::<Arc<PlayerRegistry>>().server_started(
+            &profile_id,
+            players::cluster_id(&entry.launch.args),
+            true,
+        );
+
+        // Tail from the current end of the log; anything older belongs to a session we already showed
+        spawn_log_tail(
+            window.clone(),
+            profile_id.clone(),
+            InstallLayout::detect(&entry.launch.install_path).log_file,
+            None,
+            cancellation_token.clone(),
+        );
+        spawn_query_poller(window.clone(), &entry.launch, cancellation_token.clone());
+        spawn_chat_poller(window.clone(), &profile_id, rcon, cancellation_token);
+        let crash_tracker = entry.launch.crash_tracker();
+        spawn_watchdog(
+            entry.launch,
+            WatchedProcess::Adopted(pid),
+            processes.clone(),
+            window.clone(),
+            crash_tracker,
+        );
+
+        let _ = window.emit(
+            "server-adopted",
+            serde_json::json!({ "profile_id": profile_id, "pid": pid }),
+        );
+        adopted.push(profile_id);
     }
 
-    let _ = std::fs::remove_file(&script_path);
-    let err_msg = format!(
-        "\n❌ Server file update finished with an error after {} attempts: {}",
-        MAX_RETRIES, last_error
-    );
-    log_to_frontend(&window, "update-log", &err_msg);
-    window
-        .emit("update-finished", &serde_json::json!({ "success": false }))
-        .unwrap();
-    Err(err_msg)
+    persist_running_servers(app, &procs);
+    adopted
+}
+
+/// Closes player sessions of profiles that did not come back after adoption.
+async fn reconcile_player_sessions(app: &AppHandle) {
+    let running: Vec<String> = {
+        let procs = app.state::<ServerProcesses>().0.lock().await;
+        procs.keys().cloned().collect()
+    };
+    app.state::<Arc<PlayerRegistry>>().reconcile(&running);
 }
 
 #[tauri::command]
-async fn update_map(window: Window, install_path: String, map_id: String) -> Result<(), String> {
-    let root_path = PathBuf::from(install_path);
-    let steamcmd_dir = root_path.join("steamcmd");
-    let steamcmd_exe = steamcmd_dir.join("steamcmd.exe");
-
-    if let Err(e) = ensure_steamcmd(&window, "map-update-log", &steamcmd_dir, &steamcmd_exe).await {
-        log_to_frontend(&window, "map-update-log", &e);
-        window
-            .emit(
-                "map-update-finished",
-                &serde_json::json!({ "success": false }),
-            )
-            .unwrap();
-        return Err(e);
+fn get_online_players(
+    scope: Option<PlayerScope>,
+    players: State<'_, Arc<PlayerRegistry>>,
+) -> Vec<PlayerSession> {
+    players.online(&scope.unwrap_or_default())
+}
+
+#[tauri::command]
+fn get_top_players(
+    scope: Option<PlayerScope>,
+    limit: Option<usize>,
+    players: State<'_, Arc<PlayerRegistry>>,
+) -> Vec<PlayerSummary> {
+    players.top_playtime(&scope.unwrap_or_default(), limit)
+}
+
+#[tauri::command]
+fn find_players(
+    query: String,
+    scope: Option<PlayerScope>,
+    limit: Option<usize>,
+    players: State<'_, Arc<PlayerRegistry>>,
+) -> Vec<PlayerSummary> {
+    players.find(&query, &scope.unwrap_or_default(), limit)
+}
+
+#[tauri::command]
+fn get_player(player_id: String, players: State<'_, Arc<PlayerRegistry>>) -> Option<PlayerRecord> {
+    players.player(&player_id)
+}
+
+#[tauri::command]
+fn get_player_sessions(
+    player_id: String,
+    scope: Option<PlayerScope>,
+    limit: Option<usize>,
+    players: State<'_, Arc<PlayerRegistry>>,
+) -> Vec<PlayerSession> {
+    players.sessions(&player_id, &scope.unwrap_or_default(), limit)
+}
+
+#[tauri::command]
+async fn search_logs(
+    profile_id: String,
+    query: Option<LogSearchQuery>,
+    archive: State<'_, Arc<LogArchive>>,
+) -> Result<LogSearchPage, String> {
+    let archive = archive.inner().clone();
+    let query = query.unwrap_or_default();
+    tokio::task::spawn_blocking(move || archive.search(&profile_id, &query))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn list_log_sessions(
+    profile_id: String,
+    archive: State<'_, Arc<LogArchive>>,
+) -> Vec<LogSessionInfo> {
+    archive.sessions(&profile_id)
+}
+
+#[tauri::command]
+fn get_log_rules(rules: State<'_, Arc<LogRules>>) -> Vec<LogRule> {
+    rules.list()
+}
+
+#[tauri::command]
+fn set_log_rule(
+    app: AppHandle,
+    rule: LogRule,
+    rules: State<'_, Arc<LogRules>>,
+) -> Result<(), String> {
+    rules.set_rule(&app, rule)
+}
+
+#[tauri::command]
+fn remove_log_rule(
+    app: AppHandle,
+    rule_id: String,
+    rules: State<'_, Arc<LogRules>>,
+) -> Result<(), String> {
+    rules.remove_rule(&app, &rule_id)
+}
+
+#[tauri::command]
+fn get_rcon_policies(audit: State<'_, Arc<RconAudit>>) -> Vec<CommandPolicy> {
+    audit.policies()
+}
+
+#[tauri::command]
+fn set_rcon_policy(
+    app: AppHandle,
+    policy: CommandPolicy,
+    audit: State<'_, Arc<RconAudit>>,
+) -> Result<(), String> {
+    audit.set_policy(&app, policy)
+}
+
+#[tauri::command]
+fn remove_rcon_policy(
+    app: AppHandle,
+    policy_id: String,
+    audit: State<'_, Arc<RconAudit>>,
+) -> Result<(), String> {
+    audit.remove_policy(&app, &policy_id)
+}
+
+#[tauri::command]
+async fn get_rcon_audit(
+    query: Option<RconAuditQuery>,
+    audit: State<'_, Arc<RconAudit>>,
+) -> Result<Vec<RconAuditEntry>, String> {
+    let audit = audit.inner().clone();
+    let query = query.unwrap_or_default();
+    tokio::task::spawn_blocking(move || audit.entries(&query))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Runs a rule against a sample line without saving it; returns the captures on a match.
+#[tauri::command]
+fn test_log_rule(
+    rule: LogRule,
+    line: String,
+) -> Result<Option<std::collections::BTreeMap<String, String>>, String> {
+    log_events::test_rule(&rule, &line)
+}
+
+/// Queries a running profile's query port once, outside the regular poll.
+#[tauri::command]
+async fn query_server(
+    profile_id: String,
+    processes: State<'_, ServerProcesses>,
+) -> Result<ServerQueryStatus, String> {
+    let addr = {
+        let procs = processes.0.lock().await;
+        let info = procs
+            .get(&profile_id)
+            .ok_or("Server is not running for this profile")?;
+        query_address(&info.launch).ok_or("No query port configured for this profile")?
+    };
+    Ok(a2s::query_status(addr).await)
+}
+
+#[tauri::command]
+async fn get_running_servers(
+    processes: State<'_, ServerProcesses>,
+) -> Result<Vec<RunningServerInfo>, String> {
+    let procs = processes.0.lock().await;
+    Ok(procs
+        .iter()
+        .map(|(profile_id, info)| RunningServerInfo {
+            profile_id: profile_id.clone(),
+            pid: info.pid,
+            adopted: info.adopted,
+        })
+        .collect())
+}
+
+fn force_kill_process(pid: u32) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        use std::process::Command as StdCommand;
+        StdCommand::new("taskkill")
+            .args(&["/PID", &pid.to_string(), "/F", "/T"])
+            .output()
+            .map_err(|e| format!("Failed to stop server: {}", e))?;
     }
 
-    log_to_frontend(
-        &window,
-        "map-update-log",
-        "✅ SteamCMD is ready. Starting map update...",
+    #[cfg(not(target_os = "windows"))]
+    {
+        use std::process::Command as StdCommand;
+        // Servers and SteamCMD jobs run in their own process group (see
+        // `ServerRuntime::server_command`); kill the whole group so wine/proton helpers
+        // and steamcmd.sh's child go too, falling back to the PID.
+        let group_killed = StdCommand::new("kill")
+            .args(&["-9", "--", &format!("-{}", pid)])
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false);
+        if !group_killed {
+            StdCommand::new("kill")
+                .args(&["-9", &pid.to_string()])
+                .output()
+                .map_err(|e| format!("Failed to stop server: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Sends `command` to the profile's server through the RCON policies and audit log.
+async fn rcon_exec(
+    window: &Window,
+    info: &ServerProcessInfo,
+    command: &str,
+    source: CommandSource,
+    confirm_token: Option<&str>,
+) -> Result<String, String> {
+    window
+        .state::<Arc<RconAudit>>()
+        .exec(
+            window,
+            &info.launch.profile_id,
+            info.rcon.as_deref(),
+            command,
+            source,
+            confirm_token,
+        )
+        .await
+}
+
+fn emit_shutdown_stage(window: &Window, profile_id: &str, stage: &str, message: &str) {
+    println!("[SHUTDOWN] {} ({}): {}", profile_id, stage, message);
+    let _ = window.emit(
+        "server-stopping",
+        serde_json::json!({
+            "profile_id": profile_id,
+            "stage": stage,
+            "message": message,
+        }),
     );
+}
 
-    let mut map_app_ids = HashMap::new();
-    map_app_ids.insert("ScorchedEarth_WP", "2430940");
-    map_app_ids.insert("Aberration_WP", "2430950");
-    map_app_ids.insert("Extinction_WP", "2430980");
-    map_app_ids.insert("Valguero_WP", "2430990");
-    map_app_ids.insert("Ragnarok_WP", "2430960");
-    map_app_ids.insert("TheCenter_WP", "2430970");
+// Broadcast marks (in seconds before shutdown) used during the countdown stage.
+const SHUTDOWN_WARNING_MARKS: [u64; 8] = [900, 600, 300, 120, 60, 30, 10, 5];
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 120;
 
-    let dlc_app_id = match map_app_ids.get(map_id.as_str()) {
-        Some(id) => id,
-        None => {
-            let msg = "  > Selected map is not a downloadable DLC. Nothing to do.";
-            log_to_frontend(&window, "map-update-log", msg);
-            window
-                .emit(
-                    "map-update-finished",
-                    &serde_json::json!({ "success": true }),
-                )
-                .unwrap();
-            return Ok(());
-        }
+#[tauri::command]
+async fn stop_ark_server(
+    profile_id: String,
+    graceful: Option<bool>,
+    countdown_seconds: Option<u64>,
+    timeout_seconds: Option<u64>,
+    processes: State<'_, ServerProcesses>,
+    window: Window,
+) -> Result<(), String> {
+    println!("Stopping ARK server for profile: {}", profile_id);
+
+    let process_info = {
+        let procs = processes.0.lock().await;
+        procs.get(&profile_id).cloned()
     };
 
-    log_to_frontend(
+    let info = match process_info {
+        Some(info) => info,
+        None => return Err("No running server found for this profile".to_string()),
+    };
+
+    let timeout = Duration::from_secs(timeout_seconds.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS));
+    stop_server(
         &window,
-        "map-update-log",
-        &format!(
-            "  > Downloading map '{}' (App ID: {})...",
-            map_id, dlc_app_id
-        ),
-    );
+        &info,
+        graceful.unwrap_or(true),
+        countdown_seconds.unwrap_or(0),
+        timeout,
+    )
+    .await
+}
 
-    let install_dir_arg = root_path.to_string_lossy().replace("\\", "/");
-    let script_content = format!(
-        "force_install_dir \"{}\"\nlogin anonymous\napp_update {} validate\nquit\n",
-        install_dir_arg, dlc_app_id
-    );
+/// The stop sequence: countdown broadcasts, `saveworld` and `DoExit` over RCON when
+/// `graceful`, then a forced kill if the server has not exited within `timeout`.
+async fn stop_server(
+    window: &Window,
+    info: &ServerProcessInfo,
+    graceful: bool,
+    countdown_seconds: u64,
+    timeout: Duration,
+) -> Result<(), String> {
+    let profile_id = &info.launch.profile_id;
+    info.stop_requested.store(true, Ordering::SeqCst);
 
-    let script_path = steamcmd_dir.join("map_update_script.txt");
-    if let Err(e) = std::fs::write(&script_path, &script_content) {
-        return Err(format!(
-            "❌ ERROR: Failed to write map update script: {}",
-            e
-        ));
+    if graceful && info.rcon.is_none() {
+        emit_shutdown_stage(
+            window,
+            profile_id,
+            "skipped",
+            "RCON is not enabled for this profile. Falling back to a forced stop.",
+        );
     }
 
-    const MAX_RETRIES: u32 = 3;
-    let mut last_error = String::new();
+    if graceful && info.rcon.is_some() {
+        // Stage 1: countdown broadcasts
+        let mut remaining = countdown_seconds;
+        if remaining > 0 {
+            emit_shutdown_stage(
+                window,
+                profile_id,
+                "countdown",
+                &format!("Shutting down in {} seconds...", remaining),
+            );
+            let _ = rcon_exec(
+                window,
+                info,
+                &format!("broadcast Server shutting down in {} seconds.", remaining),
+                CommandSource::Shutdown,
+                None,
+            )
+            .await;
+
+            while remaining > 0 {
+                let next_mark = SHUTDOWN_WARNING_MARKS
+                    .iter()
+                    .copied()
+                    .find(|mark| *mark < remaining)
+                    .unwrap_or(0);
+
+                tokio::select! {
+                    _ = info.cancellation_token.cancelled() => break,
+                    _ = tokio::time::sleep(Duration::from_secs(remaining - next_mark)) => {}
+                }
+                remaining = next_mark;
+
+                if remaining > 0 {
+                    emit_shutdown_stage(
+                        window,
+                        profile_id,
+                        "countdown",
+                        &format!("Shutting down in {} seconds...", remaining),
+                    );
+                    let _ = rcon_exec(
+                        window,
+                        info,
+                        &format!("broadcast Server shutting down in {} seconds.", remaining),
+                        CommandSource::Shutdown,
+                        None,
+                    )
+                    .await;
+                }
+            }
+        }
 
-    for attempt in 1..=MAX_RETRIES {
-        if attempt > 1 {
-            log_to_frontend(
-                &window,
-                "map-update-log",
-                &format!(
-                    "\n⚠️ Map update attempt {}/{} failed. Retrying in 2 seconds...",
-                    attempt - 1,
-                    MAX_RETRIES
+        // Stage 2: save the world
+        if !info.cancellation_token.is_cancelled() {
+            emit_shutdown_stage(window, profile_id, "saving", "Saving world...");
+            match rcon_exec(window, info, "saveworld", CommandSource::Shutdown, None).await {
+                Ok(_) => emit_shutdown_stage(window, profile_id, "saved", "World saved."),
+                Err(e) => emit_shutdown_stage(
+                    window,
+                    profile_id,
+                    "saveFailed",
+                    &format!("saveworld failed: {}", e),
                 ),
-            );
-            tokio::time::sleep(Duration::from_secs(2)).await;
+            }
         }
 
-        let mut cmd = Command::new(&steamcmd_exe);
-        cmd.current_dir(&steamcmd_dir)
-            .arg("+runscript")
-            .arg(&script_path);
-        cmd.stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped());
-
-        match cmd.spawn() {
-            Ok(mut child) => {
-                let stdout = child.stdout.take().expect("Failed to capture stdout");
-                let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-                let window_clone_out = window.clone();
-                tokio::spawn(async move {
-                    let mut reader = BufReader::new(stdout).lines();
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        log_to_frontend(&window_clone_out, "map-update-log", &line);
-                    }
-                });
+        // Stage 3: ask the server to exit and wait for the process watcher to see it
+        if !info.cancellation_token.is_cancelled() {
+            emit_shutdown_stage(window, profile_id, "exiting", "Sending DoExit...");
+            let exit = rcon_exec(window, info, "DoExit", CommandSource::Shutdown, None).await;
+            if let Err(e) = exit {
+                emit_shutdown_stage(
+                    window,
+                    profile_id,
+                    "exitFailed",
+                    &format!("DoExit failed: {}", e),
+                );
+            }
+        }
 
-                let window_clone_err = window.clone();
-                tokio::spawn(async move {
-                    let mut reader = BufReader::new(stderr).lines();
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        log_to_frontend(&window_clone_err, "map-update-log", &line);
-                    }
-                });
+        emit_shutdown_stage(
+            window,
+            profile_id,
+            "waiting",
+            &format!("Waiting up to {} seconds for the server to exit...", timeout.as_secs()),
+        );
+        if tokio::time::timeout(timeout, info.cancellation_token.cancelled())
+            .await
+            .is_ok()
+        {
+            emit_shutdown_stage(window, profile_id, "exited", "Server exited cleanly.");
+            println!("Server stopped gracefully");
+            return Ok(());
+        }
+    }
 
-                let status = child.wait().await.map_err(|e| e.to_string());
+    // Final stage: forced kill
+    let kill_message = if graceful && info.rcon.is_some() {
+        "Server did not exit in time. Forcing the process to stop."
+    } else {
+        "Forcing the process to stop."
+    };
+    emit_shutdown_stage(window, profile_id, "forceKill", kill_message);
+    // The launcher leads the process group, so killing it takes the server down too
+    force_kill_process(info.launcher_pid.unwrap_or(info.pid))?;
+    println!("Server stop signal sent successfully");
+    Ok(())
+}
 
-                match status {
-                    Ok(s) => {
-                        if s.success() {
-                            let _ = std::fs::remove_file(&script_path);
-                            log_to_frontend(
-                                &window,
-                                "map-update-log",
-                                "\n✅ Map update process completed successfully!",
-                            );
-                            window
-                                .emit(
-                                    "map-update-finished",
-                                    &serde_json::json!({ "success": true }),
-                                )
-                                .unwrap();
-                            return Ok(());
-                        } else {
-                            last_error = "Process finished with non-zero exit code.".to_string();
-                        }
-                    }
-                    Err(e) => {
-                        last_error = e;
-                    }
-                }
-            }
-            Err(e) => {
-                last_error = format!("Failed to start SteamCMD for map: {}", e);
-            }
+#[tauri::command]
+async fn send_rcon_command(
+    profile_id: String,
+    command: String,
+    confirm_token: Option<String>,
+    processes: State<'_, ServerProcesses>,
+    window: Window,
+) -> Result<(), String> {
+    println!("RCON command for profile {}: {}", profile_id, rcon_audit::redact(&command));
+
+    let server_info = {
+        let procs = processes.0.lock().await;
+        procs.get(&profile_id).cloned()
+    };
+
+    let info = match server_info {
+        Some(info) => info,
+        None => {
+            println!("ERROR: No server info found for profile: {}", profile_id);
+            return Err("Server is not running for this profile.".into());
+        }
+    };
+
+    match rcon_exec(
+        &window,
+        &info,
+        &command,
+        CommandSource::Console,
+        confirm_token.as_deref(),
+    )
+    .await
+    {
+        Ok(response) => {
+            let response_text = if response.trim().is_empty() {
+                format!(
+                    "✓ Command '{}' executed successfully",
+                    rcon_audit::redact(&command)
+                )
+            } else {
+                response.trim().to_string()
+            };
+
+            emit_manager_line(&window, &profile_id, LogSource::Rcon, &response_text);
+            Ok(())
+        }
+        Err(e) => {
+            println!("✗ {}", e);
+            emit_manager_line(&window, &profile_id, LogSource::Rcon, &format!("❌ {}", e));
+            Err(e)
         }
     }
+}
 
-    let _ = std::fs::remove_file(&script_path);
-    let err_msg = format!(
-        "\n❌ Map update process finished with an error after {} attempts: {}",
-        MAX_RETRIES, last_error
-    );
-    log_to_frontend(&window, "map-update-log", &err_msg);
-    window
-        .emit(
-            "map-update-finished",
-            &serde_json::json!({ "success": false }),
-        )
-        .unwrap();
-    Err(err_msg)
+#[tauri::command]
+async fn send_chat(
+    profile_id: String,
+    message: String,
+    recipient_id: Option<String>,
+    processes: State<'_, ServerProcesses>,
+    chat: State<'_, Arc<ChatHub>>,
+    window: Window,
+) -> Result<ChatMessage, String> {
+    let (command, chat_message) = chat::outgoing(&profile_id, &message, recipient_id.as_deref())?;
+    let info = {
+        let procs = processes.0.lock().await;
+        procs.get(&profile_id).cloned()
+    }
+    .ok_or("Server is not running for this profile.")?;
+
+    rcon_exec(&window, &info, &command, CommandSource::Chat, None).await?;
+    chat.record(&window, chat_message.clone());
+    Ok(chat_message)
 }
 
 #[tauri::command]
-async fn update_mods(window: Window, install_path: String, mod_ids: String) -> Result<(), String> {
-    let root_path = PathBuf::from(install_path);
-    let steamcmd_dir = root_path.join("steamcmd");
-    let steamcmd_exe = steamcmd_dir.join("steamcmd.exe");
-
-    if let Err(e) = ensure_steamcmd(&window, "mod-update-log", &steamcmd_dir, &steamcmd_exe).await {
-        log_to_frontend(&window, "mod-update-log", &e);
-        window
-            .emit(
-                "mod-update-finished",
-                &serde_json::json!({ "success": false }),
-            )
-            .unwrap();
-        return Err(e);
+async fn get_chat_history(
+    profile_id: String,
+    query: Option<ChatHistoryQuery>,
+    chat: State<'_, Arc<ChatHub>>,
+) -> Result<Vec<ChatMessage>, String> {
+    let chat = chat.inner().clone();
+    let query = query.unwrap_or_default();
+    tokio::task::spawn_blocking(move || chat.history(&profile_id, &query))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_rcon_health(
+    profile_id: String,
+    processes: State<'_, ServerProcesses>,
+) -> Result<RconHealth, String> {
+    let server_info = {
+        let procs = processes.0.lock().await;
+        procs.get(&profile_id).cloned()
+    };
+
+    match server_info {
+        Some(ServerProcessInfo {
+            rcon: Some(session),
+            ..
+        }) => Ok(session.health()),
+        Some(_) => Err("RCON is not enabled for this server profile.".to_string()),
+        None => Err("Server is not running for this profile.".to_string()),
     }
+}
 
-    log_to_frontend(
-        &window,
-        "mod-update-log",
-        "✅ SteamCMD is ready. Starting mod update...",
-    );
+/// Runs the profile's "before update" backup, refusing the update if it fails.
+async fn backup_before_update(
+    app: &AppHandle,
+    scheduler: &BackupScheduler,
+    install_path: &str,
+) -> Result<(), String> {
+    scheduler
+        .backup_for_trigger(app, install_path, BackupTrigger::BeforeUpdate)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Pre-update backup failed, update not started: {}", e))
+}
 
-    let install_dir_arg = root_path.to_string_lossy().replace("\\", "/");
-    let mut script_content = format!(
-        "force_install_dir \"{}\"\nlogin anonymous\n",
-        install_dir_arg
-    );
+#[tauri::command]
+async fn update_server_files(
+    app: AppHandle,
+    window: Window,
+    install_path: String,
+    jobs: State<'_, Arc<SteamCmdJobs>>,
+    scheduler: State<'_, Arc<BackupScheduler>>,
+) -> Result<String, String> {
+    backup_before_update(&app, &scheduler, &install_path).await?;
+    Ok(jobs.enqueue(window, install_path, JobKind::Server).await)
+}
+
+#[tauri::command]
+async fn update_map(
+    app: AppHandle,
+    window: Window,
+    install_path: String,
+    map_id: String,
+    jobs: State<'_, Arc<SteamCmdJobs>>,
+    scheduler: State<'_, Arc<BackupScheduler>>,
+) -> Result<String, String> {
+    backup_before_update(&app, &scheduler, &install_path).await?;
+    let app_id = match map_id.as_str() {
+        "ScorchedEarth_WP" => Some("2430940"),
+        "Aberration_WP" => Some("2430950"),
+        "Extinction_WP" => Some("2430980"),
+        "Valguero_WP" => Some("2430990"),
+        "Ragnarok_WP" => Some("2430960"),
+        "TheCenter_WP" => Some("2430970"),
+        _ => None,
+    };
+    let kind = JobKind::Map {
+        map_id,
+        app_id: app_id.map(|id| id.to_string()),
+    };
+    Ok(jobs.enqueue(window, install_path, kind).await)
+}
 
-    let parsed_mod_ids: Vec<&str> = mod_ids
+#[tauri::command]
+async fn update_mods(
+    app: AppHandle,
+    window: Window,
+    install_path: String,
+    mod_ids: String,
+    jobs: State<'_, Arc<SteamCmdJobs>>,
+    scheduler: State<'_, Arc<BackupScheduler>>,
+) -> Result<String, String> {
+    backup_before_update(&app, &scheduler, &install_path).await?;
+    let mod_ids = mod_ids
         .split(',')
-        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
         .collect();
-    if parsed_mod_ids.is_empty() {
-        log_to_frontend(
-            &window,
-            "mod-update-log",
-            "  > No mod IDs provided. Nothing to do.",
-        );
-        window
-            .emit(
-                "mod-update-finished",
-                &serde_json::json!({ "success": true }),
-            )
-            .unwrap();
-        return Ok(());
-    }
+    Ok(jobs
+        .enqueue(window, install_path, JobKind::Mod { mod_ids })
+        .await)
+}
 
-    log_to_frontend(
-        &window,
-        "mod-update-log",
-        &format!(
-            "  > Found {} mods to download/update...",
-            parsed_mod_ids.len()
-        ),
-    );
-    for mod_id in parsed_mod_ids {
-        script_content.push_str(&format!(
-            "workshop_download_item 2430930 {}\n",
-            mod_id.trim()
-        ));
-    }
-    script_content.push_str("quit\n");
+#[tauri::command]
+async fn cancel_steamcmd_job(
+    job_id: String,
+    jobs: State<'_, Arc<SteamCmdJobs>>,
+) -> Result<(), String> {
+    jobs.cancel(&job_id).await
+}
 
-    let script_path = steamcmd_dir.join("mod_update_script.txt");
-    if let Err(e) = std::fs::write(&script_path, script_content) {
-        return Err(format!(
-            "❌ ERROR: Failed to write mod update script: {}",
-            e
-        ));
-    }
+#[tauri::command]
+async fn list_steamcmd_jobs(jobs: State<'_, Arc<SteamCmdJobs>>) -> Result<Vec<JobInfo>, String> {
+    Ok(jobs.list().await)
+}
 
-    const MAX_RETRIES: u32 = 3;
-    let mut last_error = String::new();
+/// Whether the profile, or any server using the same install, is currently running.
+async fn install_is_running(
+    processes: &Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    install_path: &str,
+    profile_id: Option<&str>,
+) -> bool {
+    let procs = processes.lock().await;
+    procs.iter().any(|(id, info)| {
+        Some(id.as_str()) == profile_id || info.launch.install_path == install_path
+    })
+}
 
-    for attempt in 1..=MAX_RETRIES {
-        if attempt > 1 {
-            log_to_frontend(
-                &window,
-                "mod-update-log",
-                &format!(
-                    "\n⚠️ Mod update attempt {}/{} failed. Retrying in 2 seconds...",
-                    attempt - 1,
-                    MAX_RETRIES
-                ),
-            );
-            tokio::time::sleep(Duration::from_secs(2)).await;
-        }
+/// The running server for `profile_id`, or for any profile using `install_path`.
+async fn running_server(
+    processes: &Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    install_path: &str,
+    profile_id: Option<&str>,
+) -> Option<(String, ServerProcessInfo)> {
+    let procs = processes.lock().await;
+    procs
+        .iter()
+        .find(|(id, info)| {
+            Some(id.as_str()) == profile_id || info.launch.install_path == install_path
+        })
+        .map(|(id, info)| (id.clone(), info.clone()))
+}
 
-        let mut cmd = Command::new(&steamcmd_exe);
-        cmd.current_dir(&steamcmd_dir)
-            .arg("+runscript")
-            .arg(&script_path);
-        cmd.stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped());
-
-        match cmd.spawn() {
-            Ok(mut child) => {
-                let stdout = child.stdout.take().expect("Failed to capture stdout");
-                let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-                let window_clone_out = window.clone();
-                tokio::spawn(async move {
-                    let mut reader = BufReader::new(stdout).lines();
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        log_to_frontend(&window_clone_out, "mod-update-log", &line);
-                    }
-                });
+#[tauri::command]
+async fn list_access_entries(
+    install_path: String,
+    list: AccessList,
+) -> Result<AccessListContents, String> {
+    access_lists::contents(&InstallLayout::detect(&install_path).saved_dir, list)
+}
 
-                let window_clone_err = window.clone();
-                tokio::spawn(async move {
-                    let mut reader = BufReader::new(stderr).lines();
-                    while let Ok(Some(line)) = reader.next_line().await {
-                        log_to_frontend(&window_clone_err, "mod-update-log", &line);
-                    }
-                });
+#[tauri::command]
+async fn add_access_entries(
+    change: AccessListChange,
+    processes: State<'_, ServerProcesses>,
+    audit: State<'_, Arc<AccessAudit>>,
+    window: Window,
+) -> Result<AccessChange, String> {
+    change_access_list(change, AccessAction::Add, &processes.0, &audit, &window).await
+}
 
-                let status = child.wait().await.map_err(|e| e.to_string());
+#[tauri::command]
+async fn remove_access_entries(
+    change: AccessListChange,
+    processes: State<'_, ServerProcesses>,
+    audit: State<'_, Arc<AccessAudit>>,
+    window: Window,
+) -> Result<AccessChange, String> {
+    change_access_list(change, AccessAction::Remove, &processes.0, &audit, &window).await
+}
 
-                match status {
-                    Ok(s) => {
-                        if s.success() {
-                            let _ = std::fs::remove_file(&script_path);
-                            log_to_frontend(
-                                &window,
-                                "mod-update-log",
-                                "\n✅ Mod update process completed successfully!",
-                            );
-                            window
-                                .emit(
-                                    "mod-update-finished",
-                                    &serde_json::json!({ "success": true }),
-                                )
-                                .unwrap();
-                            return Ok(());
-                        } else {
-                            last_error = "Process finished with non-zero exit code.".to_string();
-                        }
-                    }
-                    Err(e) => {
-                        last_error = e;
-                    }
+/// Edits the list file, then applies the change to the running server over RCON when
+/// the list has a command for it. Each id gets its own audit entry.
+async fn change_access_list(
+    change: AccessListChange,
+    action: AccessAction,
+    processes: &Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    audit: &AccessAudit,
+    window: &Window,
+) -> Result<AccessChange, String> {
+    let AccessListChange {
+        install_path,
+        list,
+        player_ids,
+        profile_id,
+        note,
+    } = change;
+    let ids = access_lists::normalize_ids(&player_ids)?;
+    let saved_dir = InstallLayout::detect(&install_path).saved_dir;
+    let changed = access_lists::apply(&saved_dir, list, action, &ids)?;
+
+    let server = running_server(processes, &install_path, profile_id.as_deref()).await;
+    let mut live = vec![];
+    let mut restart_required = false;
+    for id in &ids {
+        let application = match &server {
+            Some((_, info)) => match list.rcon_command(action, id) {
+                Some(command) => {
+                    let result =
+                        rcon_exec(window, info, &command, CommandSource::AccessList, None).await;
+                    Some(LiveApplication {
+                        command,
+                        applied: result.is_ok(),
+                        response: result.as_ref().ok().map(|r| r.trim().to_string()),
+                        error: result.err(),
+                    })
                 }
-            }
-            Err(e) => {
-                last_error = format!("Failed to start SteamCMD for mods: {}", e);
-            }
-        }
+                None => {
+                    restart_required = true;
+                    None
+                }
+            },
+            None => None,
+        };
+        audit.record(AccessAuditEntry {
+            at: String::new(),
+            profile_id: server
+                .as_ref()
+                .map(|(id, _)| id.clone())
+                .or_else(|| profile_id.clone()),
+            install_path: install_path.clone(),
+            list,
+            action,
+            player_id: id.clone(),
+            file_changed: changed.contains(id),
+            live: application.clone(),
+            note: note.clone(),
+        });
+        live.extend(application);
     }
 
-    let _ = std::fs::remove_file(&script_path);
-    let err_msg = format!(
-        "\n❌ Mod update process finished with an error after {} attempts: {}",
-        MAX_RETRIES, last_error
-    );
-    log_to_frontend(&window, "mod-update-log", &err_msg);
-    window
-        .emit(
-            "mod-update-finished",
-            &serde_json::json!({ "success": false }),
-        )
-        .unwrap();
-    Err(err_msg.to_string())
+    Ok(AccessChange {
+        contents: access_lists::contents(&saved_dir, list)?,
+        changed,
+        live,
+        restart_required,
+    })
+}
+
+#[tauri::command]
+fn get_access_audit(
+    query: Option<AccessAuditQuery>,
+    audit: State<'_, Arc<AccessAudit>>,
+) -> Vec<AccessAuditEntry> {
+    audit.entries(&query.unwrap_or_default())
 }
 
 #[tauri::command]
@@ -1017,7 +1724,23 @@ async fn list_backups(install_path: String) -> Result<Vec<BackupInfo>, String> {
         return Ok(vec![]);
     }
 
-    let mut backups = Vec::new();
+    let store = BackupStore::new(backup_dir.clone());
+    let mut backups: Vec<BackupInfo> =
+        tauri::async_runtime::spawn_blocking(move || store.list_snapshots())
+            .await
+            .map_err(|e| e.to_string())??
+            .into_iter()
+            .map(|snapshot| BackupInfo {
+                filename: snapshot.id,
+                created_at: snapshot.created_at,
+                size: snapshot.total_size,
+                kind: "snapshot".to_string(),
+                label: snapshot.label,
+                file_count: snapshot.files.len(),
+            })
+            .collect();
+
+    // Zip backups made before the snapshot store existed
     for entry in fs::read_dir(backup_dir).map_err(|e| e.to_string())? {
         let entry = entry.map_err(|e| e.to_string())?;
         let path = entry.path();
@@ -1030,6 +1753,9 @@ async fn list_backups(install_path: String) -> Result<Vec<BackupInfo>, String> {
                 filename: path.file_name().unwrap().to_string_lossy().to_string(),
                 created_at: created_time.format("%Y-%m-%d %H:%M:%S").to_string(),
                 size: metadata.len(),
+                kind: "zip".to_string(),
+                label: None,
+                file_count: 0,
             });
         }
     }
@@ -1038,66 +1764,32 @@ async fn list_backups(install_path: String) -> Result<Vec<BackupInfo>, String> {
 }
 
 #[tauri::command]
-async fn create_backup(install_path: String) -> Result<(), String> {
+async fn create_backup(install_path: String, label: Option<String>) -> Result<String, String> {
     let backup_dir = get_backup_dir(&install_path);
     fs::create_dir_all(&backup_dir).map_err(|e| e.to_string())?;
 
-    let saved_dir = PathBuf::from(&install_path)
-        .join("ShooterGame")
-        .join("Saved");
+    let saved_dir = InstallLayout::detect(&install_path).saved_dir;
     if !saved_dir.exists() {
         return Err("Saved directory not found. Cannot create backup.".to_string());
     }
 
-    let timestamp = Local::now().format("%Y%m%d-%H%M%S");
-    let backup_filename = format!("backup-{}.zip", timestamp);
-    let backup_path = backup_dir.join(backup_filename);
-
-    let file = fs::File::create(&backup_path).map_err(|e| e.to_string())?;
-    let mut zip = ZipWriter::new(file);
-
-    let options: FileOptions<()> =
-        FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-
-    let walkdir = WalkDir::new(&saved_dir);
-    for entry in walkdir.into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-        let name = path.strip_prefix(&saved_dir).unwrap();
-
-        if path.is_file() {
-            zip.start_file(name.to_string_lossy(), options)
-                .map_err(|e| e.to_string())?;
-            let mut f = fs::File::open(path).map_err(|e| e.to_string())?;
-            let mut buffer = Vec::new();
-            f.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
-            zip.write_all(&buffer).map_err(|e| e.to_string())?;
-        } else if !name.as_os_str().is_empty() {
-            zip.add_directory(name.to_string_lossy(), options)
-                .map_err(|e| e.to_string())?;
-        }
-    }
-
-    zip.finish().map_err(|e| e.to_string())?;
-    Ok(())
+    let store = BackupStore::new(backup_dir);
+    let manifest =
+        tauri::async_runtime::spawn_blocking(move || store.create_snapshot(&saved_dir, label))
+            .await
+            .map_err(|e| e.to_string())??;
+    println!(
+        "Created backup {} ({} files, {} bytes, {} bytes new)",
+        manifest.id,
+        manifest.files.len(),
+        manifest.total_size,
+        manifest.new_data_size
+    );
+    Ok(manifest.id)
 }
 
-#[tauri::command]
-async fn restore_backup(install_path: String, backup_filename: String) -> Result<(), String> {
-    let backup_path = get_backup_dir(&install_path).join(&backup_filename);
-    if !backup_path.exists() {
-        return Err("Backup file not found.".to_string());
-    }
-
-    let saved_dir = PathBuf::from(&install_path)
-        .join("ShooterGame")
-        .join("Saved");
-    if saved_dir.exists() {
-        fs::remove_dir_all(&saved_dir)
-            .map_err(|e| format!("Failed to remove old Saved directory: {}", e))?;
-    }
-    fs::create_dir_all(&saved_dir).map_err(|e| e.to_string())?;
-
-    let file = fs::File::open(&backup_path).map_err(|e| e.to_string())?;
+fn restore_legacy_zip(backup_path: &Path, saved_dir: &Path) -> Result<(), String> {
+    let file = fs::File::open(backup_path).map_err(|e| e.to_string())?;
     let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
 
     for i in 0..archive.len() {
@@ -1116,16 +1808,85 @@ async fn restore_backup(install_path: String, backup_filename: String) -> Result
                 }
             }
             let mut outfile = fs::File::create(&outpath).map_err(|e| e.to_string())?;
-            std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
+            // Reading to the end also checks the entry's CRC
+            let written = std::io::copy(&mut file, &mut outfile)
+                .map_err(|e| format!("{}: {}", file.name(), e))?;
+            if written != file.size() {
+                return Err(format!(
+                    "{}: extracted {} bytes, expected {}",
+                    file.name(),
+                    written,
+                    file.size()
+                ));
+            }
         }
     }
     Ok(())
 }
 
+#[tauri::command]
+async fn restore_backup(
+    app: AppHandle,
+    install_path: String,
+    backup_filename: String,
+    profile_id: Option<String>,
+    processes: State<'_, ServerProcesses>,
+    scheduler: State<'_, Arc<BackupScheduler>>,
+) -> Result<(), String> {
+    if install_is_running(&processes.0, &install_path, profile_id.as_deref()).await {
+        return Err("Stop the server before restoring a backup.".to_string());
+    }
+
+    let backup_dir = get_backup_dir(&install_path);
+    let store = BackupStore::new(backup_dir.clone());
+    let is_snapshot = store.has_snapshot(&backup_filename);
+    let zip_path = backup_dir.join(&backup_filename);
+    if !is_snapshot && !(backup_filename.ends_with(".zip") && zip_path.exists()) {
+        return Err("Backup file not found.".to_string());
+    }
+
+    scheduler
+        .backup_for_trigger(&app, &install_path, BackupTrigger::BeforeRestore)
+        .await
+        .map_err(|e| format!("Pre-restore backup failed, restore aborted: {}", e))?;
+
+    // Extract and verify into a staging dir; the live Saved folder is only swapped
+    // out once that succeeds
+    let saved_dir = InstallLayout::detect(&install_path).saved_dir;
+    tauri::async_runtime::spawn_blocking(move || {
+        backup_store::replace_dir_atomically(&saved_dir, |staging| {
+            if is_snapshot {
+                store.restore_snapshot(&backup_filename, staging)?;
+                store.verify_restore(&backup_filename, staging)
+            } else {
+                restore_legacy_zip(&zip_path, staging)
+            }
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Restore failed: {}", e))
+}
+
 #[tauri::command]
 async fn delete_backup(install_path: String, backup_filename: String) -> Result<(), String> {
-    let backup_path = get_backup_dir(&install_path).join(&backup_filename);
-    if !backup_path.exists() {
+    let backup_dir = get_backup_dir(&install_path);
+    let store = BackupStore::new(backup_dir.clone());
+    if store.has_snapshot(&backup_filename) {
+        let stats = tauri::async_runtime::spawn_blocking(move || {
+            store.delete_snapshot(&backup_filename)
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+        println!(
+            "Deleted backup, freed {} chunks ({} bytes)",
+            stats.chunks_removed, stats.bytes_freed
+        );
+        return Ok(());
+    }
+
+    let backup_path = backup_dir.join(&backup_filename);
+    if !backup_filename.ends_with(".zip") || !backup_path.exists() {
         return Err("Backup file not found.".to_string());
     }
     fs::remove_file(backup_path).map_err(|e| e.to_string())?;
@@ -1133,66 +1894,473 @@ async fn delete_backup(install_path: String, backup_filename: String) -> Result<
 }
 
 #[tauri::command]
-async fn get_server_build_info(install_path: String) -> Result<String, String> {
-    let install_path_buf = PathBuf::from(&install_path);
-    
-    // Primary location: In the steamcmd/steamapps folder (your SteamCMD setup)
-    let manifest_path_steamcmd = install_path_buf
-        .join("steamcmd")
-        .join("steamapps")
-        .join("appmanifest_2430930.acf");
-    
-    // Fallback location 1: In the install path's steamapps folder (old/direct installs)
-    let manifest_path_1 = install_path_buf
-        .join("steamapps")
-        .join("appmanifest_2430930.acf");
-    
-    // Fallback location 2: In the parent steamapps folder (Steam library structure)
-    let manifest_path_2 = install_path_buf
-        .parent()
-        .and_then(|p| p.parent())
-        .map(|p| p.join("appmanifest_2430930.acf"));
-    
-    // Try each location in order
-    let manifest_paths = vec![
-        Some(manifest_path_steamcmd),  // Check steamcmd folder FIRST
-        Some(manifest_path_1),
-        manifest_path_2,
-    ];
-    
-    for maybe_path in manifest_paths.iter().flatten() {
-        if maybe_path.exists() {
-            println!("Found manifest at: {:?}", maybe_path);
-            let content = fs::read_to_string(maybe_path).map_err(|e| e.to_string())?;
-            let re = Regex::new(r#""buildid"\s*"(\d+)""#).unwrap();
-            if let Some(caps) = re.captures(&content) {
-                if let Some(build_id) = caps.get(1) {
-                    return Ok(build_id.as_str().to_string());
+async fn set_backup_policy(
+    app: AppHandle,
+    profile_id: String,
+    install_path: String,
+    policy: BackupPolicy,
+    processes: State<'_, ServerProcesses>,
+    scheduler: State<'_, Arc<BackupScheduler>>,
+) -> Result<(), String> {
+    scheduler
+        .set_policy(app, processes.0.clone(), profile_id, install_path, policy)
+        .await
+}
+
+#[tauri::command]
+async fn get_backup_policy(
+    profile_id: String,
+    scheduler: State<'_, Arc<BackupScheduler>>,
+) -> Result<Option<BackupPolicy>, String> {
+    Ok(scheduler.policy(&profile_id).await)
+}
+
+#[tauri::command]
+async fn remove_backup_policy(
+    app: AppHandle,
+    profile_id: String,
+    scheduler: State<'_, Arc<BackupScheduler>>,
+) -> Result<(), String> {
+    scheduler.remove_policy(&app, &profile_id).await
+}
+
+/// Waits until the watchdog has taken the profile's server out of `ServerProcesses`.
+async fn wait_for_server_exit(
+    processes: &Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    profile_id: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while processes.lock().await.contains_key(profile_id) {
+        if tokio::time::Instant::now() >= deadline {
+            return Err("The server did not stop in time".to_string());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    Ok(())
+}
+
+/// Stops the server for a scheduled task and waits for it to be gone; returns the
+/// launch parameters to start it again with.
+async fn stop_for_task(
+    window: &Window,
+    processes: &Arc<Mutex<HashMap<String, ServerProcessInfo>>>,
+    info: &ServerProcessInfo,
+    countdown_seconds: Option<u64>,
+) -> Result<LaunchParams, String> {
+    let timeout = Duration::from_secs(DEFAULT_SHUTDOWN_TIMEOUT_SECS);
+    stop_server(window, info, true, countdown_seconds.unwrap_or(0), timeout).await?;
+    wait_for_server_exit(processes, &info.launch.profile_id, Duration::from_secs(30)).await?;
+    Ok(info.launch.clone())
+}
+
+/// An automatic backup for a scheduled task, under the profile's retention rules.
+async fn backup_for_task(
+    app: &AppHandle,
+    task: &ScheduledTask,
+    trigger: BackupTrigger,
+) -> Result<String, String> {
+    let retention = app
+        .state::<Arc<BackupScheduler>>()
+        .policy(&task.profile_id)
+        .await
+        .map(|policy| policy.retention)
+        .unwrap_or_default();
+    backup_scheduler::run_backup(
+        app,
+        Some(&task.profile_id),
+        &task.install_path,
+        trigger,
+        retention,
+    )
+    .await
+}
+
+/// Carries out one scheduled task for `TaskScheduler`; the message ends up in the
+/// task's last run.
+async fn run_scheduled_task(app: &AppHandle, task: &ScheduledTask) -> Result<String, String> {
+    let window = main_window(app).ok_or("The main window is not available")?;
+    let processes = app.state::<ServerProcesses>().0.clone();
+    let info = processes.lock().await.get(&task.profile_id).cloned();
+    let not_running = || "Server is not running for this profile.".to_string();
+
+    match &task.action {
+        TaskAction::Restart { countdown_seconds } => {
+            let Some(info) = info else {
+                return Ok("Server is not running; nothing to restart.".to_string());
+            };
+            let params = stop_for_task(&window, &processes, &info, *countdown_seconds).await?;
+            let pid = launch_server(params, &processes, &window).await?;
+            let _ = window.emit(
+                "server-restarted",
+                serde_json::json!({ "profile_id": task.profile_id, "pid": pid, "scheduled": true }),
+            );
+            Ok(format!("Server restarted (PID {}).", pid))
+        }
+        TaskAction::UpdateCheck => {
+            let installed = get_server_build_info(task.install_path.clone()).await?;
+            let latest = get_latest_server_build(task.install_path.clone()).await?;
+            if installed == latest {
+                return Ok(format!("Build {} is up to date.", installed));
+            }
+            let _ = window.emit(
+                "server-update-available",
+                serde_json::json!({
+                    "profile_id": task.profile_id,
+                    "install_path": task.install_path,
+                    "installed_build": installed,
+                    "latest_build": latest,
+                }),
+            );
+            Ok(format!("Update available: build {} -> {}.", installed, latest))
+        }
+        TaskAction::Backup => {
+            let backup_id = backup_for_task(app, task, BackupTrigger::ScheduledTask).await?;
+            Ok(format!("Backup {} created.", backup_id))
+        }
+        TaskAction::RconCommand { command } => {
+            let info = info.ok_or_else(not_running)?;
+            let response =
+                rcon_exec(&window, &info, command, CommandSource::Scheduler, None).await?;
+            Ok(response.trim().to_string())
+        }
+        TaskAction::Broadcast { message } => {
+            let info = info.ok_or_else(not_running)?;
+            let command = format!("broadcast {}", message);
+            rcon_exec(&window, &info, &command, CommandSource::Scheduler, None).await?;
+            Ok("Broadcast sent.".to_string())
+        }
+        TaskAction::Wipe {
+            countdown_seconds,
+            restart,
+        } => {
+            let params = match &info {
+                Some(info) => {
+                    Some(stop_for_task(&window, &processes, info, *countdown_seconds).await?)
+                }
+                None => None,
+            };
+            // No wipe without a backup to come back to
+            let backup_id = match backup_for_task(app, task, BackupTrigger::BeforeWipe).await {
+                Ok(backup_id) => backup_id,
+                Err(e) => {
+                    // Nothing was wiped; don't leave a server that should restart down
+                    if let Some(params) = params.filter(|_| *restart) {
+                        if let Err(launch_error) = launch_server(params, &processes, &window).await
+                        {
+                            return Err(format!(
+                                "{} (relaunching the server also failed: {})",
+                                e, launch_error
+                            ));
+                        }
+                    }
+                    return Err(e);
+                }
+            };
+            let saves = InstallLayout::detect(&task.install_path)
+                .saved_dir
+                .join("SavedArks");
+            if saves.exists() {
+                tokio::fs::remove_dir_all(&saves)
+                    .await
+                    .map_err(|e| format!("Failed to delete {:?}: {}", saves, e))?;
+            }
+            emit_manager_line(
+                &window,
+                &task.profile_id,
+                LogSource::Manager,
+                &format!("[Manager] World wiped. Backup taken first: {}", backup_id),
+            );
+            match params {
+                Some(params) if *restart => {
+                    let pid = launch_server(params, &processes, &window).await?;
+                    Ok(format!(
+                        "World wiped (backup {}), server restarted (PID {}).",
+                        backup_id, pid
+                    ))
                 }
+                _ => Ok(format!("World wiped (backup {}).", backup_id)),
             }
-            return Err("Could not find build ID in manifest file.".to_string());
         }
     }
-    
-    // If we get here, we couldn't find the manifest anywhere
-    Err(format!(
-        "App manifest file not found. Searched in:\n  - {:?}\n  - {:?}\n  - {:?}",
-        manifest_paths[0].as_ref().unwrap(),
-        manifest_paths[1].as_ref().unwrap(),
-        manifest_paths[2].as_ref().unwrap_or(&PathBuf::from("N/A"))
-    ))
+}
+
+#[tauri::command]
+async fn list_scheduled_tasks(
+    profile_id: Option<String>,
+    scheduler: State<'_, Arc<TaskScheduler>>,
+) -> Result<Vec<ScheduledTaskStatus>, String> {
+    Ok(scheduler.tasks(profile_id.as_deref()).await)
+}
+
+#[tauri::command]
+async fn set_scheduled_task(
+    app: AppHandle,
+    task: ScheduledTask,
+    scheduler: State<'_, Arc<TaskScheduler>>,
+) -> Result<ScheduledTaskStatus, String> {
+    scheduler.set_task(&app, task).await
+}
+
+#[tauri::command]
+async fn remove_scheduled_task(
+    app: AppHandle,
+    task_id: String,
+    scheduler: State<'_, Arc<TaskScheduler>>,
+) -> Result<(), String> {
+    scheduler.remove_task(&app, &task_id).await
+}
+
+#[tauri::command]
+async fn set_profile_schedule_enabled(
+    app: AppHandle,
+    profile_id: String,
+    enabled: bool,
+    scheduler: State<'_, Arc<TaskScheduler>>,
+) -> Result<(), String> {
+    scheduler
+        .set_profile_enabled(&app, &profile_id, enabled)
+        .await
+}
+
+#[tauri::command]
+async fn run_scheduled_task_now(
+    app: AppHandle,
+    task_id: String,
+    scheduler: State<'_, Arc<TaskScheduler>>,
+) -> Result<TaskRun, String> {
+    scheduler.run_now(&app, &task_id).await
+}
+
+#[tauri::command]
+fn preview_cron(expression: String, count: Option<usize>) -> Result<Vec<String>, String> {
+    task_scheduler::preview(&expression, count)
+}
+
+fn ini_path(install_path: &str, file: IniFile) -> PathBuf {
+    InstallLayout::detect(install_path)
+        .config_dir
+        .join(file.file_name())
+}
+
+#[tauri::command]
+async fn read_ini_file(install_path: String, file: IniFile) -> Result<IniFileContents, String> {
+    let path = ini_path(&install_path, file);
+    let doc = ini::read_document(&path)?;
+    Ok(IniFileContents::new(&path, &doc))
+}
+
+#[tauri::command]
+async fn patch_ini_file(
+    install_path: String,
+    file: IniFile,
+    patches: Vec<IniPatch>,
+    profile_id: Option<String>,
+    processes: State<'_, ServerProcesses>,
+) -> Result<IniFileContents, String> {
+    // ARK rewrites its config on shutdown, which would silently undo our changes
+    if install_is_running(&processes.0, &install_path, profile_id.as_deref()).await {
+        return Err("Stop the server before editing its config files.".to_string());
+    }
+
+    let path = ini_path(&install_path, file);
+    let mut doc = ini::read_document(&path)?;
+    for patch in &patches {
+        doc.apply(patch);
+    }
+    ini::write_atomic(&path, &doc.to_bytes())?;
+    Ok(IniFileContents::new(&path, &doc))
+}
+
+#[tauri::command]
+async fn write_ini_file(
+    install_path: String,
+    file: IniFile,
+    content: String,
+    profile_id: Option<String>,
+    processes: State<'_, ServerProcesses>,
+) -> Result<IniFileContents, String> {
+    if install_is_running(&processes.0, &install_path, profile_id.as_deref()).await {
+        return Err("Stop the server before editing its config files.".to_string());
+    }
+
+    let path = ini_path(&install_path, file);
+    let doc = ini::read_document(&path)?.reparse(&content);
+    ini::write_atomic(&path, &doc.to_bytes())?;
+    Ok(IniFileContents::new(&path, &doc))
+}
+
+/// Picks the launch arguments: a `LaunchSpec` is rendered by the backend for `runtime`,
+/// otherwise the caller's pre-built list is passed through. The flag says whether the
+/// args are raw command-line tokens.
+fn launch_args(
+    args: Option<Vec<String>>,
+    launch_spec: Option<&LaunchSpec>,
+    runtime: &ServerRuntime,
+) -> Result<(Vec<String>, bool), String> {
+    match (launch_spec, args) {
+        (Some(spec), _) => {
+            let raw = runtime.takes_raw_args();
+            Ok((spec.render(raw)?, raw))
+        }
+        (None, Some(args)) => Ok((args, false)),
+        (None, None) => Err("Either args or a launch spec is required.".to_string()),
+    }
+}
+
+/// Falls back to the detected executable when the profile's path is missing or stale.
+fn resolve_server_path(install_path: &str, server_path: Option<String>) -> Result<String, String> {
+    if let Some(path) = server_path.filter(|p| !p.is_empty() && Path::new(p).exists()) {
+        return Ok(path);
+    }
+    let layout = InstallLayout::detect(install_path);
+    if !layout.executable_found {
+        return Err(format!(
+            "ArkAscendedServer.exe not found. {}",
+            layout.issues.join(" ")
+        ));
+    }
+    Ok(layout.executable.to_string_lossy().to_string())
+}
+
+fn validate_install_config(install_path: &str, args: &[String]) -> Result<ValidationReport, String> {
+    let mut report = ValidationReport::default();
+    for file in [IniFile::GameUserSettings, IniFile::Game] {
+        let doc = ini::read_document(&ini_path(install_path, file))?;
+        config_schema::validate_ini(file, &doc.sections(), &mut report);
+    }
+    config_schema::validate_launch_args(args, &mut report);
+    Ok(report)
+}
+
+#[tauri::command]
+async fn validate_config(
+    install_path: String,
+    args: Option<Vec<String>>,
+) -> Result<ValidationReport, String> {
+    validate_install_config(&install_path, &args.unwrap_or_default())
+}
+
+fn running_ports(procs: &HashMap<String, ServerProcessInfo>) -> Vec<RunningPorts> {
+    procs
+        .iter()
+        .map(|(profile_id, info)| RunningPorts {
+            profile_id: profile_id.clone(),
+            pid: info.pid,
+            ports: ServerPorts::from_launch_args(
+                &info.launch.args,
+                info.launch.rcon_enabled.then_some(info.launch.rcon_port),
+            ),
+        })
+        .collect()
+}
+
+/// Port pre-flight for a profile that is not running yet. `other_profiles` are the
+/// saved profiles to compare against; running servers are read from `ServerProcesses`.
+#[tauri::command]
+async fn check_ports(
+    profile_id: String,
+    ports: ServerPorts,
+    other_profiles: Option<Vec<ProfilePorts>>,
+    processes: State<'_, ServerProcesses>,
+) -> Result<PortCheckReport, String> {
+    let procs = processes.0.lock().await;
+    ports::check(
+        &profile_id,
+        &ports,
+        &running_ports(&procs),
+        &other_profiles.unwrap_or_default(),
+    )
+}
+
+/// Dry run of `start_ark_server`: renders the spec into the exact command that would be
+/// spawned and validates it, without starting anything.
+#[tauri::command]
+async fn preview_launch(
+    install_path: String,
+    server_path: Option<String>,
+    launch_spec: LaunchSpec,
+    runtime: Option<ServerRuntime>,
+) -> Result<LaunchPreview, String> {
+    let runtime = runtime.unwrap_or_default();
+    let raw = runtime.takes_raw_args();
+    let args = launch_spec.render(raw)?;
+    let server_path = resolve_server_path(&install_path, server_path)?;
+    let validation = validate_install_config(&install_path, &args)?;
+    let cmd = runtime.server_command(&install_path, &server_path, &args, raw)?;
+    Ok(launch::preview(&cmd, validation))
+}
+
+#[tauri::command]
+fn get_config_schema() -> Vec<SettingSpec> {
+    config_schema::SCHEMA.to_vec()
+}
+
+#[tauri::command]
+async fn read_ini_structured(
+    install_path: String,
+    file: IniFile,
+) -> Result<Vec<StructuredSection>, String> {
+    let path = ini_path(&install_path, file);
+    let doc = ini::read_document(&path)?;
+    Ok(doc.sections().iter().map(ue_text::structure_section).collect())
+}
+
+#[tauri::command]
+fn parse_ue_value(text: String) -> Result<UeValue, String> {
+    ue_text::parse(&text)
+}
+
+#[tauri::command]
+fn format_ue_value(value: UeValue) -> String {
+    value.to_string()
+}
+
+#[tauri::command]
+async fn get_server_build_info(install_path: String) -> Result<String, String> {
+    let layout = InstallLayout::detect(&install_path);
+    let manifest_path = layout.manifest_path.ok_or_else(|| {
+        let searched: Vec<String> = layout
+            .manifest_candidates
+            .iter()
+            .map(|p| format!("  - {:?}", p))
+            .collect();
+        format!(
+            "App manifest file not found. Searched in:\n{}",
+            searched.join("\n")
+        )
+    })?;
+
+    println!("Found manifest at: {:?}", manifest_path);
+    let content = fs::read_to_string(&manifest_path).map_err(|e| e.to_string())?;
+    let re = Regex::new(r#""buildid"\s*"(\d+)""#).unwrap();
+    if let Some(caps) = re.captures(&content) {
+        if let Some(build_id) = caps.get(1) {
+            return Ok(build_id.as_str().to_string());
+        }
+    }
+    Err("Could not find build ID in manifest file.".to_string())
+}
+
+#[tauri::command]
+async fn get_install_layout(install_path: String) -> Result<InstallLayout, String> {
+    Ok(InstallLayout::detect(&install_path))
 }
 
 #[tauri::command]
 async fn get_latest_server_build(install_path: String) -> Result<String, String> {
     let steamcmd_dir = PathBuf::from(&install_path).join("steamcmd");
-    let steamcmd_exe = steamcmd_dir.join("steamcmd.exe");
+    let steamcmd_exe = steamcmd_dir.join(platform::STEAMCMD_EXECUTABLE);
 
     if !steamcmd_exe.exists() {
-        return Err("steamcmd.exe not found. Cannot check for updates.".to_string());
+        return Err(format!(
+            "{} not found. Cannot check for updates.",
+            platform::STEAMCMD_EXECUTABLE
+        ));
     }
 
     let output = Command::new(&steamcmd_exe)
+        .args(platform::steamcmd_preamble_args())
         .arg("+login")
         .arg("anonymous")
         .arg("+app_info_print")
@@ -1342,7 +2510,11 @@ async fn diagnose_rcon(
 
             // Test saveworld command
             println!("Step 3: Testing saveworld command...");
-            match conn.exec("saveworld").await {
+            let audit = window.state::<Arc<RconAudit>>();
+            let started = std::time::Instant::now();
+            let result = conn.exec("saveworld").await.map_err(|e| e.to_string());
+            audit.record_direct(&window, CommandSource::Diagnostics, "saveworld", &result, started);
+            match result {
                 Ok(response) => {
                     println!("✓ saveworld successful! Response: '{}'", response);
                     emit_step(
@@ -1365,7 +2537,11 @@ async fn diagnose_rcon(
 
             // Test broadcast
             println!("Testing broadcast command...");
-            match conn.exec("broadcast RCON Test from Manager").await {
+            let command = "broadcast RCON Test from Manager";
+            let started = std::time::Instant::now();
+            let result = conn.exec(command).await.map_err(|e| e.to_string());
+            audit.record_direct(&window, CommandSource::Diagnostics, command, &result, started);
+            match result {
                 Ok(response) => {
                     println!("✓ broadcast successful! Response: '{}'", response);
                     emit_step(
@@ -1426,24 +2602,109 @@ fn main() {
             Some(vec!["--flag-as-example"]),
         ))
         .manage(ServerProcesses(Arc::new(Mutex::new(HashMap::new()))))
+        .manage(Arc::new(SteamCmdJobs::default()))
+        .manage(Arc::new(BackupScheduler::default()))
+        .manage(Arc::new(TaskScheduler::default()))
+        .manage(Arc::new(LogRules::default()))
+        .manage(Arc::new(PlayerRegistry::default()))
+        .manage(Arc::new(RconAudit::default()))
         .invoke_handler(tauri::generate_handler![
             start_ark_server,
             stop_ark_server,
             send_rcon_command,
+            get_rcon_policies,
+            set_rcon_policy,
+            remove_rcon_policy,
+            get_rcon_audit,
+            send_chat,
+            get_chat_history,
+            get_rcon_health,
+            get_running_servers,
+            adopt_profile_servers,
             update_server_files,
             update_map,
             update_mods,
+            cancel_steamcmd_job,
+            list_steamcmd_jobs,
             list_backups,
             create_backup,
             restore_backup,
             delete_backup,
+            set_backup_policy,
+            get_backup_policy,
+            remove_backup_policy,
+            list_scheduled_tasks,
+            set_scheduled_task,
+            remove_scheduled_task,
+            set_profile_schedule_enabled,
+            run_scheduled_task_now,
+            preview_cron,
             get_server_build_info,
+            get_install_layout,
+            read_ini_file,
+            patch_ini_file,
+            write_ini_file,
+            list_access_entries,
+            add_access_entries,
+            remove_access_entries,
+            get_access_audit,
+            read_ini_structured,
+            validate_config,
+            get_config_schema,
+            preview_launch,
+            check_ports,
+            query_server,
+            search_logs,
+            list_log_sessions,
+            get_online_players,
+            get_top_players,
+            find_players,
+            get_player,
+            get_player_sessions,
+            get_log_rules,
+            set_log_rule,
+            remove_log_rule,
+            test_log_rule,
+            parse_ue_value,
+            format_ue_value,
             get_latest_server_build,
             get_server_stats,
             diagnose_rcon,
             get_local_ips
         ])
         .setup(|app| {
+            app.state::<Arc<LogRules>>().load(app.handle());
+            app.state::<Arc<PlayerRegistry>>().load(app.handle());
+            app.state::<Arc<RconAudit>>().load(app.handle());
+            let log_dir = app.path().app_data_dir()?.join("logs");
+            app.manage(Arc::new(LogArchive::new(log_dir)));
+            let chat_dir = app.path().app_data_dir()?.join("chat");
+            app.manage(Arc::new(ChatHub::new(chat_dir)));
+            let audit_path = app.path().app_data_dir()?.join("access_audit.jsonl");
+            app.manage(Arc::new(AccessAudit::new(audit_path)));
+            {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    adopt_running_servers(handle.clone()).await;
+                    reconcile_player_sessions(&handle).await;
+                });
+            }
+            {
+                let handle = app.handle().clone();
+                let scheduler = app.state::<Arc<BackupScheduler>>().inner().clone();
+                let processes = app.state::<ServerProcesses>().0.clone();
+                tauri::async_runtime::spawn(async move {
+                    scheduler.load(handle, processes).await;
+                });
+            }
+            {
+                let handle = app.handle().clone();
+                let tasks = app.state::<Arc<TaskScheduler>>().inner().clone();
+                tauri::async_runtime::spawn(async move {
+                    tasks.load(handle).await;
+                });
+            }
+
             // Get the tray icon created from tauri.conf.json
             let tray = match app.tray_by_id("main") {
                 Some(t) => t,
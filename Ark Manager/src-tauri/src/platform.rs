@@ -0,0 +1,220 @@
+// src-tauri/src/platform.rs
+//
+// Host platform differences. On Windows SteamCMD and the ASA dedicated server run
+// natively; on Linux we use `steamcmd.sh`, force SteamCMD to fetch the Windows depots
+// (ASA has no Linux server build) and run `ArkAscendedServer.exe` through Wine or Proton.
+
+use std::path::{Path, PathBuf};
+use sysinfo::{Pid, System};
+use tokio::process::Command;
+
+#[cfg(target_os = "windows")]
+pub const STEAMCMD_EXECUTABLE: &str = "steamcmd.exe";
+#[cfg(not(target_os = "windows"))]
+pub const STEAMCMD_EXECUTABLE: &str = "steamcmd.sh";
+
+#[cfg(target_os = "windows")]
+const STEAMCMD_DOWNLOAD_URL: &str = "https://steamcdn-a.akamaihd.net/client/installer/steamcmd.zip";
+#[cfg(not(target_os = "windows"))]
+const STEAMCMD_DOWNLOAD_URL: &str =
+    "https://steamcdn-a.akamaihd.net/client/installer/steamcmd_linux.tar.gz";
+
+pub fn steamcmd_download_url() -> &'static str {
+    STEAMCMD_DOWNLOAD_URL
+}
+
+/// File name the SteamCMD archive is downloaded to inside the steamcmd directory.
+pub fn steamcmd_archive_name() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "steamcmd.zip"
+    } else {
+        "steamcmd_linux.tar.gz"
+    }
+}
+
+/// Extracts the downloaded SteamCMD archive. Linux ships a tarball, which we hand to
+/// the system `tar` so the executable bits survive.
+pub fn extract_steamcmd_archive(archive: &Path, dest_dir: &Path) -> Result<(), String> {
+    if cfg!(target_os = "windows") {
+        crate::unzip_file(archive, dest_dir).map_err(|e| e.to_string())
+    } else {
+        let output = std::process::Command::new("tar")
+            .arg("-xzf")
+            .arg(archive)
+            .arg("-C")
+            .arg(dest_dir)
+            .output()
+            .map_err(|e| format!("Failed to run tar: {}", e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+        }
+    }
+}
+
+/// SteamCMD commands that must run before `login` on this host.
+pub fn steamcmd_preamble() -> &'static str {
+    if cfg!(target_os = "windows") {
+        ""
+    } else {
+        "@sSteamCmdForcePlatformType windows\n"
+    }
+}
+
+/// Same as `steamcmd_preamble`, as command line arguments.
+pub fn steamcmd_preamble_args() -> Vec<&'static str> {
+    if cfg!(target_os = "windows") {
+        vec![]
+    } else {
+        vec!["+@sSteamCmdForcePlatformType", "windows"]
+    }
+}
+
+/// How the Windows server binary is executed on this host.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ServerRuntime {
+    Native,
+    #[serde(rename_all = "camelCase")]
+    Wine {
+        #[serde(default)]
+        wine_binary: Option<String>,
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Proton {
+        /// Directory containing the `proton` script, e.g. ".../common/Proton - Experimental".
+        proton_path: String,
+        #[serde(default)]
+        compat_data_path: Option<String>,
+        #[serde(default)]
+        steam_client_path: Option<String>,
+    },
+}
+
+impl Default for ServerRuntime {
+    fn default() -> Self {
+        if cfg!(target_os = "windows") {
+            ServerRuntime::Native
+        } else {
+            ServerRuntime::Wine {
+                wine_binary: None,
+                prefix: None,
+            }
+        }
+    }
+}
+
+impl ServerRuntime {
+    /// Whether launch args for this runtime are rendered as raw command-line tokens. Only
+    /// a native Windows process takes its command line verbatim; Wine and Proton receive
+    /// argv elements and quote them into a Windows command line themselves.
+    pub fn takes_raw_args(&self) -> bool {
+        cfg!(target_os = "windows") && *self == ServerRuntime::Native
+    }
+
+    /// Whether the spawned process is a launcher rather than the server itself. Proton's
+    /// `proton` script starts the server through Wine a few processes further down, so
+    /// the PID of the spawned child only tracks the launcher (see `find_server_process`).
+    pub fn wraps_server(&self) -> bool {
+        matches!(self, ServerRuntime::Proton { .. })
+    }
+
+    /// Builds the command that launches the server executable with the given args.
+    /// `raw_args` marks args that are already quoted command-line tokens (see
+    /// `launch::LaunchSpec::render`); they are passed through verbatim when the runtime
+    /// `takes_raw_args`.
+    pub fn server_command(
+        &self,
+        install_path: &str,
+        server_path: &str,
+        args: &[String],
+        raw_args: bool,
+    ) -> Result<Command, String> {
+        let mut cmd = match self {
+            ServerRuntime::Native => Command::new(server_path),
+            ServerRuntime::Wine {
+                wine_binary,
+                prefix,
+            } => {
+                let mut cmd = Command::new(wine_binary.as_deref().unwrap_or("wine"));
+                cmd.arg(server_path).env("WINEDEBUG", "-all");
+                if let Some(prefix) = prefix {
+                    cmd.env("WINEPREFIX", prefix);
+                }
+                cmd
+            }
+            ServerRuntime::Proton {
+                proton_path,
+                compat_data_path,
+                steam_client_path,
+            } => {
+                let proton = PathBuf::from(proton_path).join("proton");
+                if !proton.exists() {
+                    return Err(format!("Proton script not found at {:?}", proton));
+                }
+                let compat_data = compat_data_path
+                    .as_ref()
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| PathBuf::from(install_path).join("proton-prefix"));
+                std::fs::create_dir_all(&compat_data)
+                    .map_err(|e| format!("Failed to create Proton prefix: {}", e))?;
+                let client_path = steam_client_path.clone().unwrap_or_else(|| {
+                    std::env::var("HOME")
+                        .map(|home| format!("{}/.steam/steam", home))
+                        .unwrap_or_default()
+                });
+
+                let mut cmd = Command::new(proton);
+                cmd.arg("run")
+                    .arg(server_path)
+                    .env("STEAM_COMPAT_DATA_PATH", compat_data)
+                    .env("STEAM_COMPAT_CLIENT_INSTALL_PATH", client_path);
+                cmd
+            }
+        };
+        #[cfg(target_os = "windows")]
+        if raw_args && self.takes_raw_args() {
+            for arg in args {
+                cmd.raw_arg(arg);
+            }
+        } else {
+            cmd.args(args);
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let _ = raw_args;
+            cmd.args(args);
+        }
+
+        // Run in its own process group so a forced stop also takes down wine/proton children
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        Ok(cmd)
+    }
+}
+
+/// Finds the server process started, directly or further down, by `launcher_pid`.
+/// Linux truncates process names to 15 characters, so it shows up as "ArkAscendedServ".
+pub fn find_server_process(launcher_pid: u32) -> Option<u32> {
+    let launcher = Pid::from_u32(launcher_pid);
+    let mut sys = System::new();
+    sys.refresh_processes();
+    sys.processes()
+        .iter()
+        .filter(|(_, p)| p.name().to_lowercase().starts_with("arkascendedserv"))
+        .find(|(_, p)| {
+            let mut parent = p.parent();
+            while let Some(pid) = parent {
+                if pid == launcher {
+                    return true;
+                }
+                parent = sys.process(pid).and_then(|p| p.parent());
+            }
+            false
+        })
+        .map(|(pid, _)| pid.as_u32())
+}
@@ -0,0 +1,546 @@
+// src-tauri/src/players.rs
+//
+// Player registry. Joins and leaves from `ShooterGame.log` are written to an append-only
+// journal (`players.jsonl` in the app data dir) and replayed into memory at startup, so
+// playtime, first/last seen and the names used by each `UniqueNetId` survive restarts.
+// Sessions are kept per profile and tagged with the profile's cluster id, which lets
+// queries cover one server or the whole cluster.
+//
+// A session still open when its server stops or crashes is closed by the watchdog. One
+// left open by a manager that exited without seeing the stop is closed once we know the
+// server is gone, at the last time the profile showed activity, since that is all we
+// know about when the player really left.
+
+use chrono::{DateTime, FixedOffset, Local};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const JOURNAL_FILE: &str = "players.jsonl";
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 1000;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionEnd {
+    /// The log showed the player leaving.
+    Left,
+    /// The server stopped (requested or clean exit) while the player was online.
+    ServerStopped,
+    /// The server crashed or was killed while the player was online.
+    ServerCrashed,
+    /// Closed after the fact; the manager missed the leave or the stop.
+    Reconciled,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum JournalEntry {
+    Join {
+        profile_id: String,
+        cluster_id: Option<String>,
+        player_id: String,
+        name: String,
+        at: String,
+    },
+    Leave {
+        profile_id: String,
+        player_id: String,
+        at: String,
+        reason: SessionEnd,
+    },
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerAlias {
+    pub name: String,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+#[derive(serde::Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilePlaytime {
+    pub cluster_id: Option<String>,
+    pub sessions: u32,
+    /// Closed sessions only; see `PlayerSummary::playtime_secs` for the live total.
+    pub playtime_secs: u64,
+    pub last_seen: String,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerRecord {
+    pub player_id: String,
+    /// The most recent name.
+    pub name: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    /// Every name the id has joined with, oldest first.
+    pub aliases: Vec<PlayerAlias>,
+    pub profiles: BTreeMap<String, ProfilePlaytime>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSession {
+    pub profile_id: String,
+    pub cluster_id: Option<String>,
+    pub player_id: String,
+    pub name: String,
+    pub joined_at: String,
+    /// `None` while the player is online.
+    pub left_at: Option<String>,
+    pub duration_secs: u64,
+    pub end: Option<SessionEnd>,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSummary {
+    pub player_id: String,
+    pub name: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub sessions: u32,
+    /// Playtime within the query's scope, including sessions still open.
+    pub playtime_secs: u64,
+    pub online: bool,
+}
+
+/// Limits a query to one profile or one cluster; both empty means everything.
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerScope {
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default)]
+    pub cluster_id: Option<String>,
+}
+
+impl PlayerScope {
+    fn includes(&self, profile_id: &str, cluster_id: Option<&str>) -> bool {
+        self.profile_id.as_deref().is_none_or(|p| p == profile_id)
+            && self
+                .cluster_id
+                .as_deref()
+                .is_none_or(|c| cluster_id == Some(c))
+    }
+}
+
+struct OpenSession {
+    cluster_id: Option<String>,
+    name: String,
+    joined_at: DateTime<FixedOffset>,
+}
+
+#[derive(Default)]
+struct Registry {
+    path: Option<PathBuf>,
+    players: HashMap<String, PlayerRecord>,
+    /// Keyed by (profile id, player id).
+    open: HashMap<(String, String), OpenSession>,
+    sessions: Vec<PlayerSession>,
+    /// Cluster of each profile, from its launch arguments.
+    clusters: HashMap<String, Option<String>>,
+    /// Time of the latest join or leave per profile.
+    last_activity: HashMap<String, DateTime<FixedOffset>>,
+}
+
+#[derive(Default)]
+pub struct PlayerRegistry {
+    inner: Mutex<Registry>,
+}
+
+fn now() -> DateTime<FixedOffset> {
+    Local::now().fixed_offset()
+}
+
+fn parse(at: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(at).ok()
+}
+
+fn elapsed_secs(from: DateTime<FixedOffset>, to: DateTime<FixedOffset>) -> u64 {
+    (to - from).num_seconds().max(0) as u64
+}
+
+/// Reads `-ClusterID=` from launch arguments (flag or `?ClusterID=` URL form).
+pub fn cluster_id(args: &[String]) -> Option<String> {
+    args.iter()
+        .flat_map(|arg| arg.trim_matches('"').split(['?', ' ']))
+        .filter_map(|option| option.trim_start_matches('-').split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case("ClusterID"))
+        .map(|(_, value)| value.trim_matches('"').trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+impl Registry {
+    fn apply(&mut self, entry: &JournalEntry) {
+        match entry {
+            JournalEntry::Join {
+                profile_id,
+                cluster_id,
+                player_id,
+                name,
+                at,
+            } => {
+                let Some(time) = parse(at) else { return };
+                self.last_activity.insert(profile_id.clone(), time);
+                let key = (profile_id.clone(), player_id.clone());
+                if self.open.contains_key(&key) {
+                    return;
+                }
+                let player =
+                    self.players
+                        .entry(player_id.clone())
+                        .or_insert_with(|| PlayerRecord {
+                            player_id: player_id.clone(),
+                            name: name.clone(),
+                            first_seen: at.clone(),
+                            last_seen: at.clone(),
+                            aliases: vec![],
+                            profiles: BTreeMap::new(),
+                        });
+                player.name = name.clone();
+                player.last_seen = at.clone();
+                match player.aliases.iter_mut().find(|a| a.name == *name) {
+                    Some(alias) => alias.last_seen = at.clone(),
+                    None => player.aliases.push(PlayerAlias {
+                        name: name.clone(),
+                        first_seen: at.clone(),
+                        last_seen: at.clone(),
+                    }),
+                }
+                let stats = player.profiles.entry(profile_id.clone()).or_default();
+                stats.cluster_id = cluster_id.clone();
+                stats.sessions += 1;
+                stats.last_seen = at.clone();
+                self.open.insert(
+                    key,
+                    OpenSession {
+                        cluster_id: cluster_id.clone(),
+                        name: name.clone(),
+                        joined_at: time,
+                    },
+                );
+            }
+            JournalEntry::Leave {
+                profile_id,
+                player_id,
+                at,
+                reason,
+            } => {
+                let Some(time) = parse(at) else { return };
+                self.last_activity.insert(profile_id.clone(), time);
+                let Some(session) = self.open.remove(&(profile_id.clone(), player_id.clone()))
+                else {
+                    return;
+                };
+                let duration_secs = elapsed_secs(session.joined_at, time);
+                if let Some(player) = self.players.get_mut(player_id) {
+                    player.last_seen = at.clone();
+                    if let Some(alias) = player.aliases.iter_mut().find(|a| a.name == session.name)
+                    {
+                        alias.last_seen = at.clone();
+                    }
+                    let stats = player.profiles.entry(profile_id.clone()).or_default();
+                    stats.playtime_secs += duration_secs;
+                    stats.last_seen = at.clone();
+                }
+                self.sessions.push(PlayerSession {
+                    profile_id: profile_id.clone(),
+                    cluster_id: session.cluster_id,
+                    player_id: player_id.clone(),
+                    name: session.name,
+                    joined_at: session.joined_at.to_rfc3339(),
+                    left_at: Some(at.clone()),
+                    duration_secs,
+                    end: Some(*reason),
+                });
+            }
+        }
+    }
+
+    fn record(&mut self, entry: JournalEntry) {
+        self.apply(&entry);
+        let Some(path) = &self.path else { return };
+        let result = serde_json::to_string(&entry)
+            .map_err(|e| e.to_string())
+            .and_then(|mut line| {
+                line.push('\n');
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(line.as_bytes()))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            println!("Failed to write player journal: {}", e);
+        }
+    }
+
+    /// Closes every open session of `profile_id` at `at` (now when `None`).
+    fn close_profile(
+        &mut self,
+        profile_id: &str,
+        at: Option<DateTime<FixedOffset>>,
+        reason: SessionEnd,
+    ) {
+        let players: Vec<(String, DateTime<FixedOffset>)> = self
+            .open
+            .iter()
+            .filter(|((profile, _), _)| profile == profile_id)
+            .map(|((_, player), session)| (player.clone(), session.joined_at))
+            .collect();
+        for (player_id, joined_at) in players {
+            let at = at.unwrap_or_else(now).max(joined_at);
+            self.record(JournalEntry::Leave {
+                profile_id: profile_id.to_string(),
+                player_id,
+                at: at.to_rfc3339(),
+                reason,
+            });
+        }
+    }
+
+    fn summary(&self, player: &PlayerRecord, scope: &PlayerScope) -> Option<PlayerSummary> {
+        let now = now();
+        let mut sessions = 0;
+        let mut playtime_secs = 0;
+        let mut online = false;
+        for (profile_id, stats) in &player.profiles {
+            if !scope.includes(profile_id, stats.cluster_id.as_deref()) {
+                continue;
+            }
+            sessions += stats.sessions;
+            playtime_secs += stats.playtime_secs;
+            if let Some(session) = self
+                .open
+                .get(&(profile_id.clone(), player.player_id.clone()))
+            {
+                online = true;
+                playtime_secs += elapsed_secs(session.joined_at, now);
+            }
+        }
+        (sessions > 0).then(|| PlayerSummary {
+            player_id: player.player_id.clone(),
+            name: player.name.clone(),
+            first_seen: player.first_seen.clone(),
+            last_seen: player.last_seen.clone(),
+            sessions,
+            playtime_secs,
+            online,
+        })
+    }
+}
+
+fn clamp_limit(limit: Option<usize>) -> usize {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+impl PlayerRegistry {
+    /// Replays the journal. Called once at startup, before servers are adopted.
+    pub fn load(&self, app: &AppHandle) {
+        let dir = match app.path().app_data_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                println!("Could not resolve app data dir: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = fs::create_dir_all(&dir) {
+            println!("Failed to create app data dir: {}", e);
+        }
+        let path = dir.join(JOURNAL_FILE);
+        let mut registry = self.inner.lock().unwrap();
+        if let Ok(file) = File::open(&path) {
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                // A torn last line from a crash is skipped, everything before it is kept
+                if let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) {
+                    registry.apply(&entry);
+                }
+            }
+        }
+        registry.path = Some(path);
+        println!(
+            "Loaded {} players ({} open sessions)",
+            registry.players.len(),
+            registry.open.len()
+        );
+    }
+
+    /// A server was started or adopted. A fresh start closes sessions left over from a
+    /// run the manager did not see end; an adopted server keeps them open.
+    pub fn server_started(&self, profile_id: &str, cluster_id: Option<String>, adopted: bool) {
+        let mut registry = self.inner.lock().unwrap();
+        registry.clusters.insert(profile_id.to_string(), cluster_id);
+        if !adopted {
+            let last = registry.last_activity.get(profile_id).copied();
+            registry.close_profile(profile_id, last, SessionEnd::Reconciled);
+        }
+    }
+
+    pub fn server_stopped(&self, profile_id: &str, crashed: bool) {
+        let reason = if crashed {
+            SessionEnd::ServerCrashed
+        } else {
+            SessionEnd::ServerStopped
+        };
+        self.inner
+            .lock()
+            .unwrap()
+            .close_profile(profile_id, None, reason);
+    }
+
+    /// Closes sessions of profiles that are not running, e.g. after the manager was
+    /// closed while a server stopped.
+    pub fn reconcile(&self, running: &[String]) {
+        let mut registry = self.inner.lock().unwrap();
+        let mut stale: Vec<String> = registry
+            .open
+            .keys()
+            .map(|(profile, _)| profile.clone())
+            .filter(|profile| !running.contains(profile))
+            .collect();
+        stale.sort();
+        stale.dedup();
+        for profile_id in stale {
+            let last = registry.last_activity.get(&profile_id).copied();
+            registry.close_profile(&profile_id, last, SessionEnd::Reconciled);
+        }
+    }
+
+    pub fn player_joined(&self, profile_id: &str, player_id: &str, name: &str) {
+        if player_id.is_empty() {
+            return;
+        }
+        let mut registry = self.inner.lock().unwrap();
+        let cluster_id = registry.clusters.get(profile_id).cloned().flatten();
+        registry.record(JournalEntry::Join {
+            profile_id: profile_id.to_string(),
+            cluster_id,
+            player_id: player_id.to_string(),
+            name: name.trim().to_string(),
+            at: now().to_rfc3339(),
+        });
+    }
+
+    pub fn player_left(&self, profile_id: &str, player_id: &str) {
+        let mut registry = self.inner.lock().unwrap();
+        if !registry
+            .open
+            .contains_key(&(profile_id.to_string(), player_id.to_string()))
+        {
+            return;
+        }
+        registry.record(JournalEntry::Leave {
+            profile_id: profile_id.to_string(),
+            player_id: player_id.to_string(),
+            at: now().to_rfc3339(),
+            reason: SessionEnd::Left,
+        });
+    }
+
+    /// Open sessions in `scope`, longest online first.
+    pub fn online(&self, scope: &PlayerScope) -> Vec<PlayerSession> {
+        let registry = self.inner.lock().unwrap();
+        let now = now();
+        let mut online: Vec<PlayerSession> = registry
+            .open
+            .iter()
+            .filter(|((profile, _), session)| {
+                scope.includes(profile, session.cluster_id.as_deref())
+            })
+            .map(|((profile_id, player_id), session)| PlayerSession {
+                profile_id: profile_id.clone(),
+                cluster_id: session.cluster_id.clone(),
+                player_id: player_id.clone(),
+                name: session.name.clone(),
+                joined_at: session.joined_at.to_rfc3339(),
+                left_at: None,
+                duration_secs: elapsed_secs(session.joined_at, now),
+                end: None,
+            })
+            .collect();
+        online.sort_by_key(|s| std::cmp::Reverse(s.duration_secs));
+        online
+    }
+
+    pub fn top_playtime(&self, scope: &PlayerScope, limit: Option<usize>) -> Vec<PlayerSummary> {
+        let registry = self.inner.lock().unwrap();
+        let mut players: Vec<PlayerSummary> = registry
+            .players
+            .values()
+            .filter_map(|player| registry.summary(player, scope))
+            .collect();
+        players.sort_by_key(|p| std::cmp::Reverse(p.playtime_secs));
+        players.truncate(clamp_limit(limit));
+        players
+    }
+
+    /// Players whose id matches exactly or whose current or former name contains `text`
+    /// (case-insensitive), most recently seen first.
+    pub fn find(
+        &self,
+        text: &str,
+        scope: &PlayerScope,
+        limit: Option<usize>,
+    ) -> Vec<PlayerSummary> {
+        let needle = text.trim().to_lowercase();
+        let registry = self.inner.lock().unwrap();
+        let mut players: Vec<PlayerSummary> = registry
+            .players
+            .values()
+            .filter(|player| {
+                needle.is_empty()
+                    || player.player_id.eq_ignore_ascii_case(&needle)
+                    || player
+                        .aliases
+                        .iter()
+                        .any(|alias| alias.name.to_lowercase().contains(&needle))
+            })
+            .filter_map(|player| registry.summary(player, scope))
+            .collect();
+        players.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        players.truncate(clamp_limit(limit));
+        players
+    }
+
+    /// Full record of one player, including alias history and per-profile playtime.
+    pub fn player(&self, player_id: &str) -> Option<PlayerRecord> {
+        self.inner.lock().unwrap().players.get(player_id).cloned()
+    }
+
+    /// A player's sessions in `scope`, newest first, open ones included.
+    pub fn sessions(
+        &self,
+        player_id: &str,
+        scope: &PlayerScope,
+        limit: Option<usize>,
+    ) -> Vec<PlayerSession> {
+        let mut sessions: Vec<PlayerSession> = self
+            .online(scope)
+            .into_iter()
+            .filter(|s| s.player_id == player_id)
+            .collect();
+        let registry = self.inner.lock().unwrap();
+        sessions.extend(
+            registry
+                .sessions
+                .iter()
+                .rev()
+                .filter(|s| {
+                    s.player_id == player_id
+                        && scope.includes(&s.profile_id, s.cluster_id.as_deref())
+                })
+                .cloned(),
+        );
+        sessions.truncate(clamp_limit(limit));
+        sessions
+    }
+}
@@ -0,0 +1,327 @@
+// src-tauri/src/ports.rs
+//
+// Pre-flight port check. Two profiles on the same game, query or RCON port, or a port
+// already taken by something else on the host, make the server fail to bind with
+// nothing useful in the log. Before a start we compare the ports against running
+// servers and the other saved profiles, then try binding each one on the interface the
+// server will use.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, UdpSocket};
+
+pub const DEFAULT_GAME_PORT: u16 = 7777;
+pub const DEFAULT_QUERY_PORT: u16 = 27015;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PortKind {
+    Game,
+    Query,
+    Rcon,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Protocol {
+    Udp,
+    Tcp,
+}
+
+impl Protocol {
+    fn label(self) -> &'static str {
+        match self {
+            Protocol::Udp => "UDP",
+            Protocol::Tcp => "TCP",
+        }
+    }
+}
+
+impl PortKind {
+    fn protocol(self) -> Protocol {
+        match self {
+            PortKind::Game | PortKind::Query => Protocol::Udp,
+            PortKind::Rcon => Protocol::Tcp,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PortKind::Game => "game",
+            PortKind::Query => "query",
+            PortKind::Rcon => "RCON",
+        }
+    }
+}
+
+/// The ports one server binds. RCON is `None` when disabled.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerPorts {
+    #[serde(default)]
+    pub game_port: Option<u16>,
+    #[serde(default)]
+    pub query_port: Option<u16>,
+    #[serde(default)]
+    pub rcon_port: Option<u16>,
+    /// `-MultiHome` address; all interfaces when absent.
+    #[serde(default)]
+    pub bind_ip: Option<String>,
+}
+
+/// A saved profile's ports, sent by the UI (profiles are stored on the frontend).
+#[derive(serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilePorts {
+    pub profile_id: String,
+    #[serde(default)]
+    pub profile_name: Option<String>,
+    pub ports: ServerPorts,
+}
+
+/// A running server's ports, taken from its launch parameters.
+pub struct RunningPorts {
+    pub profile_id: String,
+    pub pid: u32,
+    pub ports: ServerPorts,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PortHolder {
+    /// A server started by the manager is bound to the port.
+    RunningServer { profile_id: String, pid: u32 },
+    /// Another saved profile is configured with the port; they cannot run together.
+    Profile {
+        profile_id: String,
+        profile_name: Option<String>,
+    },
+    /// The same profile uses the port for two things.
+    SameProfile { other_kind: PortKind },
+    /// Binding failed, so some other program on the host holds the port.
+    Host { error: String },
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConflict {
+    pub port: u16,
+    pub kind: PortKind,
+    pub protocol: Protocol,
+    pub holder: PortHolder,
+    /// Conflicts with stopped profiles are warnings; everything else blocks the start.
+    pub blocking: bool,
+    pub message: String,
+}
+
+#[derive(serde::Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PortCheckReport {
+    pub conflicts: Vec<PortConflict>,
+    pub blocking: usize,
+}
+
+impl PortCheckReport {
+    fn push(&mut self, conflict: PortConflict) {
+        if conflict.blocking {
+            self.blocking += 1;
+        }
+        self.conflicts.push(conflict);
+    }
+
+    /// One line per blocking conflict, for refusing a start.
+    pub fn blocking_summary(&self) -> String {
+        self.conflicts
+            .iter()
+            .filter(|c| c.blocking)
+            .map(|c| c.message.clone())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl ServerPorts {
+    /// Reads the ports from launch arguments (`-Port=`, `-QueryPort=`, `-MultiHome=` or
+    /// their `?Key=` map URL forms), falling back to the ASA defaults.
+    pub fn from_launch_args(args: &[String], rcon_port: Option<u16>) -> Self {
+        let mut ports = ServerPorts {
+            game_port: Some(DEFAULT_GAME_PORT),
+            query_port: Some(DEFAULT_QUERY_PORT),
+            rcon_port,
+            bind_ip: None,
+        };
+        for arg in args {
+            let arg = arg.trim_matches('"');
+            let options = arg.strip_prefix('-').map_or_else(
+                || arg.split('?').skip(1).collect::<Vec<_>>(),
+                |flag| vec![flag],
+            );
+            for option in options {
+                let Some((key, value)) = option.split_once('=') else {
+                    continue;
+                };
+                let value = value.trim_matches('"').trim();
+                match key.to_lowercase().as_str() {
+                    "port" => ports.game_port = value.parse().ok().or(ports.game_port),
+                    "queryport" => ports.query_port = value.parse().ok().or(ports.query_port),
+                    "rconport" if ports.rcon_port.is_some() => {
+                        ports.rcon_port = value.parse().ok().or(ports.rcon_port)
+                    }
+                    "multihome" if !value.is_empty() => ports.bind_ip = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+        ports
+    }
+
+    fn entries(&self) -> Vec<(PortKind, u16)> {
+        [
+            (PortKind::Game, self.game_port),
+            (PortKind::Query, self.query_port),
+            (PortKind::Rcon, self.rcon_port),
+        ]
+        .into_iter()
+        .filter_map(|(kind, port)| port.map(|p| (kind, p)))
+        .collect()
+    }
+
+    /// Which of these ports collide with `port` used as `kind` (same number and protocol).
+    fn collisions(&self, kind: PortKind, port: u16) -> Vec<PortKind> {
+        self.entries()
+            .into_iter()
+            .filter(|(k, p)| *p == port && k.protocol() == kind.protocol())
+            .map(|(k, _)| k)
+            .collect()
+    }
+
+    fn bind_addr(&self) -> Result<IpAddr, String> {
+        match self.bind_ip.as_deref() {
+            Some(ip) => ip
+                .parse()
+                .map_err(|_| format!("MultiHome address '{}' is not an IP address", ip)),
+            None => Ok(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        }
+    }
+}
+
+fn try_bind(ip: IpAddr, port: u16, protocol: Protocol) -> Result<(), String> {
+    let addr = SocketAddr::new(ip, port);
+    // Both sockets are closed again as soon as they drop
+    match protocol {
+        Protocol::Udp => UdpSocket::bind(addr).map(|_| ()),
+        Protocol::Tcp => TcpListener::bind(addr).map(|_| ()),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Checks `ports` for `profile_id` against running servers, other saved profiles and
+/// the host. Running servers and saved profiles with the same id as `profile_id` are
+/// ignored, so a profile never conflicts with itself.
+pub fn check(
+    profile_id: &str,
+    ports: &ServerPorts,
+    running: &[RunningPorts],
+    profiles: &[ProfilePorts],
+) -> Result<PortCheckReport, String> {
+    let mut report = PortCheckReport::default();
+    let bind_ip = ports.bind_addr()?;
+    let entries = ports.entries();
+
+    for (i, &(kind, port)) in entries.iter().enumerate() {
+        let protocol = kind.protocol();
+        let conflict = |holder: PortHolder, blocking: bool, message: String| PortConflict {
+            port,
+            kind,
+            protocol,
+            holder,
+            blocking,
+            message,
+        };
+
+        if let Some(&(other_kind, _)) = entries[..i]
+            .iter()
+            .find(|(k, p)| *p == port && k.protocol() == protocol)
+        {
+            report.push(conflict(
+                PortHolder::SameProfile { other_kind },
+                true,
+                format!(
+                    "The {} port {} is also used as this profile's {} port.",
+                    kind.label(),
+                    port,
+                    other_kind.label()
+                ),
+            ));
+            continue;
+        }
+
+        let mut held_by_server = false;
+        for server in running.iter().filter(|s| s.profile_id != profile_id) {
+            for other_kind in server.ports.collisions(kind, port) {
+                held_by_server = true;
+                report.push(conflict(
+                    PortHolder::RunningServer {
+                        profile_id: server.profile_id.clone(),
+                        pid: server.pid,
+                    },
+                    true,
+                    format!(
+                        "The {} port {} ({}) is in use as the {} port of running profile {} (PID {}).",
+                        kind.label(),
+                        port,
+                        protocol.label(),
+                        other_kind.label(),
+                        server.profile_id,
+                        server.pid
+                    ),
+                ));
+            }
+        }
+
+        for profile in profiles.iter().filter(|p| {
+            p.profile_id != profile_id && !running.iter().any(|s| s.profile_id == p.profile_id)
+        }) {
+            for other_kind in profile.ports.collisions(kind, port) {
+                let name = profile
+                    .profile_name
+                    .as_deref()
+                    .unwrap_or(&profile.profile_id);
+                report.push(conflict(
+                    PortHolder::Profile {
+                        profile_id: profile.profile_id.clone(),
+                        profile_name: profile.profile_name.clone(),
+                    },
+                    false,
+                    format!(
+                        "The {} port {} is also the {} port of profile '{}'; they cannot run at the same time.",
+                        kind.label(),
+                        port,
+                        other_kind.label(),
+                        name
+                    ),
+                ));
+            }
+        }
+
+        // A port held by one of our servers would fail the bind too; it is already reported
+        if held_by_server {
+            continue;
+        }
+        if let Err(error) = try_bind(bind_ip, port, protocol) {
+            report.push(conflict(
+                PortHolder::Host {
+                    error: error.clone(),
+                },
+                true,
+                format!(
+                    "The {} port {} ({}) on {} cannot be bound; another program is using it ({}).",
+                    kind.label(),
+                    port,
+                    protocol.label(),
+                    bind_ip,
+                    error
+                ),
+            ));
+        }
+    }
+    Ok(report)
+}
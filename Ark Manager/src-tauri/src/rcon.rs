@@ -0,0 +1,442 @@
+// src-tauri/src/rcon.rs
+//
+// Long-lived RCON session per running profile. Commands are serialized through a
+// single connection which is re-opened on demand, with exponential backoff after
+// failures so a polling console doesn't hammer a server that is still booting.
+// Health is kept behind its own short-lived lock so the status panel never waits
+// on a command that is still in flight.
+
+use chrono::Local;
+use rercon::{Connection, Settings};
+use std::io::ErrorKind;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(15);
+const BACKOFF_BASE_SECS: u64 = 1;
+const BACKOFF_MAX_SECS: u64 = 60;
+/// ARK silently drops idle RCON sockets, and a command written to one may or may not
+/// have run, so connections idle for this long are re-opened before sending.
+const IDLE_RECONNECT: Duration = Duration::from_secs(30);
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RconHealthState {
+    Idle,
+    Connected,
+    Disconnected,
+    BackingOff,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RconHealth {
+    pub state: RconHealthState,
+    pub address: String,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<String>,
+    pub retry_in_seconds: Option<u64>,
+}
+
+struct ConnectionState {
+    conn: Option<Connection>,
+    password: String,
+    last_used: Option<Instant>,
+}
+
+#[derive(Default)]
+struct HealthState {
+    connected: bool,
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_success_at: Option<String>,
+    backoff_until: Option<Instant>,
+}
+
+/// How far a failed command got, which decides whether it is safe to send again.
+#[derive(Debug, PartialEq)]
+enum CommandError {
+    /// The server never saw the command: connecting failed, or the socket was
+    /// already closed when the command was written.
+    NotSent(String),
+    /// The command may have run (timeout, broken or missing reply), so sending it
+    /// again could run it twice.
+    MaybeSent(String),
+}
+
+impl CommandError {
+    fn from_exec(error: rercon::Error) -> Self {
+        let message = format!("RCON command failed: {}", error);
+        match &error {
+            // Only writes report these, so the command itself never left
+            rercon::Error::IO(e)
+                if matches!(e.kind(), ErrorKind::BrokenPipe | ErrorKind::NotConnected) =>
+            {
+                CommandError::NotSent(message)
+            }
+            _ => CommandError::MaybeSent(message),
+        }
+    }
+
+    fn into_message(self) -> String {
+        match self {
+            CommandError::NotSent(message) | CommandError::MaybeSent(message) => message,
+        }
+    }
+}
+
+pub struct RconSession {
+    address: String,
+    connection: Mutex<ConnectionState>,
+    health: std::sync::Mutex<HealthState>,
+}
+
+impl RconSession {
+    pub fn new(ip: &str, port: u16, password: Option<String>) -> Self {
+        RconSession {
+            address: format!("{}:{}", ip, port),
+            connection: Mutex::new(ConnectionState {
+                conn: None,
+                password: password.unwrap_or_default(),
+                last_used: None,
+            }),
+            health: std::sync::Mutex::new(HealthState::default()),
+        }
+    }
+
+    /// Runs a command on the shared connection, opening it first if needed.
+    /// A command is only retried (once, on a fresh connection) when a reused
+    /// connection failed before the command reached the server; after a timeout or
+    /// a broken reply it may already have run, so the error is returned instead.
+    pub async fn exec(&self, command: &str) -> Result<String, String> {
+        let mut connection = self.connection.lock().await;
+
+        if let Some(error) = self.backoff_error() {
+            return Err(error);
+        }
+
+        if connection
+            .last_used
+            .is_some_and(|used| used.elapsed() >= IDLE_RECONNECT)
+        {
+            connection.conn = None;
+        }
+
+        let reused = connection.conn.is_some();
+        let mut result = self.exec_once(&mut connection, command).await;
+        if reused && matches!(result, Err(CommandError::NotSent(_))) {
+            result = self.exec_once(&mut connection, command).await;
+        }
+
+        let connected = connection.conn.is_some();
+        match result {
+            Ok(response) => {
+                self.record_success(connected);
+                Ok(response)
+            }
+            Err(e) => {
+                let message = e.into_message();
+                self.record_failure(connected, &message);
+                Err(message)
+            }
+        }
+    }
+
+    async fn exec_once(
+        &self,
+        state: &mut ConnectionState,
+        command: &str,
+    ) -> Result<String, CommandError> {
+        if state.conn.is_none() {
+            let conn = tokio::time::timeout(
+                CONNECT_TIMEOUT,
+                Connection::open(&self.address, &state.password, Settings::default()),
+            )
+            .await
+            .map_err(|_| CommandError::NotSent("RCON connection timed out".to_string()))?
+            .map_err(|e| CommandError::NotSent(format!("RCON connection failed: {}", e)))?;
+            state.conn = Some(conn);
+        }
+
+        let conn = state.conn.as_mut().expect("connection was just opened");
+        let result = match tokio::time::timeout(COMMAND_TIMEOUT, conn.exec(command)).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(e)) => Err(CommandError::from_exec(e)),
+            Err(_) => Err(CommandError::MaybeSent(
+                "RCON command timed out".to_string(),
+            )),
+        };
+
+        state.last_used = Some(Instant::now());
+        if result.is_err() {
+            // Never reuse a connection that failed mid-command
+            state.conn = None;
+        }
+        result
+    }
+
+    /// Sets the password for the next connection, e.g. once the saved profile of a
+    /// re-adopted server supplies it, and retries without waiting out the backoff.
+    pub async fn set_password(&self, password: Option<String>) {
+        self.connection.lock().await.password = password.unwrap_or_default();
+        self.health_state().backoff_until = None;
+    }
+
+    fn health_state(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn backoff_error(&self) -> Option<String> {
+        let health = self.health_state();
+        let now = Instant::now();
+        let until = health.backoff_until.filter(|until| *until > now)?;
+        Some(format!(
+            "RCON is unavailable (retrying in {}s): {}",
+            (until - now).as_secs() + 1,
+            health.last_error.as_deref().unwrap_or("unknown error")
+        ))
+    }
+
+    fn record_success(&self, connected: bool) {
+        let mut health = self.health_state();
+        health.connected = connected;
+        health.consecutive_failures = 0;
+        health.last_error = None;
+        health.backoff_until = None;
+        health.last_success_at = Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+    }
+
+    fn record_failure(&self, connected: bool, error: &str) {
+        let mut health = self.health_state();
+        health.connected = connected;
+        health.consecutive_failures += 1;
+        health.last_error = Some(error.to_string());
+        let exponent = health.consecutive_failures.saturating_sub(1).min(6);
+        let delay = (BACKOFF_BASE_SECS << exponent).min(BACKOFF_MAX_SECS);
+        health.backoff_until = Some(Instant::now() + Duration::from_secs(delay));
+    }
+
+    pub fn health(&self) -> RconHealth {
+        let health = self.health_state();
+        let now = Instant::now();
+        let retry_in_seconds = health
+            .backoff_until
+            .filter(|until| *until > now)
+            .map(|until| (until - now).as_secs() + 1);
+
+        let health_state = if health.connected {
+            RconHealthState::Connected
+        } else if retry_in_seconds.is_some() {
+            RconHealthState::BackingOff
+        } else if health.consecutive_failures > 0 {
+            RconHealthState::Disconnected
+        } else {
+            RconHealthState::Idle
+        };
+
+        RconHealth {
+            state: health_state,
+            address: self.address.clone(),
+            consecutive_failures: health.consecutive_failures,
+            last_error: health.last_error.clone(),
+            last_success_at: health.last_success_at.clone(),
+            retry_in_seconds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const TYPE_RESPONSE: i32 = 0;
+    const TYPE_AUTH_RESPONSE: i32 = 2;
+
+    /// Commands the fake server saw and how many connections it accepted.
+    #[derive(Default)]
+    struct Seen {
+        commands: Vec<String>,
+        connections: usize,
+    }
+
+    async fn read_packet(stream: &mut TcpStream) -> Option<(i32, String)> {
+        let len = stream.read_i32_le().await.ok()? as usize;
+        let mut buf = vec![0; len];
+        stream.read_exact(&mut buf).await.ok()?;
+        let id = i32::from_le_bytes(buf[0..4].try_into().unwrap());
+        let body = String::from_utf8_lossy(&buf[8..len - 2]).to_string();
+        Some((id, body))
+    }
+
+    async fn write_packet(stream: &mut TcpStream, id: i32, kind: i32, body: &str) {
+        let mut packet = (body.len() as i32 + 10).to_le_bytes().to_vec();
+        packet.extend_from_slice(&id.to_le_bytes());
+        packet.extend_from_slice(&kind.to_le_bytes());
+        packet.extend_from_slice(body.as_bytes());
+        packet.extend_from_slice(&[0, 0]);
+        stream.write_all(&packet).await.unwrap();
+    }
+
+    /// Fake ARK RCON server. Answers every command with "ok <command>", except
+    /// "drop" (closes the socket without replying) and "hang" (never replies).
+    async fn fake_server() -> (SocketAddr, Arc<std::sync::Mutex<Seen>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let seen = Arc::new(std::sync::Mutex::new(Seen::default()));
+        let server_seen = seen.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                server_seen.lock().unwrap().connections += 1;
+                let seen = server_seen.clone();
+                tokio::spawn(async move {
+                    let Some((auth_id, _)) = read_packet(&mut stream).await else {
+                        return;
+                    };
+                    write_packet(&mut stream, auth_id, TYPE_AUTH_RESPONSE, "").await;
+                    while let Some((id, body)) = read_packet(&mut stream).await {
+                        if !body.is_empty() {
+                            seen.lock().unwrap().commands.push(body.clone());
+                        }
+                        match body.as_str() {
+                            "drop" => return,
+                            "hang" => {
+                                std::future::pending::<()>().await;
+                            }
+                            "" => write_packet(&mut stream, id, TYPE_RESPONSE, "").await,
+                            command => {
+                                let reply = format!("ok {}", command);
+                                write_packet(&mut stream, id, TYPE_RESPONSE, &reply).await
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        (addr, seen)
+    }
+
+    fn session_for(addr: SocketAddr) -> RconSession {
+        RconSession::new(
+            &addr.ip().to_string(),
+            addr.port(),
+            Some("secret".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn commands_share_one_connection() {
+        let (addr, seen) = fake_server().await;
+        let session = session_for(addr);
+        assert_eq!(session.health().state, RconHealthState::Idle);
+
+        assert_eq!(session.exec("ListPlayers").await.unwrap(), "ok ListPlayers");
+        assert_eq!(session.exec("SaveWorld").await.unwrap(), "ok SaveWorld");
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.connections, 1);
+        assert_eq!(seen.commands, ["ListPlayers", "SaveWorld"]);
+        let health = session.health();
+        assert_eq!(health.state, RconHealthState::Connected);
+        assert!(health.last_success_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn command_without_reply_is_not_resent() {
+        let (addr, seen) = fake_server().await;
+        let session = session_for(addr);
+        session.exec("ListPlayers").await.unwrap();
+
+        // The server got the command on a reused connection but never answered
+        assert!(session.exec("drop").await.is_err());
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.commands, ["ListPlayers", "drop"]);
+        assert_eq!(seen.connections, 1);
+        let health = session.health();
+        assert_eq!(health.state, RconHealthState::BackingOff);
+        assert_eq!(health.consecutive_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn idle_connection_is_reopened_before_sending() {
+        let (addr, seen) = fake_server().await;
+        let session = session_for(addr);
+        session.exec("ListPlayers").await.unwrap();
+
+        session.connection.lock().await.last_used = Instant::now().checked_sub(IDLE_RECONNECT);
+        session.exec("SaveWorld").await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.connections, 2);
+        assert_eq!(seen.commands, ["ListPlayers", "SaveWorld"]);
+    }
+
+    #[tokio::test]
+    async fn health_is_readable_while_a_command_is_in_flight() {
+        let (addr, seen) = fake_server().await;
+        let session = Arc::new(session_for(addr));
+        session.exec("ListPlayers").await.unwrap();
+
+        let running = session.clone();
+        let pending = tokio::spawn(async move { running.exec("hang").await });
+        while !seen.lock().unwrap().commands.iter().any(|c| c == "hang") {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(session.health().state, RconHealthState::Connected);
+        pending.abort();
+    }
+
+    #[tokio::test]
+    async fn refused_connection_backs_off() {
+        // Bind then drop a listener to get a port nothing listens on
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let session = session_for(addr);
+
+        let error = session.exec("ListPlayers").await.unwrap_err();
+        assert!(error.starts_with("RCON connection failed"), "{}", error);
+        let health = session.health();
+        assert_eq!(health.state, RconHealthState::BackingOff);
+        assert_eq!(health.retry_in_seconds, Some(1));
+
+        let error = session.exec("ListPlayers").await.unwrap_err();
+        assert!(error.starts_with("RCON is unavailable"), "{}", error);
+        assert_eq!(session.health().consecutive_failures, 1);
+
+        session.set_password(None).await;
+        assert_eq!(session.health().state, RconHealthState::Disconnected);
+    }
+
+    #[test]
+    fn only_write_side_errors_count_as_not_sent() {
+        let io = |kind| rercon::Error::IO(std::io::Error::from(kind));
+        assert!(matches!(
+            CommandError::from_exec(io(ErrorKind::BrokenPipe)),
+            CommandError::NotSent(_)
+        ));
+        assert!(matches!(
+            CommandError::from_exec(io(ErrorKind::NotConnected)),
+            CommandError::NotSent(_)
+        ));
+        // rercon reports a closed reader as ConnectionReset, after the command was written
+        assert!(matches!(
+            CommandError::from_exec(io(ErrorKind::ConnectionReset)),
+            CommandError::MaybeSent(_)
+        ));
+        assert!(matches!(
+            CommandError::from_exec(rercon::Error::UnexpectedPacket),
+            CommandError::MaybeSent(_)
+        ));
+    }
+}
@@ -0,0 +1,633 @@
+// src-tauri/src/rcon_audit.rs
+//
+// Every RCON command the manager sends goes through `RconAudit::exec`, which checks it
+// against the command policies and records what happened in `rcon_audit.jsonl` in the
+// app data dir: profile, time, caller, the response and whether it succeeded. The one
+// exception is the chat poller's `getchat`, which runs every few seconds; it is only
+// recorded when it fails or a policy stops it.
+//
+// Policies match on the command name and either block the command or require a
+// confirmation. A confirmation is a single-use token handed out in the error (and in a
+// `rcon-confirmation-required` event) that the console sends back with the retried
+// command. Callers with nobody to confirm, such as the shutdown sequence, treat a
+// confirmation policy as a block, so policies should list the sources they are meant for.
+//
+// Commands typed or triggered by a person (console, chat, access lists, scheduled tasks)
+// also go into the session log archive, but only once the policies let them through.
+// Passwords are masked everywhere a command is echoed (see `redact`).
+
+use crate::log_archive::LogSource;
+use crate::rcon::RconSession;
+use chrono::Local;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, Window};
+
+const POLICIES_FILE: &str = "rcon_policies.json";
+const AUDIT_FILE: &str = "rcon_audit.jsonl";
+/// The audit file is moved to `rcon_audit.1.jsonl` past this size.
+const AUDIT_MAX_BYTES: u64 = 8 * 1024 * 1024;
+const MAX_RESPONSE_CHARS: usize = 4000;
+const CONFIRMATION_TTL: Duration = Duration::from_secs(120);
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+const MAX_AUDIT_LIMIT: usize = 5000;
+
+static TOKEN_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Who sent a command.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CommandSource {
+    /// Typed into the console (`send_rcon_command`).
+    Console,
+    /// `send_chat`.
+    Chat,
+    /// The chat poller's `getchat`.
+    ChatPoll,
+    /// Ban and whitelist changes applied live.
+    AccessList,
+    /// Countdown broadcasts, `saveworld` and `DoExit` from `stop_server`.
+    Shutdown,
+    /// The connection test in `diagnose_rcon`.
+    Diagnostics,
+    /// RCON command and broadcast tasks from the task scheduler.
+    Scheduler,
+}
+
+impl CommandSource {
+    /// Sources with a person behind them who can answer a confirmation.
+    fn can_confirm(self) -> bool {
+        self == CommandSource::Console
+    }
+
+    /// Sources whose commands are shown in the session log archive.
+    fn is_archived(self) -> bool {
+        matches!(
+            self,
+            CommandSource::Console
+                | CommandSource::Chat
+                | CommandSource::AccessList
+                | CommandSource::Scheduler
+        )
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PolicyAction {
+    Block,
+    Confirm,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandPolicy {
+    pub id: String,
+    pub name: String,
+    /// Command names, case-insensitive. A trailing `*` matches any suffix (`Destroy*`).
+    pub commands: Vec<String>,
+    pub action: PolicyAction,
+    /// Callers the policy applies to; all of them when empty.
+    #[serde(default)]
+    pub sources: Vec<CommandSource>,
+    /// Only this profile's server; all profiles when `None`.
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditOutcome {
+    Executed,
+    Failed,
+    Blocked,
+    ConfirmationRequired,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RconAuditEntry {
+    pub at: String,
+    /// `None` for diagnostics against a server that is not a running profile.
+    pub profile_id: Option<String>,
+    pub source: CommandSource,
+    /// The command as sent, with passwords masked.
+    pub command: String,
+    pub outcome: AuditOutcome,
+    pub success: bool,
+    #[serde(default)]
+    pub response: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// The policy that blocked the command or asked for confirmation.
+    #[serde(default)]
+    pub policy_id: Option<String>,
+    /// Sent with a confirmation token.
+    #[serde(default)]
+    pub confirmed: bool,
+    pub duration_ms: u64,
+}
+
+#[derive(serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RconAuditQuery {
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default)]
+    pub source: Option<CommandSource>,
+    /// Only blocked, unconfirmed and failed commands.
+    #[serde(default)]
+    pub failures_only: bool,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+struct PendingConfirmation {
+    profile_id: String,
+    command: String,
+    issued: Instant,
+}
+
+#[derive(Default)]
+pub struct RconAudit {
+    policies: RwLock<Vec<CommandPolicy>>,
+    pending: Mutex<HashMap<String, PendingConfirmation>>,
+    audit_path: RwLock<Option<PathBuf>>,
+    // Serializes appends and rotation
+    write_lock: Mutex<()>,
+}
+
+/// Confirmation for world wipes, killing players and stopping the server from the console.
+fn default_policies() -> Vec<CommandPolicy> {
+    let policy = |id: &str, name: &str, commands: &[&str]| CommandPolicy {
+        id: id.to_string(),
+        name: name.to_string(),
+        commands: commands.iter().map(|c| c.to_string()).collect(),
+        action: PolicyAction::Confirm,
+        sources: vec![CommandSource::Console],
+        profile_id: None,
+        enabled: true,
+    };
+    vec![
+        policy(
+            "default-destroy",
+            "Destroy commands",
+            &[
+                "DestroyWildDinos",
+                "DestroyAll*",
+                "DestroyTribe*",
+                "DestroyStructures",
+            ],
+        ),
+        policy(
+            "default-players",
+            "Player punishment",
+            &["KillPlayer", "ClearPlayerInventory"],
+        ),
+        policy("default-exit", "Server exit", &["DoExit", "Exit", "Quit"]),
+    ]
+}
+
+/// `admincheat` and `cheat` run the rest of the line as a cheat command, so policies
+/// and redaction look past them.
+const CHEAT_PREFIXES: [&str; 2] = ["admincheat", "cheat"];
+
+/// `command` without its leading `admincheat`/`cheat` prefixes, however many there are.
+fn strip_cheat_prefix(command: &str) -> &str {
+    let mut rest = command.trim_start();
+    loop {
+        let (first, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        if !CHEAT_PREFIXES.iter().any(|p| p.eq_ignore_ascii_case(first)) {
+            return rest;
+        }
+        rest = tail.trim_start();
+    }
+}
+
+fn command_name(command: &str) -> &str {
+    strip_cheat_prefix(command)
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+}
+
+fn matches_name(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.trim();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name
+            .get(..prefix.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(prefix)),
+        None => name.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/// Masks the password of `EnableCheats <password>`, keeping any cheat prefix.
+pub fn redact(command: &str) -> String {
+    if command_name(command).eq_ignore_ascii_case("EnableCheats") {
+        let command = command.trim_start();
+        let prefix = &command[..command.len() - strip_cheat_prefix(command).len()];
+        return format!("{}EnableCheats ****", prefix);
+    }
+    command.to_string()
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_RESPONSE_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+fn new_token(profile_id: &str, command: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let mut hasher = Sha256::new();
+    hasher.update(nanos.to_le_bytes());
+    hasher.update(TOKEN_SEQ.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    hasher.update(profile_id.as_bytes());
+    hasher.update(command.as_bytes());
+    hasher.finalize()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn validate_policy(policy: &CommandPolicy) -> Result<(), String> {
+    if policy.id.trim().is_empty() {
+        return Err("Policy id is required".to_string());
+    }
+    if policy.commands.iter().all(|c| c.trim().is_empty()) {
+        return Err(format!("Policy '{}' lists no commands", policy.name));
+    }
+    Ok(())
+}
+
+impl RconAudit {
+    /// Loads the policies (the defaults when none were saved) and sets the audit file.
+    pub fn load(&self, app: &AppHandle) {
+        let Ok(dir) = app.path().app_data_dir() else {
+            println!("Could not resolve app data dir; RCON commands are not audited");
+            return;
+        };
+        let policies: Vec<CommandPolicy> = fs::read_to_string(dir.join(POLICIES_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(default_policies);
+        *self.policies.write().unwrap() = policies;
+        *self.audit_path.write().unwrap() = Some(dir.join(AUDIT_FILE));
+    }
+
+    pub fn policies(&self) -> Vec<CommandPolicy> {
+        self.policies.read().unwrap().clone()
+    }
+
+    /// Adds `policy`, or replaces the policy with the same id.
+    pub fn set_policy(&self, app: &AppHandle, policy: CommandPolicy) -> Result<(), String> {
+        validate_policy(&policy)?;
+        {
+            let mut policies = self.policies.write().unwrap();
+            match policies.iter_mut().find(|p| p.id == policy.id) {
+                Some(existing) => *existing = policy,
+                None => policies.push(policy),
+            }
+        }
+        self.save(app)
+    }
+
+    pub fn remove_policy(&self, app: &AppHandle, id: &str) -> Result<(), String> {
+        self.policies.write().unwrap().retain(|p| p.id != id);
+        self.save(app)
+    }
+
+    fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(POLICIES_FILE);
+        let content = serde_json::to_string_pretty(&self.policies()).map_err(|e| e.to_string())?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+
+    /// The strictest enabled policy for `command`; a block wins over a confirmation.
+    fn matching_policy(
+        &self,
+        profile_id: &str,
+        command: &str,
+        source: CommandSource,
+    ) -> Option<CommandPolicy> {
+        let name = command_name(command);
+        let policies = self.policies.read().unwrap();
+        let mut matching = policies.iter().filter(|p| {
+            p.enabled
+                && (p.sources.is_empty() || p.sources.contains(&source))
+                && p.profile_id.as_deref().is_none_or(|id| id == profile_id)
+                && p.commands.iter().any(|pattern| matches_name(pattern, name))
+        });
+        let first = matching.clone().find(|p| p.action == PolicyAction::Block);
+        first.or_else(|| matching.next()).cloned()
+    }
+
+    /// Consumes `token` if it was issued for this command on this profile.
+    fn redeem(&self, token: &str, profile_id: &str, command: &str) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, p| p.issued.elapsed() < CONFIRMATION_TTL);
+        match pending.get(token) {
+            Some(p) if p.profile_id == profile_id && p.command == command.trim() => {
+                pending.remove(token);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks `command` against the policies, runs it on `session` and records the result.
+    pub async fn exec(
+        &self,
+        window: &Window,
+        profile_id: &str,
+        session: Option<&RconSession>,
+        command: &str,
+        source: CommandSource,
+        confirm_token: Option<&str>,
+    ) -> Result<String, String> {
+        let mut entry = RconAuditEntry {
+            at: Local::now().to_rfc3339(),
+            profile_id: Some(profile_id.to_string()),
+            source,
+            command: redact(command),
+            outcome: AuditOutcome::Executed,
+            success: false,
+            response: None,
+            error: None,
+            policy_id: None,
+            confirmed: false,
+            duration_ms: 0,
+        };
+
+        if let Some(policy) = self.matching_policy(profile_id, command, source) {
+            entry.policy_id = Some(policy.id.clone());
+            let blocked = policy.action == PolicyAction::Block || !source.can_confirm();
+            if blocked {
+                let error = format!(
+                    "'{}' is blocked by the RCON policy '{}'.",
+                    command_name(command),
+                    policy.name
+                );
+                entry.outcome = AuditOutcome::Blocked;
+                entry.error = Some(error.clone());
+                self.record(window, entry);
+                return Err(error);
+            }
+            if confirm_token.is_some_and(|token| self.redeem(token, profile_id, command)) {
+                entry.confirmed = true;
+            } else {
+                let token = new_token(profile_id, command);
+                self.pending.lock().unwrap().insert(
+                    token.clone(),
+                    PendingConfirmation {
+                        profile_id: profile_id.to_string(),
+                        command: command.trim().to_string(),
+                        issued: Instant::now(),
+                    },
+                );
+                let _ = window.emit(
+                    "rcon-confirmation-required",
+                    serde_json::json!({
+                        "profile_id": profile_id,
+                        "command": entry.command,
+                        "policy_id": policy.id,
+                        "policy_name": policy.name,
+                        "token": token,
+                        "expires_in_seconds": CONFIRMATION_TTL.as_secs(),
+                    }),
+                );
+                let error = format!(
+                    "'{}' needs confirmation (RCON policy '{}').",
+                    command_name(command),
+                    policy.name
+                );
+                entry.outcome = AuditOutcome::ConfirmationRequired;
+                entry.error = Some(error.clone());
+                self.record(window, entry);
+                return Err(error);
+            }
+        }
+
+        if source.is_archived() {
+            let line = format!("> {}", entry.command);
+            crate::archive_line(window, profile_id, LogSource::Rcon, &line);
+        }
+        let started = Instant::now();
+        let result = match session {
+            Some(session) => session.exec(command).await,
+            None => Err("RCON is not enabled for this server profile.".to_string()),
+        };
+        entry.duration_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(response) => {
+                entry.success = true;
+                entry.response = Some(truncate(response));
+            }
+            Err(e) => {
+                entry.outcome = AuditOutcome::Failed;
+                entry.error = Some(e.clone());
+            }
+        }
+        if !(source == CommandSource::ChatPoll && entry.success) {
+            self.record(window, entry);
+        }
+        result
+    }
+
+    /// Records a command sent on a connection of its own rather than through `exec`.
+    pub fn record_direct(
+        &self,
+        window: &Window,
+        source: CommandSource,
+        command: &str,
+        result: &Result<String, String>,
+        started: Instant,
+    ) {
+        self.record(
+            window,
+            RconAuditEntry {
+                at: Local::now().to_rfc3339(),
+                profile_id: None,
+                source,
+                command: redact(command),
+                outcome: if result.is_ok() {
+                    AuditOutcome::Executed
+                } else {
+                    AuditOutcome::Failed
+                },
+                success: result.is_ok(),
+                response: result.as_ref().ok().map(|r| truncate(r)),
+                error: result.as_ref().err().cloned(),
+                policy_id: None,
+                confirmed: false,
+                duration_ms: started.elapsed().as_millis() as u64,
+            },
+        );
+    }
+
+    /// Appends `entry` to the audit file and emits it as `rcon-audit`.
+    pub fn record(&self, window: &Window, entry: RconAuditEntry) {
+        if let Err(e) = self.append(&entry) {
+            println!("Failed to write RCON audit entry: {}", e);
+        }
+        let _ = window.emit(
+            "rcon-audit",
+            serde_json::json!({ "profile_id": entry.profile_id, "entry": entry }),
+        );
+    }
+
+    fn append(&self, entry: &RconAuditEntry) -> Result<(), String> {
+        let Some(path) = self.audit_path.read().unwrap().clone() else {
+            return Ok(());
+        };
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        if fs::metadata(&path).is_ok_and(|m| m.len() >= AUDIT_MAX_BYTES) {
+            fs::rename(&path, path.with_extension("1.jsonl")).map_err(|e| e.to_string())?;
+        }
+        let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Matching entries, newest first.
+    pub fn entries(&self, query: &RconAuditQuery) -> Vec<RconAuditEntry> {
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_AUDIT_LIMIT)
+            .clamp(1, MAX_AUDIT_LIMIT);
+        let Some(path) = self.audit_path.read().unwrap().clone() else {
+            return vec![];
+        };
+        let _guard = self.write_lock.lock().unwrap();
+        let mut entries: Vec<RconAuditEntry> = vec![];
+        for file in [path.with_extension("1.jsonl"), path] {
+            let Ok(file) = File::open(&file) else {
+                continue;
+            };
+            entries.extend(
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter_map(|line| serde_json::from_str::<RconAuditEntry>(&line).ok())
+                    .filter(|e| {
+                        query
+                            .profile_id
+                            .as_ref()
+                            .is_none_or(|p| e.profile_id.as_ref() == Some(p))
+                            && query.source.is_none_or(|s| e.source == s)
+                            && (!query.failures_only || !e.success)
+                    }),
+            );
+        }
+        entries.reverse();
+        entries.truncate(limit);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audit_with_defaults() -> RconAudit {
+        let audit = RconAudit::default();
+        *audit.policies.write().unwrap() = default_policies();
+        audit
+    }
+
+    fn policy_for(audit: &RconAudit, command: &str) -> Option<String> {
+        audit
+            .matching_policy("p1", command, CommandSource::Console)
+            .map(|p| p.id)
+    }
+
+    #[test]
+    fn strips_cheat_prefixes() {
+        assert_eq!(
+            strip_cheat_prefix("admincheat DestroyWildDinos"),
+            "DestroyWildDinos"
+        );
+        assert_eq!(strip_cheat_prefix("  CHEAT  cheat DoExit"), "DoExit");
+        assert_eq!(
+            strip_cheat_prefix("AdminCheat KillPlayer 123"),
+            "KillPlayer 123"
+        );
+        assert_eq!(strip_cheat_prefix("ListPlayers"), "ListPlayers");
+        assert_eq!(strip_cheat_prefix("cheat"), "");
+        assert_eq!(command_name("admincheat admincheat Quit now"), "Quit");
+    }
+
+    #[test]
+    fn prefixed_commands_match_policies() {
+        let audit = audit_with_defaults();
+        assert_eq!(
+            policy_for(&audit, "admincheat DestroyWildDinos").as_deref(),
+            Some("default-destroy")
+        );
+        assert_eq!(
+            policy_for(&audit, "cheat DoExit").as_deref(),
+            Some("default-exit")
+        );
+        assert_eq!(
+            policy_for(&audit, "AdminCheat KillPlayer 123456").as_deref(),
+            Some("default-players")
+        );
+        assert_eq!(
+            policy_for(&audit, "cheat admincheat DestroyAllEnemies").as_deref(),
+            Some("default-destroy")
+        );
+        assert_eq!(policy_for(&audit, "admincheat ListPlayers"), None);
+        // A prefix is only stripped as a whole word
+        assert_eq!(policy_for(&audit, "cheatDoExit"), None);
+    }
+
+    #[test]
+    fn prefixed_commands_skip_sources_a_policy_does_not_list() {
+        let audit = audit_with_defaults();
+        let policy = audit.matching_policy("p1", "admincheat DoExit", CommandSource::Shutdown);
+        assert!(policy.is_none());
+    }
+
+    #[test]
+    fn redacts_enable_cheats_behind_prefixes() {
+        assert_eq!(redact("EnableCheats hunter2"), "EnableCheats ****");
+        assert_eq!(
+            redact("admincheat EnableCheats hunter2"),
+            "admincheat EnableCheats ****"
+        );
+        assert_eq!(
+            redact("Cheat  enablecheats hunter2"),
+            "Cheat  EnableCheats ****"
+        );
+        assert_eq!(redact("admincheat ListPlayers"), "admincheat ListPlayers");
+    }
+}
@@ -0,0 +1,652 @@
+// src-tauri/src/steamcmd.rs
+//
+// SteamCMD job runner. Server, map and mod updates all go through here: jobs for the
+// same install are queued behind each other so they never clash over `steamcmd/`,
+// every job has an ID that can be cancelled, and progress is reported through a
+// common set of `steamcmd-job-*` events alongside the legacy per-kind events.
+
+use crate::layout::InstallLayout;
+use crate::steamcmd_progress::{self, RetryBudgets, SteamCmdError, SteamCmdOutput};
+use crate::platform;
+use crate::{download_file, force_kill_process, log_to_frontend};
+use chrono::Local;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tauri::{Emitter, Window};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+pub const ARK_SERVER_APP_ID: &str = "2430930";
+const MAX_FINISHED_JOBS_KEPT: usize = 50;
+
+#[derive(Clone, Debug)]
+pub enum JobKind {
+    Server,
+    Map {
+        map_id: String,
+        app_id: Option<String>,
+    },
+    Mod {
+        mod_ids: Vec<String>,
+    },
+}
+
+impl JobKind {
+    fn name(&self) -> &'static str {
+        match self {
+            JobKind::Server => "serverUpdate",
+            JobKind::Map { .. } => "mapUpdate",
+            JobKind::Mod { .. } => "modUpdate",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            JobKind::Server => "Server file update",
+            JobKind::Map { .. } => "Map update",
+            JobKind::Mod { .. } => "Mod update",
+        }
+    }
+
+    // Event names the existing progress modals listen to
+    fn log_event(&self) -> &'static str {
+        match self {
+            JobKind::Server => "update-log",
+            JobKind::Map { .. } => "map-update-log",
+            JobKind::Mod { .. } => "mod-update-log",
+        }
+    }
+
+    fn finished_event(&self) -> &'static str {
+        match self {
+            JobKind::Server => "update-finished",
+            JobKind::Map { .. } => "map-update-finished",
+            JobKind::Mod { .. } => "mod-update-finished",
+        }
+    }
+
+    /// Returns the SteamCMD commands to run after login, or a message explaining why
+    /// there is nothing to do.
+    fn script_commands(&self) -> Result<Vec<String>, String> {
+        match self {
+            JobKind::Server => Ok(vec![format!("app_update {} validate", ARK_SERVER_APP_ID)]),
+            JobKind::Map { app_id: Some(app_id), .. } => {
+                Ok(vec![format!("app_update {} validate", app_id)])
+            }
+            JobKind::Map { app_id: None, .. } => {
+                Err("  > Selected map is not a downloadable DLC. Nothing to do.".to_string())
+            }
+            JobKind::Mod { mod_ids } if mod_ids.is_empty() => {
+                Err("  > No mod IDs provided. Nothing to do.".to_string())
+            }
+            JobKind::Mod { mod_ids } => Ok(mod_ids
+                .iter()
+                .map(|id| format!("workshop_download_item {} {}", ARK_SERVER_APP_ID, id))
+                .collect()),
+        }
+    }
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(serde::Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub job_id: String,
+    pub kind: String,
+    pub install_path: String,
+    pub status: JobStatus,
+    pub attempt: u32,
+    pub queued_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub error: Option<String>,
+    pub error_detail: Option<SteamCmdError>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancellation_token: CancellationToken,
+}
+
+#[derive(Default)]
+pub struct SteamCmdJobs {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    // One lock per install directory; tokio's Mutex is fair, so waiting jobs run in FIFO order
+    install_locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+fn now_string() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+fn install_key(install_path: &str) -> String {
+    let normalized = install_path.replace('\\', "/").trim_end_matches('/').to_string();
+    if cfg!(target_os = "windows") {
+        normalized.to_lowercase()
+    } else {
+        normalized
+    }
+}
+
+#[derive(Clone)]
+struct JobContext {
+    window: Window,
+    job_id: String,
+    kind: JobKind,
+    install_path: String,
+}
+
+impl JobContext {
+    fn log(&self, line: &str) {
+        log_to_frontend(&self.window, self.kind.log_event(), line);
+        let _ = self.window.emit(
+            "steamcmd-job-log",
+            serde_json::json!({
+                "job_id": self.job_id,
+                "kind": self.kind.name(),
+                "line": line,
+            }),
+        );
+    }
+
+    fn emit_status(&self, event: &str, extra: serde_json::Value) {
+        let mut payload = serde_json::json!({
+            "job_id": self.job_id,
+            "kind": self.kind.name(),
+            "install_path": self.install_path,
+        });
+        if let (Some(payload), Some(extra)) = (payload.as_object_mut(), extra.as_object()) {
+            payload.extend(extra.clone());
+        }
+        let _ = self.window.emit(event, payload);
+    }
+}
+
+impl SteamCmdJobs {
+    /// Queues a job and returns its ID immediately. The job starts once every earlier
+    /// job on the same install has finished.
+    pub async fn enqueue(
+        self: &Arc<Self>,
+        window: Window,
+        install_path: String,
+        kind: JobKind,
+    ) -> String {
+        let job_id = format!(
+            "job-{}-{}",
+            Local::now().format("%Y%m%d%H%M%S"),
+            self.next_id.fetch_add(1, Ordering::SeqCst) + 1
+        );
+        let cancellation_token = CancellationToken::new();
+
+        {
+            let mut jobs = self.jobs.lock().await;
+            jobs.insert(
+                job_id.clone(),
+                JobEntry {
+                    info: JobInfo {
+                        job_id: job_id.clone(),
+                        kind: kind.name().to_string(),
+                        install_path: install_path.clone(),
+                        status: JobStatus::Queued,
+                        attempt: 0,
+                        queued_at: now_string(),
+                        started_at: None,
+                        finished_at: None,
+                        error: None,
+                        error_detail: None,
+                    },
+                    cancellation_token: cancellation_token.clone(),
+                },
+            );
+            prune_finished(&mut jobs);
+        }
+
+        let install_lock = {
+            let mut locks = self.install_locks.lock().await;
+            locks
+                .entry(install_key(&install_path))
+                .or_insert_with(|| Arc::new(Mutex::new(())))
+                .clone()
+        };
+
+        let ctx = JobContext {
+            window,
+            job_id: job_id.clone(),
+            kind,
+            install_path,
+        };
+        ctx.emit_status("steamcmd-job-queued", serde_json::json!({}));
+
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            let result = {
+                let guard = tokio::select! {
+                    guard = install_lock.lock() => Some(guard),
+                    _ = cancellation_token.cancelled() => None,
+                };
+                if guard.is_some() {
+                    jobs.update(&ctx.job_id, |info| {
+                        info.status = JobStatus::Running;
+                        info.started_at = Some(now_string());
+                    })
+                    .await;
+                    ctx.emit_status("steamcmd-job-started", serde_json::json!({}));
+                    run_job(&ctx, &jobs, &cancellation_token).await
+                } else {
+                    Err(JobError::Cancelled)
+                }
+            };
+            jobs.finish(&ctx, result).await;
+        });
+
+        job_id
+    }
+
+    pub async fn cancel(&self, job_id: &str) -> Result<(), String> {
+        let jobs = self.jobs.lock().await;
+        match jobs.get(job_id) {
+            Some(entry) if matches!(entry.info.status, JobStatus::Queued | JobStatus::Running) => {
+                entry.cancellation_token.cancel();
+                Ok(())
+            }
+            Some(_) => Err("Job has already finished.".to_string()),
+            None => Err("Job not found.".to_string()),
+        }
+    }
+
+    pub async fn list(&self) -> Vec<JobInfo> {
+        let jobs = self.jobs.lock().await;
+        let mut list: Vec<JobInfo> = jobs.values().map(|entry| entry.info.clone()).collect();
+        list.sort_by(|a, b| a.queued_at.cmp(&b.queued_at).then(a.job_id.cmp(&b.job_id)));
+        list
+    }
+
+    async fn update<F: FnOnce(&mut JobInfo)>(&self, job_id: &str, f: F) {
+        let mut jobs = self.jobs.lock().await;
+        if let Some(entry) = jobs.get_mut(job_id) {
+            f(&mut entry.info);
+        }
+    }
+
+    async fn finish(&self, ctx: &JobContext, result: Result<(), JobError>) {
+        let (status, error, error_detail) = match &result {
+            Ok(()) => (JobStatus::Succeeded, None, None),
+            Err(JobError::Cancelled) => {
+                (JobStatus::Cancelled, Some("Cancelled".to_string()), None)
+            }
+            Err(JobError::Failed { message, detail }) => {
+                (JobStatus::Failed, Some(message.clone()), detail.clone())
+            }
+        };
+
+        match &result {
+            Ok(()) => ctx.log(&format!("\n✅ {} completed successfully!", ctx.kind.label())),
+            Err(JobError::Cancelled) => ctx.log(&format!("\n⚠️ {} was cancelled.", ctx.kind.label())),
+            Err(JobError::Failed { message, .. }) => ctx.log(message),
+        }
+
+        self.update(&ctx.job_id, |info| {
+            info.status = status;
+            info.finished_at = Some(now_string());
+            info.error = error.clone();
+            info.error_detail = error_detail.clone();
+        })
+        .await;
+
+        let success = status == JobStatus::Succeeded;
+        let _ = ctx
+            .window
+            .emit(ctx.kind.finished_event(), serde_json::json!({ "success": success }));
+        ctx.emit_status(
+            "steamcmd-job-finished",
+            serde_json::json!({
+                "status": status,
+                "success": success,
+                "error": error,
+                "error_detail": error_detail,
+            }),
+        );
+    }
+}
+
+fn prune_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(String, String)> = jobs
+        .values()
+        .filter(|entry| entry.info.finished_at.is_some())
+        .map(|entry| (entry.info.queued_at.clone(), entry.info.job_id.clone()))
+        .collect();
+    if finished.len() <= MAX_FINISHED_JOBS_KEPT {
+        return;
+    }
+    finished.sort();
+    let excess = finished.len() - MAX_FINISHED_JOBS_KEPT;
+    for (_, job_id) in finished.into_iter().take(excess) {
+        jobs.remove(&job_id);
+    }
+}
+
+enum JobError {
+    Cancelled,
+    Failed {
+        message: String,
+        // The last error SteamCMD printed, when there was one
+        detail: Option<SteamCmdError>,
+    },
+}
+
+impl JobError {
+    fn failed(message: String) -> Self {
+        JobError::Failed {
+            message,
+            detail: None,
+        }
+    }
+}
+
+pub fn steamcmd_paths(install_path: &str) -> (PathBuf, PathBuf) {
+    let steamcmd_dir = PathBuf::from(install_path).join("steamcmd");
+    let steamcmd_exe = steamcmd_dir.join(platform::STEAMCMD_EXECUTABLE);
+    (steamcmd_dir, steamcmd_exe)
+}
+
+pub async fn ensure_steamcmd(
+    window: &Window,
+    log_event: &str,
+    steamcmd_dir: &Path,
+    steamcmd_exe: &Path,
+) -> Result<(), String> {
+    if !steamcmd_exe.exists() {
+        log_to_frontend(
+            window,
+            log_event,
+            &format!("  > {} not found. Setting up...", platform::STEAMCMD_EXECUTABLE),
+        );
+        if let Err(e) = std::fs::create_dir_all(steamcmd_dir) {
+            return Err(format!(
+                "❌ ERROR: Failed to create steamcmd directory: {}",
+                e
+            ));
+        }
+
+        let archive_name = platform::steamcmd_archive_name();
+        let archive_path = steamcmd_dir.join(archive_name);
+
+        if let Err(e) = download_file(platform::steamcmd_download_url(), &archive_path).await {
+            return Err(format!("❌ ERROR: Failed to download {}: {}", archive_name, e));
+        }
+        log_to_frontend(window, log_event, "  > Download complete. Extracting...");
+
+        if let Err(e) = platform::extract_steamcmd_archive(&archive_path, steamcmd_dir) {
+            return Err(format!("❌ ERROR: Failed to extract {}: {}", archive_name, e));
+        }
+        log_to_frontend(
+            window,
+            log_event,
+            "  > Extraction complete. Deleting archive...",
+        );
+        let _ = std::fs::remove_file(&archive_path);
+    }
+    Ok(())
+}
+
+async fn run_job(
+    ctx: &JobContext,
+    jobs: &SteamCmdJobs,
+    cancellation_token: &CancellationToken,
+) -> Result<(), JobError> {
+    let commands = match ctx.kind.script_commands() {
+        Ok(commands) => commands,
+        Err(nothing_to_do) => {
+            ctx.log(&nothing_to_do);
+            return Ok(());
+        }
+    };
+
+    let (steamcmd_dir, steamcmd_exe) = steamcmd_paths(&ctx.install_path);
+    ensure_steamcmd(&ctx.window, ctx.kind.log_event(), &steamcmd_dir, &steamcmd_exe)
+        .await
+        .map_err(JobError::failed)?;
+
+    ctx.log(&format!(
+        "✅ SteamCMD is ready. Starting {}...",
+        ctx.kind.label().to_lowercase()
+    ));
+    match &ctx.kind {
+        JobKind::Map { map_id, app_id: Some(app_id) } => ctx.log(&format!(
+            "  > Downloading map '{}' (App ID: {})...",
+            map_id, app_id
+        )),
+        JobKind::Mod { mod_ids } => ctx.log(&format!(
+            "  > Found {} mods to download/update...",
+            mod_ids.len()
+        )),
+        _ => {}
+    }
+
+    let install_dir_arg = InstallLayout::detect(&ctx.install_path)
+        .server_root
+        .to_string_lossy()
+        .replace('\\', "/");
+    let mut script_content = format!(
+        "{}force_install_dir \"{}\"\nlogin anonymous\n",
+        platform::steamcmd_preamble(),
+        install_dir_arg
+    );
+    for command in commands {
+        script_content.push_str(&command);
+        script_content.push('\n');
+    }
+    script_content.push_str("quit\n");
+
+    // Each job gets its own script file so queued jobs never overwrite a running one
+    let script_path = steamcmd_dir.join(format!("{}.txt", ctx.job_id));
+    std::fs::write(&script_path, &script_content).map_err(|e| {
+        JobError::failed(format!(
+            "❌ ERROR: Failed to write SteamCMD script file: {}",
+            e
+        ))
+    })?;
+
+    let result = run_with_retries(
+        ctx,
+        jobs,
+        cancellation_token,
+        &steamcmd_dir,
+        &steamcmd_exe,
+        &script_path,
+    )
+    .await;
+    let _ = std::fs::remove_file(&script_path);
+    result
+}
+
+/// Forwards one of SteamCMD's output streams to the frontend, emitting structured
+/// progress and error events along the way. Resolves to the last error seen.
+fn spawn_output_reader<R: AsyncRead + Unpin + Send + 'static>(
+    ctx: JobContext,
+    stream: R,
+) -> JoinHandle<Option<SteamCmdError>> {
+    tokio::spawn(async move {
+        let mut last_error = None;
+        let mut reader = BufReader::new(stream).lines();
+        while let Ok(Some(line)) = reader.next_line().await {
+            ctx.log(&line);
+            match steamcmd_progress::parse_line(&line) {
+                Some(SteamCmdOutput::Progress(progress)) => {
+                    ctx.emit_status(
+                        "steamcmd-job-progress",
+                        serde_json::to_value(&progress).unwrap_or_default(),
+                    );
+                }
+                Some(SteamCmdOutput::Error(error)) => {
+                    ctx.emit_status(
+                        "steamcmd-job-error",
+                        serde_json::to_value(&error).unwrap_or_default(),
+                    );
+                    last_error = Some(error);
+                }
+                Some(SteamCmdOutput::Success(message)) => {
+                    ctx.emit_status(
+                        "steamcmd-job-success",
+                        serde_json::json!({ "message": message }),
+                    );
+                }
+                None => {}
+            }
+        }
+        last_error
+    })
+}
+
+async fn run_with_retries(
+    ctx: &JobContext,
+    jobs: &SteamCmdJobs,
+    cancellation_token: &CancellationToken,
+    steamcmd_dir: &Path,
+    steamcmd_exe: &Path,
+    script_path: &Path,
+) -> Result<(), JobError> {
+    let mut attempt = 0;
+    let mut budgets = RetryBudgets::default();
+    loop {
+        attempt += 1;
+        jobs.update(&ctx.job_id, |info| info.attempt = attempt)
+            .await;
+
+        let mut cmd = Command::new(steamcmd_exe);
+        cmd.current_dir(steamcmd_dir)
+            .arg("+runscript")
+            .arg(script_path);
+        cmd.stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        // steamcmd.sh runs the real binary as a child instead of exec'ing it, so give the
+        // job its own process group that a cancel can take down as a whole
+        #[cfg(unix)]
+        cmd.process_group(0);
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let message = format!("Failed to start SteamCMD process: {}", e);
+                retry_after_failure(
+                    ctx,
+                    cancellation_token,
+                    &mut budgets,
+                    attempt,
+                    message,
+                    None,
+                )
+                .await?;
+                continue;
+            }
+        };
+
+        let stdout_reader = child
+            .stdout
+            .take()
+            .map(|stdout| spawn_output_reader(ctx.clone(), stdout));
+        let stderr_reader = child
+            .stderr
+            .take()
+            .map(|stderr| spawn_output_reader(ctx.clone(), stderr));
+
+        let status = tokio::select! {
+            status = child.wait() => status,
+            _ = cancellation_token.cancelled() => {
+                if let Some(pid) = child.id() {
+                    let _ = force_kill_process(pid);
+                }
+                let _ = child.kill().await;
+                return Err(JobError::Cancelled);
+            }
+        };
+
+        let mut output_error = None;
+        for reader in [stdout_reader, stderr_reader].into_iter().flatten() {
+            if let Ok(Some(error)) = reader.await {
+                output_error = Some(error);
+            }
+        }
+
+        let (message, detail) = match (status, output_error) {
+            // SteamCMD sometimes exits with 0 after printing an error, so the output wins
+            (Ok(s), None) if s.success() => return Ok(()),
+            (_, Some(error)) => {
+                if !error.class.is_retryable() {
+                    return Err(JobError::Failed {
+                        message: format!(
+                            "\n❌ {} failed with an error that retrying won't fix: {}",
+                            ctx.kind.label(),
+                            error.message
+                        ),
+                        detail: Some(error),
+                    });
+                }
+                (error.message.clone(), Some(error))
+            }
+            (Ok(_), None) => (
+                "Process finished with non-zero exit code.".to_string(),
+                None,
+            ),
+            (Err(e), None) => (e.to_string(), None),
+        };
+        retry_after_failure(
+            ctx,
+            cancellation_token,
+            &mut budgets,
+            attempt,
+            message,
+            detail,
+        )
+        .await?;
+    }
+}
+
+/// Waits out the backoff for the error class of a failed attempt, or fails the job
+/// once that class's retry budget is spent.
+async fn retry_after_failure(
+    ctx: &JobContext,
+    cancellation_token: &CancellationToken,
+    budgets: &mut RetryBudgets,
+    attempt: u32,
+    message: String,
+    detail: Option<SteamCmdError>,
+) -> Result<(), JobError> {
+    let Some(delay) = budgets.next_delay(detail.as_ref()) else {
+        return Err(JobError::Failed {
+            message: format!(
+                "\n❌ {} finished with an error after {} attempts: {}",
+                ctx.kind.label(),
+                attempt,
+                message
+            ),
+            detail,
+        });
+    };
+    ctx.log(&format!(
+        "\n⚠️ {} attempt {} failed ({}). Retrying in {} seconds...",
+        ctx.kind.label(),
+        attempt,
+        message,
+        delay.as_secs()
+    ));
+    tokio::select! {
+        _ = cancellation_token.cancelled() => Err(JobError::Cancelled),
+        _ = tokio::time::sleep(delay) => Ok(()),
+    }
+}
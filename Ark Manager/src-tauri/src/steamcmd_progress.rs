@@ -0,0 +1,396 @@
+// src-tauri/src/steamcmd_progress.rs
+//
+// Turns raw SteamCMD output lines into structured progress and result events, and
+// classifies failures so the job runner only retries errors that can succeed on a
+// second attempt, with a retry budget and backoff per class.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// " Update state (0x61) downloading, progress: 12.34 (123456789 / 987654321)"
+static UPDATE_STATE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"Update state \((?P<code>0x[0-9a-fA-F]+)\)\s*(?P<phase>[a-zA-Z ]+?),\s*progress:\s*(?P<percent>[\d.]+)\s*\((?P<done>\d+)\s*/\s*(?P<total>\d+)\)").unwrap()
+});
+// "Success! App '2430930' fully installed." / "Success. Downloaded item 123 to "..." (456 bytes)"
+static SUCCESS_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Success[.!]\s*(?P<message>.*)$").unwrap());
+// "Error! App '2430930' state is 0x202 after update job."
+static APP_STATE_ERROR_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)Error! App '(?P<app>\d+)' state is (?P<code>0x[0-9a-fA-F]+)").unwrap()
+});
+// "ERROR! Failed to install app '2430930' (No subscription)" / "ERROR! Download item 123 failed (Failure)."
+static GENERIC_ERROR_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^ERROR!\s*(?P<message>.*?)(?:\s*\((?P<reason>[^)]+)\))?\.?$").unwrap());
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SteamCmdPhase {
+    Reconfiguring,
+    Preallocating,
+    Downloading,
+    Verifying,
+    Committing,
+    Other,
+}
+
+impl SteamCmdPhase {
+    fn from_text(text: &str) -> Self {
+        let text = text.to_lowercase();
+        if text.contains("reconfiguring") {
+            SteamCmdPhase::Reconfiguring
+        } else if text.contains("preallocating") {
+            SteamCmdPhase::Preallocating
+        } else if text.contains("downloading") {
+            SteamCmdPhase::Downloading
+        } else if text.contains("verifying") {
+            SteamCmdPhase::Verifying
+        } else if text.contains("committing") {
+            SteamCmdPhase::Committing
+        } else {
+            SteamCmdPhase::Other
+        }
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SteamCmdProgress {
+    pub phase: SteamCmdPhase,
+    pub state_code: String,
+    pub percent: f64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SteamCmdErrorClass {
+    /// App state 0x202: not enough free disk space.
+    DiskSpace,
+    /// App state 0x402 / 0x602 and similar: the download or content was interrupted.
+    ContentInterrupted,
+    NoSubscription,
+    MissingConfiguration,
+    Timeout,
+    NoConnection,
+    Other,
+}
+
+impl SteamCmdErrorClass {
+    /// How many times a job is retried after this error. Interrupted content resumes
+    /// where it stopped, so it gets the most retries; an unrecognised error gets one.
+    pub fn retry_budget(&self) -> u32 {
+        match self {
+            SteamCmdErrorClass::ContentInterrupted => 5,
+            SteamCmdErrorClass::Timeout | SteamCmdErrorClass::NoConnection => 4,
+            SteamCmdErrorClass::Other => 1,
+            SteamCmdErrorClass::DiskSpace
+            | SteamCmdErrorClass::NoSubscription
+            | SteamCmdErrorClass::MissingConfiguration => 0,
+        }
+    }
+
+    /// Whether running the same job again has a reasonable chance of succeeding.
+    pub fn is_retryable(&self) -> bool {
+        self.retry_budget() > 0
+    }
+
+    /// Wait before retry number `retry` (1-based), or `None` once the budget is spent.
+    /// Network trouble backs off exponentially (5s, 10s, 20s, ...) to give Steam time
+    /// to come back; anything else is retried after a short fixed pause.
+    pub fn retry_delay(&self, retry: u32) -> Option<Duration> {
+        if retry == 0 || retry > self.retry_budget() {
+            return None;
+        }
+        Some(match self {
+            SteamCmdErrorClass::Timeout | SteamCmdErrorClass::NoConnection => {
+                Duration::from_secs(5 << (retry - 1))
+            }
+            _ => Duration::from_secs(2),
+        })
+    }
+
+    fn from_state_code(code: &str) -> Self {
+        match code.to_lowercase().as_str() {
+            "0x202" => SteamCmdErrorClass::DiskSpace,
+            "0x402" | "0x602" | "0x6" | "0x2" => SteamCmdErrorClass::ContentInterrupted,
+            _ => SteamCmdErrorClass::Other,
+        }
+    }
+
+    fn from_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+        if message.contains("no subscription") {
+            SteamCmdErrorClass::NoSubscription
+        } else if message.contains("missing configuration") {
+            SteamCmdErrorClass::MissingConfiguration
+        } else if message.contains("timeout") || message.contains("timed out") {
+            SteamCmdErrorClass::Timeout
+        } else if message.contains("connection") || message.contains("network") {
+            SteamCmdErrorClass::NoConnection
+        } else if message.contains("disk space") || message.contains("disk write failure") {
+            SteamCmdErrorClass::DiskSpace
+        } else {
+            SteamCmdErrorClass::Other
+        }
+    }
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SteamCmdError {
+    pub class: SteamCmdErrorClass,
+    pub state_code: Option<String>,
+    pub message: String,
+}
+
+/// Retries a job has spent so far, counted separately per error class so that, say,
+/// a network blip doesn't eat into the budget for interrupted content.
+#[derive(Default)]
+pub struct RetryBudgets {
+    used: HashMap<SteamCmdErrorClass, u32>,
+}
+
+impl RetryBudgets {
+    /// Counts a failed attempt and returns how long to wait before the next one, or
+    /// `None` once the failure's class has no retries left. Failures without a
+    /// recognised SteamCMD error count as `Other`.
+    pub fn next_delay(&mut self, error: Option<&SteamCmdError>) -> Option<Duration> {
+        let class = error.map_or(SteamCmdErrorClass::Other, |e| e.class);
+        let used = self.used.entry(class).or_insert(0);
+        *used += 1;
+        class.retry_delay(*used)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SteamCmdOutput {
+    Progress(SteamCmdProgress),
+    Success(String),
+    Error(SteamCmdError),
+}
+
+pub fn parse_line(line: &str) -> Option<SteamCmdOutput> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    if let Some(caps) = UPDATE_STATE_REGEX.captures(line) {
+        return Some(SteamCmdOutput::Progress(SteamCmdProgress {
+            phase: SteamCmdPhase::from_text(&caps["phase"]),
+            state_code: caps["code"].to_string(),
+            percent: caps["percent"].parse().unwrap_or(0.0),
+            bytes_done: caps["done"].parse().unwrap_or(0),
+            bytes_total: caps["total"].parse().unwrap_or(0),
+        }));
+    }
+
+    if let Some(caps) = APP_STATE_ERROR_REGEX.captures(line) {
+        let code = caps["code"].to_string();
+        return Some(SteamCmdOutput::Error(SteamCmdError {
+            class: SteamCmdErrorClass::from_state_code(&code),
+            state_code: Some(code),
+            message: line.to_string(),
+        }));
+    }
+
+    if let Some(caps) = GENERIC_ERROR_REGEX.captures(line) {
+        let reason = caps
+            .name("reason")
+            .map(|m| m.as_str())
+            .unwrap_or_else(|| caps.name("message").map_or("", |m| m.as_str()));
+        return Some(SteamCmdOutput::Error(SteamCmdError {
+            class: SteamCmdErrorClass::from_message(reason),
+            state_code: None,
+            message: line.to_string(),
+        }));
+    }
+
+    if let Some(caps) = SUCCESS_REGEX.captures(line) {
+        return Some(SteamCmdOutput::Success(caps["message"].to_string()));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(line: &str) -> SteamCmdError {
+        match parse_line(line) {
+            Some(SteamCmdOutput::Error(error)) => error,
+            other => panic!("expected an error for {:?}, got {:?}", line, other),
+        }
+    }
+
+    fn progress(line: &str) -> SteamCmdProgress {
+        match parse_line(line) {
+            Some(SteamCmdOutput::Progress(progress)) => progress,
+            other => panic!("expected progress for {:?}, got {:?}", line, other),
+        }
+    }
+
+    #[test]
+    fn app_state_0x202_is_a_disk_space_error() {
+        let error = error("Error! App '2430930' state is 0x202 after update job.");
+        assert_eq!(error.class, SteamCmdErrorClass::DiskSpace);
+        assert_eq!(error.state_code.as_deref(), Some("0x202"));
+        assert!(!error.class.is_retryable());
+    }
+
+    #[test]
+    fn interrupted_app_states_are_retried() {
+        for code in ["0x402", "0x602"] {
+            let line = format!("Error! App '2430930' state is {} after update job.", code);
+            assert_eq!(error(&line).class, SteamCmdErrorClass::ContentInterrupted);
+        }
+    }
+
+    #[test]
+    fn account_and_configuration_errors_are_not_retried() {
+        let error1 = error("ERROR! Failed to install app '2430930' (No subscription)");
+        assert_eq!(error1.class, SteamCmdErrorClass::NoSubscription);
+        assert!(!error1.class.is_retryable());
+
+        let error2 = error("ERROR! Failed to install app '2430930' (Missing configuration)");
+        assert_eq!(error2.class, SteamCmdErrorClass::MissingConfiguration);
+        assert!(!error2.class.is_retryable());
+    }
+
+    #[test]
+    fn timeouts_and_connection_errors_are_classified() {
+        for line in [
+            "ERROR! Download item 928102085 failed (Timeout).",
+            "ERROR! Failed to install app '2430930' (Connection timed out)",
+            "ERROR! Timeout downloading item 928102085",
+        ] {
+            assert_eq!(error(line).class, SteamCmdErrorClass::Timeout, "{}", line);
+        }
+        assert_eq!(
+            error("ERROR! Failed to install app '2430930' (No Connection)").class,
+            SteamCmdErrorClass::NoConnection
+        );
+        assert_eq!(
+            error("ERROR! Download item 928102085 failed (Failure).").class,
+            SteamCmdErrorClass::Other
+        );
+    }
+
+    #[test]
+    fn update_state_lines_are_progress() {
+        let downloading =
+            progress(" Update state (0x61) downloading, progress: 12.34 (123456789 / 987654321)");
+        assert_eq!(downloading.phase, SteamCmdPhase::Downloading);
+        assert_eq!(downloading.state_code, "0x61");
+        assert_eq!(downloading.percent, 12.34);
+        assert_eq!(downloading.bytes_done, 123456789);
+        assert_eq!(downloading.bytes_total, 987654321);
+
+        let verifying =
+            progress(" Update state (0x5) verifying install, progress: 3.50 (35 / 1000)");
+        assert_eq!(verifying.phase, SteamCmdPhase::Verifying);
+        assert_eq!(verifying.percent, 3.5);
+
+        let preallocating = progress(" Update state (0x11) preallocating, progress: 0.00 (0 / 0)");
+        assert_eq!(preallocating.phase, SteamCmdPhase::Preallocating);
+
+        let committing = progress(" Update state (0x101) committing, progress: 100.00 (10 / 10)");
+        assert_eq!(committing.phase, SteamCmdPhase::Committing);
+    }
+
+    #[test]
+    fn success_lines_carry_their_message() {
+        match parse_line("Success! App '2430930' fully installed.") {
+            Some(SteamCmdOutput::Success(message)) => {
+                assert_eq!(message, "App '2430930' fully installed.")
+            }
+            other => panic!("expected success, got {:?}", other),
+        }
+        assert!(parse_line("Loading Steam API...OK").is_none());
+    }
+
+    #[test]
+    fn network_errors_back_off_exponentially() {
+        for class in [
+            SteamCmdErrorClass::NoConnection,
+            SteamCmdErrorClass::Timeout,
+        ] {
+            let delays: Vec<u64> = (1..=5)
+                .map_while(|retry| class.retry_delay(retry))
+                .map(|d| d.as_secs())
+                .collect();
+            assert_eq!(delays, [5, 10, 20, 40]);
+        }
+    }
+
+    #[test]
+    fn retry_budgets_depend_on_the_error_class() {
+        let budget = |class: SteamCmdErrorClass| {
+            (1..=10)
+                .take_while(|retry| class.retry_delay(*retry).is_some())
+                .count()
+        };
+        assert_eq!(budget(SteamCmdErrorClass::ContentInterrupted), 5);
+        assert_eq!(budget(SteamCmdErrorClass::Other), 1);
+        assert_eq!(budget(SteamCmdErrorClass::DiskSpace), 0);
+        assert_eq!(budget(SteamCmdErrorClass::NoSubscription), 0);
+    }
+
+    #[test]
+    fn retry_budgets_are_counted_per_class() {
+        let failure = |class| SteamCmdError {
+            class,
+            state_code: None,
+            message: String::new(),
+        };
+        let timeout = failure(SteamCmdErrorClass::Timeout);
+        let interrupted = failure(SteamCmdErrorClass::ContentInterrupted);
+        let mut budgets = RetryBudgets::default();
+        let mut delays = vec![];
+        for error in [
+            Some(&timeout),
+            Some(&timeout),
+            Some(&interrupted),
+            None,
+            Some(&timeout),
+            Some(&interrupted),
+            Some(&timeout),
+            Some(&timeout),
+            None,
+        ] {
+            delays.push(budgets.next_delay(error).map(|d| d.as_secs()));
+        }
+        // Timeouts keep backing off from where they left off, interrupted content and
+        // unrecognised failures have their own counters
+        assert_eq!(
+            delays,
+            [
+                Some(5),
+                Some(10),
+                Some(2),
+                Some(2),
+                Some(20),
+                Some(2),
+                Some(40),
+                None,
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn unretryable_errors_stop_the_job_immediately() {
+        let disk = SteamCmdError {
+            class: SteamCmdErrorClass::DiskSpace,
+            state_code: Some("0x202".to_string()),
+            message: String::new(),
+        };
+        let mut budgets = RetryBudgets::default();
+        assert_eq!(budgets.next_delay(None), Some(Duration::from_secs(2)));
+        assert_eq!(budgets.next_delay(Some(&disk)), None);
+    }
+}
@@ -0,0 +1,623 @@
+// src-tauri/src/task_scheduler.rs
+//
+// Per-profile scheduled tasks (restarts, update checks, backups, RCON commands,
+// broadcasts and wipes) driven by cron expressions. Tasks are persisted to
+// `scheduled_tasks.json` in the app data dir together with the profiles whose schedule
+// is switched off, and each enabled task gets its own loop that sleeps until the next
+// occurrence plus the task's jitter. The work itself is done by `run_scheduled_task`
+// in main.rs.
+//
+// Sleeps are checked against the wall clock in short steps, so a suspended machine or
+// a clock change is noticed. An occurrence handled more than `MISSED_RUN_GRACE` late,
+// including ones that passed while the manager was closed, is missed: the task's
+// `missed_run` policy decides whether it still runs once or is skipped.
+//
+// Events: `scheduled-task-upcoming` when a run is armed, `scheduled-task-finished` after
+// it ran and `scheduled-task-skipped` with the reason when it did not.
+
+use crate::cron::CronSchedule;
+use chrono::{DateTime, Local};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+const TASKS_FILE: &str = "scheduled_tasks.json";
+/// How late an occurrence may be handled before it counts as missed.
+const MISSED_RUN_GRACE: Duration = Duration::from_secs(120);
+/// Longest single sleep, so clock jumps and suspends are noticed.
+const MAX_SLEEP_STEP: Duration = Duration::from_secs(60);
+const MAX_JITTER_SECONDS: u64 = 3600;
+const DEFAULT_PREVIEW_COUNT: usize = 5;
+const MAX_PREVIEW_COUNT: usize = 100;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum TaskAction {
+    /// Stops the server (with countdown broadcasts) and starts it again with the same
+    /// launch parameters. Does nothing when the server is not running.
+    Restart {
+        #[serde(default)]
+        countdown_seconds: Option<u64>,
+    },
+    /// Compares the installed build with the latest one on Steam and emits
+    /// `server-update-available` when they differ.
+    UpdateCheck,
+    /// An automatic backup under the profile's retention rules.
+    Backup,
+    RconCommand {
+        command: String,
+    },
+    Broadcast {
+        message: String,
+    },
+    /// Stops the server, backs up `Saved` and deletes the world saves (`SavedArks`).
+    Wipe {
+        #[serde(default)]
+        countdown_seconds: Option<u64>,
+        /// Start the server again afterwards if it was running.
+        #[serde(default)]
+        restart: bool,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MissedRunPolicy {
+    #[default]
+    Skip,
+    /// Run once as soon as possible, however many occurrences were missed.
+    RunOnce,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRun {
+    /// The occurrence this run was for; `None` for `run_scheduled_task_now`.
+    #[serde(default)]
+    pub scheduled_for: Option<String>,
+    pub started_at: String,
+    pub finished_at: String,
+    pub success: bool,
+    pub message: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTask {
+    pub id: String,
+    pub profile_id: String,
+    pub install_path: String,
+    #[serde(default)]
+    pub name: String,
+    /// Five-field cron expression in local time, see `cron.rs`.
+    pub cron: String,
+    pub action: TaskAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Each run starts up to this many seconds after its occurrence.
+    #[serde(default)]
+    pub jitter_seconds: u64,
+    #[serde(default)]
+    pub missed_run: MissedRunPolicy,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    /// The last occurrence that was run or skipped, used to find runs missed while
+    /// the manager was closed.
+    #[serde(default)]
+    pub last_occurrence: Option<String>,
+    #[serde(default)]
+    pub last_run: Option<TaskRun>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTaskStatus {
+    #[serde(flatten)]
+    pub task: ScheduledTask,
+    pub profile_enabled: bool,
+    /// When the next run starts, jitter included; `None` when the task is disabled or
+    /// the expression never fires again.
+    pub next_run: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct PersistedSchedule {
+    #[serde(default)]
+    tasks: Vec<ScheduledTask>,
+    #[serde(default)]
+    disabled_profiles: Vec<String>,
+}
+
+struct TaskEntry {
+    task: ScheduledTask,
+    token: Option<CancellationToken>,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    tasks: HashMap<String, TaskEntry>,
+    disabled_profiles: HashSet<String>,
+}
+
+#[derive(Default)]
+pub struct TaskScheduler {
+    state: Mutex<SchedulerState>,
+    /// Profiles with a task running; a second task for the same server is skipped.
+    busy: std::sync::Mutex<HashSet<String>>,
+}
+
+fn tasks_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(TASKS_FILE))
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|t| t.with_timezone(&Local))
+}
+
+/// A stable offset in `0..=max_seconds` for one occurrence of a task, so a retried
+/// lookup lands on the same start time. SHA-256 rather than `DefaultHasher`, whose
+/// output may change between Rust releases.
+fn jitter(task_id: &str, occurrence: DateTime<Local>, max_seconds: u64) -> chrono::Duration {
+    if max_seconds == 0 {
+        return chrono::Duration::zero();
+    }
+    let digest = Sha256::digest(format!("{}@{}", task_id, occurrence.timestamp()));
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    chrono::Duration::seconds((u64::from_le_bytes(bytes) % (max_seconds + 1)) as i64)
+}
+
+fn validate_task(task: &ScheduledTask) -> Result<CronSchedule, String> {
+    if task.id.trim().is_empty() {
+        return Err("Task id is required".to_string());
+    }
+    if task.profile_id.trim().is_empty() {
+        return Err("Task profile is required".to_string());
+    }
+    if task.jitter_seconds > MAX_JITTER_SECONDS {
+        return Err(format!(
+            "Jitter can be at most {} seconds",
+            MAX_JITTER_SECONDS
+        ));
+    }
+    match &task.action {
+        TaskAction::RconCommand { command } if command.trim().is_empty() => {
+            return Err("The RCON command is empty".to_string())
+        }
+        TaskAction::Broadcast { message } if message.trim().is_empty() => {
+            return Err("The broadcast message is empty".to_string())
+        }
+        _ => {}
+    }
+    CronSchedule::parse(&task.cron)
+}
+
+/// The next `count` start times of `expression`, for previewing a schedule.
+pub fn preview(expression: &str, count: Option<usize>) -> Result<Vec<String>, String> {
+    let schedule = CronSchedule::parse(expression)?;
+    let count = count
+        .unwrap_or(DEFAULT_PREVIEW_COUNT)
+        .clamp(1, MAX_PREVIEW_COUNT);
+    Ok(schedule
+        .upcoming(Local::now(), count)
+        .iter()
+        .map(|t| t.to_rfc3339())
+        .collect())
+}
+
+impl TaskScheduler {
+    /// Loads persisted tasks and arms the enabled ones, catching up on occurrences
+    /// missed while the manager was closed.
+    pub async fn load(self: &Arc<Self>, app: AppHandle) {
+        let persisted: PersistedSchedule = tasks_path(&app)
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let mut state = self.state.lock().await;
+        state.disabled_profiles = persisted.disabled_profiles.into_iter().collect();
+        for task in persisted.tasks {
+            let from = task
+                .last_occurrence
+                .as_deref()
+                .or(task.created_at.as_deref())
+                .and_then(parse_time)
+                .unwrap_or_else(Local::now);
+            let token = self.arm(&app, &task, from);
+            state
+                .tasks
+                .insert(task.id.clone(), TaskEntry { task, token });
+        }
+    }
+
+    pub async fn tasks(&self, profile_id: Option<&str>) -> Vec<ScheduledTaskStatus> {
+        let state = self.state.lock().await;
+        let mut tasks: Vec<ScheduledTaskStatus> = state
+            .tasks
+            .values()
+            .filter(|entry| profile_id.is_none_or(|p| entry.task.profile_id == p))
+            .map(|entry| status(&state, &entry.task))
+            .collect();
+        tasks.sort_by(|a, b| {
+            (&a.task.profile_id, &a.task.name).cmp(&(&b.task.profile_id, &b.task.name))
+        });
+        tasks
+    }
+
+    /// Adds or replaces a task. Run history is kept when an existing task is edited.
+    pub async fn set_task(
+        self: &Arc<Self>,
+        app: &AppHandle,
+        mut task: ScheduledTask,
+    ) -> Result<ScheduledTaskStatus, String> {
+        validate_task(&task)?;
+        let status = {
+            let mut state = self.state.lock().await;
+            match state.tasks.remove(&task.id) {
+                Some(old) => {
+                    if let Some(token) = old.token {
+                        token.cancel();
+                    }
+                    task.created_at = old.task.created_at;
+                    task.last_occurrence = old.task.last_occurrence;
+                    task.last_run = old.task.last_run;
+                }
+                None => task.created_at = Some(Local::now().to_rfc3339()),
+            }
+            // An edited schedule starts from now rather than catching up under the new rules
+            let token = self.arm(app, &task, Local::now());
+            let status = status(&state, &task);
+            state
+                .tasks
+                .insert(task.id.clone(), TaskEntry { task, token });
+            status
+        };
+        self.save(app).await?;
+        Ok(status)
+    }
+
+    pub async fn remove_task(&self, app: &AppHandle, task_id: &str) -> Result<(), String> {
+        {
+            let mut state = self.state.lock().await;
+            let entry = state
+                .tasks
+                .remove(task_id)
+                .ok_or_else(|| format!("Scheduled task '{}' not found", task_id))?;
+            if let Some(token) = entry.token {
+                token.cancel();
+            }
+        }
+        self.save(app).await
+    }
+
+    /// Switches every task of a profile on or off without touching the tasks' own flags.
+    pub async fn set_profile_enabled(
+        &self,
+        app: &AppHandle,
+        profile_id: &str,
+        enabled: bool,
+    ) -> Result<(), String> {
+        {
+            let mut state = self.state.lock().await;
+            if enabled {
+                state.disabled_profiles.remove(profile_id);
+            } else {
+                state.disabled_profiles.insert(profile_id.to_string());
+            }
+        }
+        self.save(app).await
+    }
+
+    /// Runs a task immediately, whether or not it or its profile is enabled.
+    pub async fn run_now(&self, app: &AppHandle, task_id: &str) -> Result<TaskRun, String> {
+        let task = {
+            let state = self.state.lock().await;
+            state.tasks.get(task_id).map(|entry| entry.task.clone())
+        }
+        .ok_or_else(|| format!("Scheduled task '{}' not found", task_id))?;
+        self.run(app, &task, None)
+            .await
+            .ok_or_else(|| "Another task is already running for this profile".to_string())
+    }
+
+    /// Starts the loop for an enabled task; returns its cancellation token.
+    fn arm(
+        self: &Arc<Self>,
+        app: &AppHandle,
+        task: &ScheduledTask,
+        from: DateTime<Local>,
+    ) -> Option<CancellationToken> {
+        if !task.enabled {
+            return None;
+        }
+        let schedule = match CronSchedule::parse(&task.cron) {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                println!("Scheduled task {} not armed: {}", task.id, e);
+                return None;
+            }
+        };
+        let token = CancellationToken::new();
+        tauri::async_runtime::spawn(self.clone().task_loop(
+            app.clone(),
+            task.id.clone(),
+            schedule,
+            from,
+            token.clone(),
+        ));
+        Some(token)
+    }
+
+    async fn task_loop(
+        self: Arc<Self>,
+        app: AppHandle,
+        task_id: String,
+        schedule: CronSchedule,
+        mut from: DateTime<Local>,
+        token: CancellationToken,
+    ) {
+        loop {
+            let Some(occurrence) = schedule.next_after(from) else {
+                println!("Scheduled task {} has no future runs", task_id);
+                return;
+            };
+            let Some((task, profile_enabled)) = self.current(&task_id).await else {
+                return;
+            };
+            let due = occurrence + jitter(&task.id, occurrence, task.jitter_seconds);
+
+            if due > Local::now() && profile_enabled {
+                let _ = app.emit(
+                    "scheduled-task-upcoming",
+                    serde_json::json!({
+                        "profile_id": task.profile_id,
+                        "task_id": task.id,
+                        "name": task.name,
+                        "scheduled_for": occurrence.to_rfc3339(),
+                        "runs_at": due.to_rfc3339(),
+                    }),
+                );
+            }
+            while let Ok(remaining) = (due - Local::now()).to_std() {
+                if remaining.is_zero() {
+                    break;
+                }
+                tokio::select! {
+                    _ = token.cancelled() => return,
+                    _ = tokio::time::sleep(remaining.min(MAX_SLEEP_STEP)) => {}
+                }
+            }
+
+            // Re-read the task; the profile may have been switched off while sleeping
+            let Some((task, profile_enabled)) = self.current(&task_id).await else {
+                return;
+            };
+            // After a gap, every occurrence that has passed collapses onto the latest one
+            let now = Local::now();
+            let (mut occurrence, mut due) = (occurrence, due);
+            while let Some(next) = schedule.next_after(occurrence) {
+                let next_due = next + jitter(&task.id, next, task.jitter_seconds);
+                if next_due > now {
+                    break;
+                }
+                (occurrence, due) = (next, next_due);
+            }
+            let missed = (now - due)
+                .to_std()
+                .is_ok_and(|late| late > MISSED_RUN_GRACE);
+            let skip_reason = if !profile_enabled {
+                Some("profileDisabled")
+            } else if missed && task.missed_run == MissedRunPolicy::Skip {
+                Some("missed")
+            } else {
+                None
+            };
+
+            match skip_reason {
+                Some(reason) => self.skip(&app, &task, occurrence, reason).await,
+                None => {
+                    if self.run(&app, &task, Some(occurrence)).await.is_none() {
+                        self.skip(&app, &task, occurrence, "busy").await;
+                    }
+                }
+            }
+            from = occurrence;
+        }
+    }
+
+    async fn current(&self, task_id: &str) -> Option<(ScheduledTask, bool)> {
+        let state = self.state.lock().await;
+        state.tasks.get(task_id).map(|entry| {
+            (
+                entry.task.clone(),
+                !state.disabled_profiles.contains(&entry.task.profile_id),
+            )
+        })
+    }
+
+    /// Runs the task and records the result; `None` when the profile is busy.
+    async fn run(
+        &self,
+        app: &AppHandle,
+        task: &ScheduledTask,
+        occurrence: Option<DateTime<Local>>,
+    ) -> Option<TaskRun> {
+        if !self.busy.lock().unwrap().insert(task.profile_id.clone()) {
+            return None;
+        }
+        let started_at = Local::now().to_rfc3339();
+        let result = crate::run_scheduled_task(app, task).await;
+        self.busy.lock().unwrap().remove(&task.profile_id);
+
+        let (success, message) = match result {
+            Ok(message) => (true, message),
+            Err(e) => (false, e),
+        };
+        println!(
+            "Scheduled task {} ({}) finished: {}",
+            task.id, task.profile_id, message
+        );
+        let run = TaskRun {
+            scheduled_for: occurrence.map(|t| t.to_rfc3339()),
+            started_at,
+            finished_at: Local::now().to_rfc3339(),
+            success,
+            message,
+        };
+        self.record(app, &task.id, occurrence, Some(run.clone()))
+            .await;
+        let _ = app.emit(
+            "scheduled-task-finished",
+            serde_json::json!({
+                "profile_id": task.profile_id,
+                "task_id": task.id,
+                "name": task.name,
+                "run": run,
+            }),
+        );
+        Some(run)
+    }
+
+    async fn skip(
+        &self,
+        app: &AppHandle,
+        task: &ScheduledTask,
+        occurrence: DateTime<Local>,
+        reason: &str,
+    ) {
+        self.record(app, &task.id, Some(occurrence), None).await;
+        let _ = app.emit(
+            "scheduled-task-skipped",
+            serde_json::json!({
+                "profile_id": task.profile_id,
+                "task_id": task.id,
+                "name": task.name,
+                "scheduled_for": occurrence.to_rfc3339(),
+                "reason": reason,
+            }),
+        );
+    }
+
+    async fn record(
+        &self,
+        app: &AppHandle,
+        task_id: &str,
+        occurrence: Option<DateTime<Local>>,
+        run: Option<TaskRun>,
+    ) {
+        {
+            let mut state = self.state.lock().await;
+            let Some(entry) = state.tasks.get_mut(task_id) else {
+                return;
+            };
+            if let Some(occurrence) = occurrence {
+                entry.task.last_occurrence = Some(occurrence.to_rfc3339());
+            }
+            if run.is_some() {
+                entry.task.last_run = run;
+            }
+        }
+        if let Err(e) = self.save(app).await {
+            println!("Failed to save scheduled tasks: {}", e);
+        }
+    }
+
+    async fn save(&self, app: &AppHandle) -> Result<(), String> {
+        let path = tasks_path(app).ok_or("Could not resolve app data dir")?;
+        let persisted = {
+            let state = self.state.lock().await;
+            let mut tasks: Vec<ScheduledTask> = state
+                .tasks
+                .values()
+                .map(|entry| entry.task.clone())
+                .collect();
+            tasks.sort_by(|a, b| a.id.cmp(&b.id));
+            let mut disabled_profiles: Vec<String> =
+                state.disabled_profiles.iter().cloned().collect();
+            disabled_profiles.sort();
+            PersistedSchedule {
+                tasks,
+                disabled_profiles,
+            }
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(&persisted).map_err(|e| e.to_string())?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content).map_err(|e| e.to_string())?;
+        fs::rename(&tmp_path, &path).map_err(|e| e.to_string())
+    }
+}
+
+fn status(state: &SchedulerState, task: &ScheduledTask) -> ScheduledTaskStatus {
+    let profile_enabled = !state.disabled_profiles.contains(&task.profile_id);
+    let next_run = task
+        .enabled
+        .then(|| CronSchedule::parse(&task.cron).ok())
+        .flatten()
+        .and_then(|schedule| schedule.next_after(Local::now()))
+        .map(|t| (t + jitter(&task.id, t, task.jitter_seconds)).to_rfc3339());
+    ScheduledTaskStatus {
+        task: task.clone(),
+        profile_enabled,
+        next_run,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn occurrence(timestamp: i64) -> DateTime<Local> {
+        Local.timestamp_opt(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn jitter_is_stable_for_an_occurrence() {
+        // 2024-05-01 04:00:00 UTC
+        let at = occurrence(1_714_536_000);
+        let offset = jitter("nightly-restart", at, 900);
+        assert_eq!(offset, jitter("nightly-restart", at, 900));
+        // Pinned, so a change of hash or derivation that would move armed runs shows up
+        assert_eq!(offset.num_seconds(), 54);
+        assert_eq!(jitter("nightly-restart", at, 0), chrono::Duration::zero());
+    }
+
+    #[test]
+    fn jitter_spreads_occurrences_within_the_bound() {
+        let offsets: HashSet<i64> = (0..50)
+            .map(|day| occurrence(1_714_536_000 + day * 86_400))
+            .map(|at| jitter("nightly-restart", at, 900).num_seconds())
+            .collect();
+        assert!(offsets.iter().all(|s| (0..=900).contains(s)));
+        assert!(offsets.len() > 40, "{:?}", offsets);
+        let at = occurrence(1_714_536_000);
+        assert_ne!(
+            jitter("nightly-restart", at, 900),
+            jitter("hourly-save", at, 900)
+        );
+    }
+}
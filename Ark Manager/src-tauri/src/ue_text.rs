@@ -0,0 +1,388 @@
+// src-tauri/src/ue_text.rs
+//
+// Parser and printer for Unreal's property text format, as used by structured Game.ini
+// values such as `ConfigOverrideSupplyCrateItems=(SupplyCrateClassString="...",ItemSets=((...)))`.
+// A parenthesised list of `Name=Value` pairs is a struct, a list of bare values is an
+// array, and anything else is a quoted string or a bare token (number, bool, name,
+// class reference).
+//
+// `UeValue` serializes to tagged JSON for the UI, e.g. `{"type":"bare","value":"1.0"}`,
+// `{"type":"quoted","value":"..."}`, `{"type":"array","items":[...]}` and
+// `{"type":"struct","fields":[{"name":"...","value":{...}}]}`, so a value read back from
+// the UI prints exactly as it was parsed: bare tokens stay unquoted and struct fields
+// keep their original order.
+
+use crate::ini::IniSection;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UeValue {
+    Struct(Vec<(String, UeValue)>),
+    Array(Vec<UeValue>),
+    Quoted(String),
+    Bare(String),
+}
+
+/// `PerLevelStatsMultiplier_Player[3]` -> ("PerLevelStatsMultiplier_Player", Some(3)).
+pub fn split_indexed_key(key: &str) -> (&str, Option<u32>) {
+    if let Some(open) = key.find('[') {
+        if let Some(inner) = key[open + 1..].strip_suffix(']') {
+            if let Ok(index) = inner.trim().parse() {
+                return (&key[..open], Some(index));
+            }
+        }
+    }
+    (key, None)
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredEntry {
+    pub key: String,
+    pub name: String,
+    pub index: Option<u32>,
+    pub value: UeValue,
+}
+
+#[derive(serde::Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredSection {
+    pub name: String,
+    pub entries: Vec<StructuredEntry>,
+}
+
+/// Parses every value of an INI section. Values that are not valid property text
+/// (e.g. a session name containing commas) are kept as a single bare token.
+pub fn structure_section(section: &IniSection) -> StructuredSection {
+    StructuredSection {
+        name: section.name.clone(),
+        entries: section
+            .entries
+            .iter()
+            .map(|entry| {
+                let (name, index) = split_indexed_key(&entry.key);
+                StructuredEntry {
+                    key: entry.key.clone(),
+                    name: name.to_string(),
+                    index,
+                    value: parse(&entry.value)
+                        .unwrap_or_else(|_| UeValue::Bare(entry.value.clone())),
+                }
+            })
+            .collect(),
+    }
+}
+
+pub fn parse(text: &str) -> Result<UeValue, String> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+    };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}", message, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<UeValue, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('(') => self.parse_group(),
+            Some('"') => self.parse_quoted().map(UeValue::Quoted),
+            _ => Ok(UeValue::Bare(self.parse_bare())),
+        }
+    }
+
+    fn parse_quoted(&mut self) -> Result<String, String> {
+        self.pos += 1; // opening quote
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('n') => out.push('\n'),
+                        Some('t') => out.push('\t'),
+                        Some(c) => out.push(c),
+                        None => return Err(self.error("unterminated string")),
+                    }
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// A bare token runs until the next separator outside of single quotes, so class
+    /// references like `BlueprintGeneratedClass'/Game/A.B_C'` stay in one piece.
+    fn parse_bare(&mut self) -> String {
+        let start = self.pos;
+        let mut in_single_quote = false;
+        while let Some(c) = self.peek() {
+            match c {
+                '\'' => in_single_quote = !in_single_quote,
+                ',' | ')' | '(' if !in_single_quote => break,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+        self.chars[start..self.pos]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    /// Looks ahead for `Name=` or `Name[3]=`; returns the key and moves past the '='.
+    fn try_parse_key(&mut self) -> Option<String> {
+        let start = self.pos;
+        let mut end = self.pos;
+        while self
+            .chars
+            .get(end)
+            .is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '[' || *c == ']')
+        {
+            end += 1;
+        }
+        let key: String = self.chars[start..end].iter().collect();
+        let mut after = end;
+        while self.chars.get(after).is_some_and(|c| c.is_whitespace()) {
+            after += 1;
+        }
+        if key.is_empty() || self.chars.get(after) != Some(&'=') {
+            return None;
+        }
+        self.pos = after + 1;
+        Some(key)
+    }
+
+    fn parse_group(&mut self) -> Result<UeValue, String> {
+        self.pos += 1; // '('
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.pos += 1;
+            return Ok(UeValue::Array(vec![]));
+        }
+
+        let mut fields = vec![];
+        let mut items = vec![];
+        loop {
+            self.skip_whitespace();
+            match self.try_parse_key() {
+                Some(key) if items.is_empty() => fields.push((key, self.parse_value()?)),
+                None if fields.is_empty() => items.push(self.parse_value()?),
+                _ => return Err(self.error("mixed struct fields and array elements")),
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(_) => return Err(self.error("expected ',' or ')'")),
+                None => return Err(self.error("missing ')'")),
+            }
+        }
+
+        Ok(if fields.is_empty() {
+            UeValue::Array(items)
+        } else {
+            UeValue::Struct(fields)
+        })
+    }
+}
+
+impl fmt::Display for UeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UeValue::Struct(fields) => {
+                write!(f, "(")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}={}", key, value)?;
+                }
+                write!(f, ")")
+            }
+            UeValue::Array(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+            UeValue::Quoted(s) => {
+                write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+            UeValue::Bare(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Borrowed JSON shape of a `UeValue`. The UI gets an explicit `type` tag so bare
+/// tokens (names, enums, numbers) never come back quoted, and structs carry an
+/// ordered field list because JSON objects lose their key order.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum TaggedRef<'a> {
+    Struct { fields: Vec<FieldRef<'a>> },
+    Array { items: &'a [UeValue] },
+    Quoted { value: &'a str },
+    Bare { value: &'a str },
+}
+
+#[derive(Serialize)]
+struct FieldRef<'a> {
+    name: &'a str,
+    value: &'a UeValue,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Tagged {
+    Struct { fields: Vec<Field> },
+    Array { items: Vec<UeValue> },
+    Quoted { value: String },
+    Bare { value: String },
+}
+
+#[derive(Deserialize)]
+struct Field {
+    name: String,
+    value: UeValue,
+}
+
+impl Serialize for UeValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let tagged = match self {
+            UeValue::Struct(fields) => TaggedRef::Struct {
+                fields: fields
+                    .iter()
+                    .map(|(name, value)| FieldRef { name, value })
+                    .collect(),
+            },
+            UeValue::Array(items) => TaggedRef::Array { items },
+            UeValue::Quoted(value) => TaggedRef::Quoted { value },
+            UeValue::Bare(value) => TaggedRef::Bare { value },
+        };
+        tagged.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for UeValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Tagged::deserialize(deserializer)? {
+            Tagged::Struct { fields } => UeValue::Struct(
+                fields
+                    .into_iter()
+                    .map(|field| (field.name, field.value))
+                    .collect(),
+            ),
+            Tagged::Array { items } => UeValue::Array(items),
+            Tagged::Quoted { value } => UeValue::Quoted(value),
+            Tagged::Bare { value } => UeValue::Bare(value),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Taken from real Game.ini files
+    const GAME_INI_SAMPLES: [&str; 6] = [
+        r#"(SupplyCrateClassString="SupplyCrate_Level03_C",MinItemSets=1,MaxItemSets=1,NumItemSetsPower=1.0,bSetsRandomWithoutReplacement=true,ItemSets=((MinNumItems=1,MaxNumItems=1,NumItemsPower=1.0,SetWeight=1.0,bItemsRandomWithoutReplacement=true,ItemEntries=((EntryWeight=1.0,ItemClassStrings=("PrimalItemResource_Metal_C"),ItemsWeights=(1.0),MinQuantity=10,MaxQuantity=20,MinQuality=1.0,MaxQuality=1.0,bForceBlueprint=false,ChanceToBeBlueprintOverride=0.0)))))"#,
+        r#"(ItemClassString="PrimalItemResource_Stone_C",Quantity=(MaxItemQuantity=1000,bIgnoreMultiplier=true))"#,
+        r#"(DinoNameTag=Rex,SpawnWeightMultiplier=0.5,OverrideSpawnLimitPercentage=True,SpawnLimitPercentage=0.1)"#,
+        r#"(NPCSpawnEntriesContainerClassString="DinoSpawnEntriesBeach_C",NPCSpawnEntries=((AnEntryName="Dodo",EntryWeight=0.2,NPCsToSpawnStrings=("Dodo_Character_BP_C"))),NPCSpawnLimits=((NPCClassString="Dodo_Character_BP_C",MaxPercentageOfDesiredNumToAllow=0.1)))"#,
+        r#"(ItemClassString="PrimalItemAmmo_ArrowStone_C",BaseCraftingResourceRequirements=((ResourceItemTypeString="PrimalItemResource_Wood_C",BaseResourceRequirement=1.0,bCraftingRequireExactResourceType=False)))"#,
+        r#"(SupplyCrateClassString=SupplyCrate_Level03_C,MinItemSets=1)"#,
+    ];
+
+    #[test]
+    fn game_ini_values_print_as_parsed() {
+        for sample in GAME_INI_SAMPLES {
+            let value = parse(sample).unwrap();
+            let printed = value.to_string();
+            assert_eq!(printed, sample);
+            assert_eq!(parse(&printed).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn game_ini_values_survive_a_json_round_trip() {
+        for sample in GAME_INI_SAMPLES {
+            let value = parse(sample).unwrap();
+            let json = serde_json::to_value(&value).unwrap();
+            let back: UeValue = serde_json::from_value(json).unwrap();
+            assert_eq!(back, value);
+            assert_eq!(back.to_string(), sample);
+        }
+    }
+
+    #[test]
+    fn bare_names_stay_bare_through_json() {
+        let value = parse("(SupplyCrateClassString=SupplyCrate_Level03_C)").unwrap();
+        let json = serde_json::to_value(&value).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "struct",
+                "fields": [{
+                    "name": "SupplyCrateClassString",
+                    "value": { "type": "bare", "value": "SupplyCrate_Level03_C" },
+                }],
+            })
+        );
+        let back: UeValue = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            back.to_string(),
+            "(SupplyCrateClassString=SupplyCrate_Level03_C)"
+        );
+    }
+
+    #[test]
+    fn struct_fields_keep_their_order_through_json() {
+        let text = r#"(ZName="a",AName=1,MName=(2,3))"#;
+        let json = serde_json::to_value(parse(text).unwrap()).unwrap();
+        let back: UeValue = serde_json::from_value(json).unwrap();
+        assert_eq!(back.to_string(), text);
+    }
+}
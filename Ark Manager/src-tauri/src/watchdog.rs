@@ -0,0 +1,261 @@
+// src-tauri/src/watchdog.rs
+//
+// Classifies why a server process exited and decides whether a crashed server
+// should be restarted, giving up once it crashes too often within a time window.
+
+use std::collections::VecDeque;
+use std::process::ExitStatus;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+
+pub const DEFAULT_MAX_CRASHES: u32 = 3;
+pub const DEFAULT_CRASH_WINDOW_SECS: u64 = 600;
+
+#[derive(serde::Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum StopReason {
+    /// The manager asked the server to stop (`stop_ark_server`).
+    Requested,
+    /// The server exited cleanly on its own, e.g. `DoExit` from an in-game admin.
+    Exited,
+    /// The server terminated with a failure exit code.
+    Crashed,
+    /// The process was killed from outside the manager.
+    KilledExternally,
+    /// The process is gone but there is no exit status to tell why, as with adopted
+    /// servers. Never restarted: a clean `DoExit` looks exactly the same.
+    Unknown,
+}
+
+/// A server process the watchdog can wait on.
+pub enum WatchedProcess {
+    /// Spawned by this manager instance.
+    Child(tokio::process::Child),
+    /// Adopted after a manager restart; it can only be polled by PID.
+    Adopted(u32),
+}
+
+impl WatchedProcess {
+    /// Waits for the process to exit. Adopted processes have no exit status.
+    pub async fn wait(&mut self) -> Option<std::io::Result<ExitStatus>> {
+        match self {
+            WatchedProcess::Child(child) => Some(child.wait().await),
+            WatchedProcess::Adopted(pid) => {
+                let pid = Pid::from_u32(*pid);
+                let mut sys = System::new();
+                while sys.refresh_process(pid) {
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Without an exit status (adopted processes) an unrequested stop is `Unknown`.
+pub fn classify_exit(
+    stop_requested: bool,
+    status: Option<&std::io::Result<ExitStatus>>,
+) -> StopReason {
+    if stop_requested {
+        return StopReason::Requested;
+    }
+
+    let status = match status {
+        Some(Ok(status)) => status,
+        Some(Err(_)) | None => return StopReason::Unknown,
+    };
+
+    match status.code() {
+        Some(0) => StopReason::Exited,
+        // On Windows a forced kill (`taskkill /F`, "End task") also leaves a plain failure
+        // code, 1, which is just as often a real crash, so it gets restarted like one
+        Some(_) => StopReason::Crashed,
+        // No exit code means the process was terminated by a signal
+        None => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::ExitStatusExt;
+                // SIGKILL and SIGTERM come from someone else; anything else (SIGSEGV, SIGABRT...) is a crash
+                match status.signal() {
+                    Some(9) | Some(15) => StopReason::KilledExternally,
+                    _ => StopReason::Crashed,
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                StopReason::KilledExternally
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RestartPolicy {
+    pub enabled: bool,
+    pub max_crashes: u32,
+    pub window: Duration,
+}
+
+impl RestartPolicy {
+    pub fn new(
+        enabled: Option<bool>,
+        max_crashes: Option<u32>,
+        crash_window_seconds: Option<u64>,
+    ) -> Self {
+        RestartPolicy {
+            enabled: enabled.unwrap_or(true),
+            max_crashes: max_crashes.unwrap_or(DEFAULT_MAX_CRASHES).max(1),
+            window: Duration::from_secs(crash_window_seconds.unwrap_or(DEFAULT_CRASH_WINDOW_SECS)),
+        }
+    }
+}
+
+/// Sliding window of recent crash times for one profile.
+pub struct CrashTracker {
+    policy: RestartPolicy,
+    crashes: VecDeque<Instant>,
+}
+
+impl CrashTracker {
+    pub fn new(policy: RestartPolicy) -> Self {
+        CrashTracker {
+            policy,
+            crashes: VecDeque::new(),
+        }
+    }
+
+    /// Records a crash and returns the number of crashes inside the current window.
+    pub fn record_crash(&mut self) -> u32 {
+        self.record_crash_at(Instant::now())
+    }
+
+    fn record_crash_at(&mut self, now: Instant) -> u32 {
+        self.crashes.push_back(now);
+        while let Some(first) = self.crashes.front() {
+            if now.duration_since(*first) > self.policy.window {
+                self.crashes.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.crashes.len() as u32
+    }
+
+    pub fn should_restart(&self, crash_count: u32) -> bool {
+        self.policy.enabled && crash_count < self.policy.max_crashes
+    }
+
+    pub fn policy(&self) -> &RestartPolicy {
+        &self.policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn exit(code: i32) -> std::io::Result<ExitStatus> {
+        use std::os::unix::process::ExitStatusExt;
+        Ok(ExitStatus::from_raw(code << 8))
+    }
+
+    #[cfg(unix)]
+    fn signalled(signal: i32) -> std::io::Result<ExitStatus> {
+        use std::os::unix::process::ExitStatusExt;
+        Ok(ExitStatus::from_raw(signal))
+    }
+
+    #[test]
+    fn requested_stops_win_over_the_exit_status() {
+        assert_eq!(classify_exit(true, None), StopReason::Requested);
+        #[cfg(unix)]
+        assert_eq!(classify_exit(true, Some(&exit(3))), StopReason::Requested);
+    }
+
+    #[test]
+    fn exits_without_a_status_are_unknown() {
+        assert_eq!(classify_exit(false, None), StopReason::Unknown);
+        let failed_wait = Err(std::io::Error::other("wait failed"));
+        assert_eq!(
+            classify_exit(false, Some(&failed_wait)),
+            StopReason::Unknown
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn exit_code_one_is_a_crash_on_windows() {
+        use std::os::windows::process::ExitStatusExt;
+        let status = Ok(ExitStatus::from_raw(1));
+        assert_eq!(classify_exit(false, Some(&status)), StopReason::Crashed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exit_codes_and_signals_are_classified() {
+        assert_eq!(classify_exit(false, Some(&exit(0))), StopReason::Exited);
+        assert_eq!(classify_exit(false, Some(&exit(1))), StopReason::Crashed);
+        assert_eq!(classify_exit(false, Some(&exit(139))), StopReason::Crashed);
+        assert_eq!(
+            classify_exit(false, Some(&signalled(9))),
+            StopReason::KilledExternally
+        );
+        assert_eq!(
+            classify_exit(false, Some(&signalled(15))),
+            StopReason::KilledExternally
+        );
+        assert_eq!(
+            classify_exit(false, Some(&signalled(11))),
+            StopReason::Crashed
+        );
+        assert_eq!(
+            classify_exit(false, Some(&signalled(6))),
+            StopReason::Crashed
+        );
+    }
+
+    #[test]
+    fn restart_policy_defaults() {
+        let policy = RestartPolicy::new(None, None, None);
+        assert!(policy.enabled);
+        assert_eq!(policy.max_crashes, DEFAULT_MAX_CRASHES);
+        assert_eq!(
+            policy.window,
+            Duration::from_secs(DEFAULT_CRASH_WINDOW_SECS)
+        );
+        assert_eq!(RestartPolicy::new(None, Some(0), None).max_crashes, 1);
+    }
+
+    #[test]
+    fn crash_tracker_stops_restarting_after_max_crashes_in_the_window() {
+        let mut tracker = CrashTracker::new(RestartPolicy::new(None, Some(3), Some(600)));
+        let start = Instant::now();
+        let counts: Vec<u32> = (0..3)
+            .map(|i| tracker.record_crash_at(start + Duration::from_secs(i * 60)))
+            .collect();
+        assert_eq!(counts, [1, 2, 3]);
+        assert!(tracker.should_restart(2));
+        assert!(!tracker.should_restart(3));
+    }
+
+    #[test]
+    fn crash_tracker_forgets_crashes_outside_the_window() {
+        let mut tracker = CrashTracker::new(RestartPolicy::new(None, Some(3), Some(600)));
+        let start = Instant::now();
+        tracker.record_crash_at(start);
+        tracker.record_crash_at(start + Duration::from_secs(300));
+        assert_eq!(tracker.record_crash_at(start + Duration::from_secs(700)), 2);
+        assert_eq!(
+            tracker.record_crash_at(start + Duration::from_secs(1400)),
+            1
+        );
+    }
+
+    #[test]
+    fn disabled_policy_never_restarts() {
+        let tracker = CrashTracker::new(RestartPolicy::new(Some(false), None, None));
+        assert!(!tracker.should_restart(0));
+    }
+}